@@ -0,0 +1,18 @@
+//! Build a random ZObject tree from the input, evaluate it over a small global context, and check evaluation never panics,
+//! respects the ExecutionContext limits, and can be replayed if it fails.
+#![no_main]
+
+use interpreter2::{
+    GlobalContext, RcI,
+    fuzzing::{ByteSource, build_tree, check_evaluation_properties, fuzzing_global_context},
+};
+use libfuzzer_sys::fuzz_target;
+
+thread_local! {
+    static GLOBAL_CONTEXT: RcI<GlobalContext> = RcI::new(fuzzing_global_context());
+}
+
+fuzz_target!(|data: &[u8]| {
+    let tree = build_tree(&mut ByteSource::new(data));
+    GLOBAL_CONTEXT.with(|global_context| check_evaluation_properties(tree, global_context.clone()));
+});
@@ -0,0 +1,8 @@
+//! Arbitrary JSON must never make parse_value panic, only return a LoadError.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    interpreter2::fuzzing::check_parse_json(data);
+});
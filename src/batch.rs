@@ -0,0 +1,202 @@
+//! Evaluate ZObjects (usually Z7 function calls) stored as JSON Lines, one per line, as used for regression corpora.
+//!
+//! Each line get a record in the output JSON Lines, in the same order, that can later be used as the expected output.
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, Write},
+    time::Instant,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ExecutionContext,
+    data_types::WfDataType,
+    parsing::{parse_json::parse_value, serialize_json::SerializeWfData},
+    util::catch_panic,
+};
+
+/// The result of evaluating one line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchRecord {
+    /// 1-indexed line number in the input
+    pub line: usize,
+    /// In microseconds, as an integer so it is written and read back exactly
+    pub duration_us: u64,
+    /// The evaluated value, if the evaluation succeeded
+    pub output: Option<serde_json::Value>,
+    /// `InvalidRequest` if the line couldn’t be parsed, otherwise the name of the `EvalErrorKind`
+    pub error_kind: Option<String>,
+    pub message: Option<String>,
+    pub trace: Option<String>,
+}
+
+impl BatchRecord {
+    /// Whether both have the same output or error. Timing and trace are ignored.
+    pub fn same_outcome(&self, other: &Self) -> bool {
+        self.output == other.output
+            && self.error_kind == other.error_kind
+            && self.message == other.message
+    }
+}
+
+/// Parse and evaluate a line, in a fresh context (so function call counters are not shared between lines)
+pub fn evaluate_line(line: &str, line_number: usize, context: &ExecutionContext) -> BatchRecord {
+    let start = Instant::now();
+    let mut record = BatchRecord {
+        line: line_number,
+        duration_us: 0,
+        output: None,
+        error_kind: None,
+        message: None,
+        trace: None,
+    };
+
+    let parsed = sonic_rs::from_str(line)
+        .map_err(|e| e.to_string())
+        .and_then(|value| parse_value(&value).map_err(|e| e.to_string()));
+    match parsed {
+        Err(message) => {
+            record.error_kind = Some("InvalidRequest".to_string());
+            record.message = Some(message);
+        }
+        Ok(data) => {
+            let line_context = context.new_fresh();
+            match catch_panic(|| data.evaluate(&line_context).map_err(|(e, _)| e)) {
+                Ok(value) => {
+                    record.output = Some(
+                        serde_json::to_value(SerializeWfData(&value))
+                            .expect("serializing WfData shouldn’t fail"),
+                    )
+                }
+                Err(e) => {
+                    record.error_kind = Some(e.get_kind().name().to_string());
+                    record.message = Some(e.get_kind().to_string());
+                    record.trace = Some(e.render_trace());
+                }
+            }
+        }
+    }
+
+    record.duration_us = start.elapsed().as_micros() as u64;
+    record
+}
+
+/// Evaluate every non-empty line of the reader, writing each record to the writer as soon as it is evaluated.
+pub fn run_batch<R: BufRead, W: Write>(
+    reader: R,
+    writer: &mut W,
+    context: &ExecutionContext,
+) -> Result<Vec<BatchRecord>, anyhow::Error> {
+    let mut records = Vec::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line.context("reading requests")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = evaluate_line(&line, line_number + 1, context);
+        serde_json::to_writer(&mut *writer, &record)?;
+        writer.write_all(b"\n")?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Read records, as written by `run_batch`
+pub fn load_records<R: BufRead>(reader: R) -> Result<Vec<BatchRecord>, anyhow::Error> {
+    let mut result = Vec::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line.context("reading records")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        result.push(
+            serde_json::from_str(&line)
+                .with_context(|| format!("parsing line {} of records", line_number + 1))?,
+        );
+    }
+    Ok(result)
+}
+
+/// A line whose outcome differ from the expected one. None if the line is only present on one side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchDifference {
+    pub line: usize,
+    pub expected: Option<BatchRecord>,
+    pub actual: Option<BatchRecord>,
+}
+
+/// Compare records by line number, with `BatchRecord::same_outcome`
+pub fn diff_records(expected: &[BatchRecord], actual: &[BatchRecord]) -> Vec<BatchDifference> {
+    let mut by_line: BTreeMap<usize, (Option<&BatchRecord>, Option<&BatchRecord>)> =
+        BTreeMap::new();
+    for record in expected {
+        by_line.entry(record.line).or_default().0 = Some(record);
+    }
+    for record in actual {
+        by_line.entry(record.line).or_default().1 = Some(record);
+    }
+
+    by_line
+        .into_iter()
+        .filter(|(_, records)| match records {
+            (Some(expected), Some(actual)) => !expected.same_outcome(actual),
+            _ => true,
+        })
+        .map(|(line, (expected, actual))| BatchDifference {
+            line,
+            expected: expected.cloned(),
+            actual: actual.cloned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExecutionContext, RcI,
+        batch::{diff_records, load_records, run_batch},
+    };
+
+    #[test]
+    fn test_run_batch() {
+        let context = ExecutionContext::default_for_global(RcI::new(
+            crate::fuzzing::fuzzing_global_context(),
+        ));
+        let input = [
+            r#"{"Z1K1": "Z7", "Z7K1": "Z10001", "Z10001K1": "Z41"}"#,
+            "",
+            r#"{"Z1K1": "Z7", "Z7K1": "Z10001", "Z10001K1": "Z12345"}"#,
+            "{",
+        ]
+        .join("\n");
+
+        let mut output = Vec::new();
+        let records = run_batch(input.as_bytes(), &mut output, &context).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].output, Some(serde_json::json!("Z42")));
+        assert_eq!(records[1].line, 3);
+        assert_eq!(
+            records[1].error_kind.as_deref(),
+            Some("MissingPersistentObject")
+        );
+        assert!(records[1].trace.is_some());
+        assert_eq!(records[2].error_kind.as_deref(), Some("InvalidRequest"));
+
+        // the output can be read back, and used as the expected output
+        let expected = load_records(&output[..]).unwrap();
+        assert_eq!(expected, records);
+        assert!(diff_records(&expected, &records).is_empty());
+
+        let mut changed = records.clone();
+        changed[0].output = Some(serde_json::json!("Z41"));
+        changed[0].duration_us += 1;
+        changed.pop();
+        let differences = diff_records(&expected, &changed);
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[0].line, 1);
+        assert_eq!(differences[1].line, 4);
+        assert!(differences[1].actual.is_none());
+    }
+}
@@ -6,6 +6,11 @@ mod benches {
     use map_macro::btree_map;
     use test::Bencher;
 
+    use crate::{
+        ExecutionContext, GlobalContext, RcI,
+        data_types::{MaybeEvaluated, WfBoolean, WfData, WfDataType, WfString, WfTypedList},
+    };
+
     fn create_test_data<T: Clone, F: Fn() -> T>(f: F) -> Vec<T> {
         let mut source_vec: Vec<T> = Vec::with_capacity(1000);
         for _ in 0..5 {
@@ -73,6 +78,51 @@ mod benches {
         });
     }
 
+    // The following benches use RcI, and so measure Rc or Arc depending on whether the `multithread` feature is enabled.
+    // Compare `cargo bench` with `cargo bench --features multithread` to see the cost of the switch.
+
+    fn boolean_equality_call() -> WfData {
+        WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(7)),
+            keyindex!(7, 1) => WfData::new_reference(zid!(844)),
+            keyindex!(844, 1) => WfData::new_reference(zid!(41)),
+            keyindex!(844, 2) => WfData::new_reference(zid!(42)),
+        })
+    }
+
+    #[bench]
+    fn clone_1000_wf_data_rci(b: &mut Bencher) {
+        bench_clone(|| WfString::new("hello").into_wf_data(), b);
+    }
+
+    #[bench]
+    fn clone_1000_wf_data_untyped(b: &mut Bencher) {
+        bench_clone(boolean_equality_call, b);
+    }
+
+    #[bench]
+    fn evaluate_boolean_equality(b: &mut Bencher) {
+        let global_context = RcI::new(GlobalContext::default_for_test());
+        let call = boolean_equality_call();
+        b.iter(|| {
+            let context = ExecutionContext::default_for_global(global_context.clone());
+            test::black_box(call.clone().evaluate(&context).unwrap());
+        });
+    }
+
+    #[bench]
+    fn iterate_typed_list_1000(b: &mut Bencher) {
+        let list = WfTypedList::new(
+            MaybeEvaluated::Unchecked(WfData::new_reference(zid!(40))),
+            vec![WfBoolean::new(true).into_wf_data(); 1000],
+        );
+        b.iter(|| {
+            for entry in test::black_box(&list).iter() {
+                test::black_box(entry);
+            }
+        });
+    }
+
     #[bench]
     fn create_1000_arc(b: &mut Bencher) {
         b.iter(|| {
@@ -0,0 +1,114 @@
+//! Evaluate ZObjects (usually Z7 function calls) from a JSON Lines file, one per line, and write the results as JSON Lines.
+//!
+//! Usage: `batch_evaluate [--dump PATH | --snapshot PATH] [--output PATH] [--expected PATH] [INPUT]`
+//!
+//! Without INPUT, the requests are read from the standard input. Results are written in the same order as the
+//! requests, to the standard output if `--output` isn’t provided.
+//!
+//! If an expected output (a previous result file) is provided, exit with a non-zero code if any outcome differ from it.
+use anyhow::{Context, bail};
+use interpreter2::{
+    ExecutionContext, GlobalContext, RcI,
+    batch::{diff_records, load_records, run_batch},
+};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    process::ExitCode,
+};
+
+struct Arguments {
+    dump: Option<String>,
+    snapshot: Option<String>,
+    output: Option<String>,
+    expected: Option<String>,
+    input: Option<String>,
+}
+
+fn parse_arguments() -> Result<Arguments, anyhow::Error> {
+    let mut result = Arguments {
+        dump: None,
+        snapshot: None,
+        output: None,
+        expected: None,
+        input: None,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dump" => result.dump = Some(args.next().context("--dump expect a path")?),
+            "--snapshot" => {
+                result.snapshot = Some(args.next().context("--snapshot expect a path")?)
+            }
+            "--output" => result.output = Some(args.next().context("--output expect a path")?),
+            "--expected" => {
+                result.expected = Some(args.next().context("--expected expect a path")?)
+            }
+            other if other.starts_with("--") => bail!("unknown argument {:?}", other),
+            other => {
+                if result.input.is_some() {
+                    bail!("only one input file can be provided");
+                }
+                result.input = Some(other.to_string())
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn main() -> Result<ExitCode, anyhow::Error> {
+    let arguments = parse_arguments()?;
+
+    let (global_context, _) = GlobalContext::load_for_cli(
+        arguments.dump.as_deref(),
+        arguments.snapshot.as_deref(),
+        false,
+    )?;
+    let context = ExecutionContext::default_for_global(RcI::new(global_context));
+
+    let input: Box<dyn BufRead> = match &arguments.input {
+        Some(path) => Box::new(BufReader::new(
+            File::open(path).context("opening the requests")?,
+        )),
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let records = match &arguments.output {
+        Some(path) => {
+            let mut writer = BufWriter::new(File::create(path).context("creating output")?);
+            let records = run_batch(input, &mut writer, &context)?;
+            writer.flush()?;
+            records
+        }
+        None => run_batch(input, &mut std::io::stdout().lock(), &context)?,
+    };
+
+    let succeeded = records.iter().filter(|r| r.output.is_some()).count();
+    eprintln!("{}/{} evaluated without error", succeeded, records.len());
+
+    if let Some(expected_path) = &arguments.expected {
+        let expected = load_records(BufReader::new(
+            File::open(expected_path).context("opening expected output")?,
+        ))?;
+        let differences = diff_records(&expected, &records);
+        for difference in &differences {
+            let describe = |record: &Option<interpreter2::batch::BatchRecord>| match record {
+                None => "missing".to_string(),
+                Some(record) => match (&record.output, &record.error_kind) {
+                    (Some(output), _) => output.to_string(),
+                    (None, Some(kind)) => format!("error {}", kind),
+                    (None, None) => "no result".to_string(),
+                },
+            };
+            eprintln!(
+                "DIFFERENCE at line {}: expected {}, got {}",
+                difference.line,
+                describe(&difference.expected),
+                describe(&difference.actual)
+            );
+        }
+        if !differences.is_empty() {
+            return Ok(ExitCode::FAILURE);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
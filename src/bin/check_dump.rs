@@ -0,0 +1,36 @@
+//! List the zobject pages of a dump that can’t be loaded, one per line as `TITLE<tab>PATH<tab>ERROR`, where PATH is
+//! where the conversion failed inside the page (empty if it failed for another reason, such as invalid JSON).
+//!
+//! Usage: `check_dump [--dump PATH]`
+//!
+//! Exit with a non-zero code if any page can’t be loaded.
+use anyhow::{Context, bail};
+use interpreter2::GlobalContext;
+use std::process::ExitCode;
+
+fn main() -> Result<ExitCode, anyhow::Error> {
+    let mut dump = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dump" => dump = Some(args.next().context("--dump expect a path")?),
+            other => bail!("unexpected argument {:?}", other),
+        }
+    }
+
+    let (_, failures) = GlobalContext::load_for_cli(dump.as_deref(), None, true)?;
+    for failure in &failures {
+        let path = if failure.path.is_empty() {
+            String::new()
+        } else {
+            failure.path_text()
+        };
+        println!("{}\t{}\t{:#}", failure.title, path, failure.error);
+    }
+    eprintln!("{} pages can’t be loaded", failures.len());
+    Ok(if failures.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
@@ -0,0 +1,73 @@
+//! Query the references between the objects of a dump. Pages that can’t be loaded are listed and skipped.
+//!
+//! Usage: `dependency_graph [--dump PATH] COMMAND [ZID...]`, where COMMAND is one of:
+//! - `dependencies ZID...`: the ZIDs, and every object they transitively reference, one per line
+//! - `dependents ZID...`: the ZIDs, and every object that transitively reference one of them, one per line
+//! - `cycles`: the groups of objects that reference each other (such as mutually recursive functions), one per line
+//! - `dot [ZID...]`: the graph in the DOT format of Graphviz, limited to the dependencies of the ZIDs if any are given
+use anyhow::{Context, bail};
+use interpreter2::{GlobalContext, Zid, dependency_graph::DependencyGraph};
+use std::io::{BufWriter, Write};
+
+fn main() -> Result<(), anyhow::Error> {
+    let mut dump = None;
+    let mut command = None;
+    let mut zids = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dump" => dump = Some(args.next().context("--dump expect a path")?),
+            other if command.is_none() => command = Some(other.to_string()),
+            other => {
+                zids.push(Zid::from_str(other).with_context(|| format!("parsing ZID {}", other))?)
+            }
+        }
+    }
+    let Some(command) = command else {
+        bail!("expected a command: dependencies, dependents, cycles or dot");
+    };
+    // checked before the (slow) loading of the dump
+    match command.as_str() {
+        "dependencies" | "dependents" if zids.is_empty() => {
+            bail!("{} expect at least one ZID", command)
+        }
+        "cycles" if !zids.is_empty() => bail!("cycles doesn’t take any ZID"),
+        "dependencies" | "dependents" | "cycles" | "dot" => (),
+        other => bail!("unknown command {:?}", other),
+    }
+
+    let (global_context, failures) = GlobalContext::load_for_cli(dump.as_deref(), None, true)?;
+    for failure in &failures {
+        eprintln!("skipped {}", failure);
+    }
+    let graph = DependencyGraph::new(&global_context);
+
+    let mut output = BufWriter::new(std::io::stdout().lock());
+    match command.as_str() {
+        "dependencies" => {
+            for zid in graph.dependencies(zids) {
+                writeln!(output, "{}", zid)?;
+            }
+        }
+        "dependents" => {
+            for zid in graph.dependents(zids) {
+                writeln!(output, "{}", zid)?;
+            }
+        }
+        "cycles" => {
+            for component in graph.strongly_connected_components() {
+                if component.len() > 1 {
+                    let zids: Vec<String> = component.iter().map(Zid::to_string).collect();
+                    writeln!(output, "{}", zids.join(" "))?;
+                }
+            }
+        }
+        "dot" => {
+            let only = (!zids.is_empty()).then(|| graph.dependencies(zids));
+            graph.write_dot(&mut output, only.as_ref())?;
+        }
+        _ => unreachable!("the command was checked before loading"),
+    }
+    output.flush()?;
+    Ok(())
+}
@@ -0,0 +1,117 @@
+//! Serve the orchestrator evaluate endpoint (`POST /1/v1/evaluate/`) on localhost.
+//!
+//! Usage: `evaluation_server [--dump PATH | --snapshot PATH] [--write-snapshot PATH] [--port N] [--max-depth N] [--max-calls N]`
+//!
+//! The request body is a Z7 function call (optionally in the `zobject` field), and the response a Z22 evaluation
+//! result. With `--write-snapshot`, the loaded objects are written as a snapshot (much faster to load than the dump),
+//! and the program exit without serving.
+use anyhow::{Context, bail};
+use interpreter2::{ExecutionContext, ExecutionLimits, GlobalContext, RcI, server::handle_request};
+use std::{fs::File, io::BufWriter};
+use tiny_http::{Header, Response, Server};
+
+struct Arguments {
+    dump: Option<String>,
+    snapshot: Option<String>,
+    write_snapshot: Option<String>,
+    port: u16,
+    limits: ExecutionLimits,
+}
+
+fn parse_arguments() -> Result<Arguments, anyhow::Error> {
+    let mut result = Arguments {
+        dump: None,
+        snapshot: None,
+        write_snapshot: None,
+        port: 6254,
+        limits: ExecutionLimits::default(),
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dump" => result.dump = Some(args.next().context("--dump expect a path")?),
+            "--snapshot" => {
+                result.snapshot = Some(args.next().context("--snapshot expect a path")?)
+            }
+            "--write-snapshot" => {
+                result.write_snapshot = Some(args.next().context("--write-snapshot expect a path")?)
+            }
+            "--port" => {
+                result.port = args
+                    .next()
+                    .context("--port expect a number")?
+                    .parse()
+                    .context("parsing --port")?
+            }
+            "--max-depth" => {
+                result.limits.max_function_call_depth = args
+                    .next()
+                    .context("--max-depth expect a number")?
+                    .parse()
+                    .context("parsing --max-depth")?
+            }
+            "--max-calls" => {
+                result.limits.max_function_call_count = args
+                    .next()
+                    .context("--max-calls expect a number")?
+                    .parse()
+                    .context("parsing --max-calls")?
+            }
+            other => bail!("unknown argument {:?}", other),
+        }
+    }
+    Ok(result)
+}
+
+fn main() -> anyhow::Result<()> {
+    let arguments = parse_arguments()?;
+
+    let (global_context, _) = GlobalContext::load_for_cli(
+        arguments.dump.as_deref(),
+        arguments.snapshot.as_deref(),
+        false,
+    )?;
+
+    if let Some(path) = &arguments.write_snapshot {
+        let file = BufWriter::new(File::create(path).context("creating the snapshot")?);
+        global_context
+            .write_snapshot(file)
+            .context("writing snapshot")?;
+        return Ok(());
+    }
+
+    let context = ExecutionContext::default_for_global(RcI::new(global_context))
+        .new_with_limits(arguments.limits);
+
+    let server = match Server::http(("127.0.0.1", arguments.port)) {
+        Ok(s) => s,
+        Err(e) => bail!("can’t listen on port {}: {}", arguments.port, e),
+    };
+    eprintln!("listening on http://127.0.0.1:{}", arguments.port);
+
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let response = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => handle_request(request.method().as_str(), request.url(), &body, &context),
+            Err(e) => interpreter2::server::Response {
+                status: 400,
+                body: format!("can’t read the request body: {}", e),
+            },
+        };
+        let content_type = if response.status == 200 {
+            "application/json"
+        } else {
+            "text/plain; charset=utf-8"
+        };
+        let http_response = Response::from_string(response.body)
+            .with_status_code(response.status)
+            .with_header(
+                Header::from_bytes("Content-Type", content_type)
+                    .expect("the header should be valid"),
+            );
+        if let Err(e) = request.respond(http_response) {
+            eprintln!("failed to respond: {}", e);
+        }
+    }
+    Ok(())
+}
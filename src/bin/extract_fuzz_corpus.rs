@@ -0,0 +1,51 @@
+//! Write the JSON of ZObject pages from the dump into a directory, one file per page, to seed the `parse_json` fuzz target.
+//!
+//! Usage: `extract_fuzz_corpus [--dump PATH] [--limit N] OUTPUT_DIRECTORY`
+use anyhow::{Context, bail};
+use interpreter2::{DEFAULT_DUMP_PATH, decompress};
+use std::{fs::File, io::BufReader, path::PathBuf};
+
+fn main() -> Result<(), anyhow::Error> {
+    let mut dump = DEFAULT_DUMP_PATH.to_string();
+    let mut limit = usize::MAX;
+    let mut output = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dump" => dump = args.next().context("--dump expect a path")?,
+            "--limit" => {
+                limit = args
+                    .next()
+                    .context("--limit expect a number")?
+                    .parse()
+                    .context("parsing --limit")?
+            }
+            other => output = Some(PathBuf::from(other)),
+        }
+    }
+    let Some(output) = output else {
+        bail!("expected an output directory");
+    };
+    std::fs::create_dir_all(&output).context("creating output directory")?;
+
+    let file = BufReader::new(File::open(&dump).context("opening dump")?);
+    let file = decompress(file).context("reading dump")?;
+    let mut written = 0;
+    for page in parse_mediawiki_dump_reboot::parse(file) {
+        if written >= limit {
+            break;
+        }
+        let page = match page {
+            Ok(page) => page,
+            Err(e) => bail!("reading dump: {:?}", e),
+        };
+        if page.model.as_deref() != Some("zobject") {
+            continue;
+        }
+        std::fs::write(output.join(format!("{}.json", page.title)), &page.text)
+            .with_context(|| format!("writing page {}", page.title))?;
+        written += 1;
+    }
+    eprintln!("wrote {} pages", written);
+    Ok(())
+}
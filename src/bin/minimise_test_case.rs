@@ -0,0 +1,87 @@
+//! Run the given test cases, and for each one failing with an error, print a minimised ZObject still reproducing it.
+//!
+//! Usage: `minimise_test_case [--dump PATH] [--max-attempts N] TEST_ZID...`
+//!
+//! The minimisation start from the root of the replay (the innermost data that fail to evaluate), so the output can be
+//! evaluated on its own. Test cases whose validator returned false are not minimised, as they do not fail with an error.
+use anyhow::{Context, bail};
+use interpreter2::{
+    EvalErrorKind, ExecutionContext, GlobalContext, RcI, Zid,
+    data_types::{WfDataType, WfTestCase},
+    minimise::minimise,
+    replay,
+    util::panic_message,
+};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+fn main() -> Result<(), anyhow::Error> {
+    let mut dump = None;
+    let mut max_attempts = 10_000;
+    let mut tests = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dump" => dump = Some(args.next().context("--dump expect a path")?),
+            "--max-attempts" => {
+                max_attempts = args
+                    .next()
+                    .context("--max-attempts expect a number")?
+                    .parse()
+                    .context("parsing --max-attempts")?
+            }
+            other => tests
+                .push(Zid::from_str(other).with_context(|| format!("parsing test ZID {}", other))?),
+        }
+    }
+    if tests.is_empty() {
+        bail!("expected at least one test case ZID");
+    }
+
+    let (global_context, _) = GlobalContext::load_for_cli(dump.as_deref(), None, false)?;
+    let global_context = RcI::new(global_context);
+
+    for test in tests {
+        let context = ExecutionContext::default_for_global(global_context.clone());
+        let test_case = match WfTestCase::load(test, &context) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("can’t load test case {}: {}", test, e);
+                continue;
+            }
+        };
+        let error = match test_case.clone().run_test(&context) {
+            Ok(()) => {
+                eprintln!("{} passed, nothing to minimise", test);
+                continue;
+            }
+            Err(e) => e,
+        };
+        if let EvalErrorKind::TestCaseFailedWithFalse(_) = error.get_kind() {
+            eprintln!("{} failed with false, nothing to minimise", test);
+            continue;
+        }
+        eprintln!("{} failed with: {}", test, error);
+
+        let root = match catch_unwind(AssertUnwindSafe(|| {
+            replay::generate_replay(test_case.into_wf_data(), &context, &error)
+        })) {
+            Ok(replay_info) => replay_info.root,
+            Err(payload) => {
+                eprintln!("replay panicked: {}", panic_message(&*payload));
+                continue;
+            }
+        };
+
+        match minimise(root, error.get_kind(), &context, max_attempts) {
+            Some(result) => {
+                eprintln!(
+                    "minimised {} in {} attempts ({} reductions)",
+                    test, result.attempts, result.reductions
+                );
+                println!("{}", result.to_json());
+            }
+            None => eprintln!("the replay root of {} does not reproduce the error", test),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! Interactive evaluation of ZObjects, keeping the global context loaded between them.
+//!
+//! Usage: `repl [--dump PATH | --snapshot PATH | --pages DIRECTORY | --fetch API_URL] [--lazy]`
+//!
+//! With `--lazy`, the dump or snapshot is only indexed, and each object is converted when first used. `--pages` (a
+//! directory of `Z12345.json` page files) and `--fetch` (the `api.php` URL of a wiki running WikiLambda, in plain HTTP)
+//! are always lazy.
+//!
+//! Type `:help` for the list of commands, and `:quit` (or end the input) to exit.
+use anyhow::{Context, bail};
+use interpreter2::{
+    DEFAULT_DUMP_PATH, ExecutionContext, GlobalContext, RcI,
+    object_source::{DirectorySource, DumpSource, HttpSource, SnapshotSource},
+    repl::Repl,
+};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Write},
+};
+
+struct Arguments {
+    dump: Option<String>,
+    snapshot: Option<String>,
+    pages: Option<String>,
+    fetch: Option<String>,
+    lazy: bool,
+}
+
+fn parse_arguments() -> Result<Arguments, anyhow::Error> {
+    let mut result = Arguments {
+        dump: None,
+        snapshot: None,
+        pages: None,
+        fetch: None,
+        lazy: false,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dump" => result.dump = Some(args.next().context("--dump expect a path")?),
+            "--snapshot" => {
+                result.snapshot = Some(args.next().context("--snapshot expect a path")?)
+            }
+            "--pages" => result.pages = Some(args.next().context("--pages expect a path")?),
+            "--fetch" => result.fetch = Some(args.next().context("--fetch expect a URL")?),
+            "--lazy" => result.lazy = true,
+            other => bail!("unknown argument {:?}", other),
+        }
+    }
+    Ok(result)
+}
+
+fn main() -> anyhow::Result<()> {
+    let arguments = parse_arguments()?;
+
+    eprintln!("loading...");
+    let global_context = if let Some(pages) = &arguments.pages {
+        GlobalContext::from_source(DirectorySource::new(pages))
+    } else if let Some(url) = &arguments.fetch {
+        GlobalContext::from_source(HttpSource::new(url).context("parsing --fetch")?)
+    } else if arguments.lazy {
+        if let Some(snapshot) = &arguments.snapshot {
+            let file = BufReader::new(File::open(snapshot).context("opening the snapshot")?);
+            GlobalContext::from_source(
+                SnapshotSource::from_reader(file).context("indexing snapshot")?,
+            )
+        } else {
+            let dump = arguments.dump.as_deref().unwrap_or(DEFAULT_DUMP_PATH);
+            let file = BufReader::new(File::open(dump).context("opening the dump")?);
+            GlobalContext::from_source(DumpSource::from_reader(file).context("indexing dump")?)
+        }
+    } else {
+        GlobalContext::load_for_cli(
+            arguments.dump.as_deref(),
+            arguments.snapshot.as_deref(),
+            false,
+        )?
+        .0
+    };
+    let mut repl = Repl::new(ExecutionContext::default_for_global(RcI::new(
+        global_context,
+    )));
+    eprintln!("ready, type :help for the list of commands");
+
+    let mut stdout = std::io::stdout();
+    let mut lines = std::io::stdin().lock().lines();
+    loop {
+        write!(stdout, "> ")?;
+        stdout.flush()?;
+        let line = match lines.next() {
+            Some(line) => line.context("reading input")?,
+            None => break,
+        };
+        if line.trim() == ":quit" {
+            break;
+        }
+        let output = repl.process_line(&line);
+        if !output.is_empty() {
+            writeln!(stdout, "{}", output)?;
+        }
+    }
+    Ok(())
+}
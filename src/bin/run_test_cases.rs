@@ -1,31 +1,299 @@
-use interpreter2::{ExecutionContext, GlobalContext, RcI, Zid, data_types::WfTestCase};
-use std::{collections::BTreeMap, fs::File, io::BufReader};
-
-fn run_test_case(zid: Zid, context: &ExecutionContext) {
-    let test_case = WfTestCase::parse(
-        context.get_global().get_object_value(&zid).unwrap(),
-        context,
-    )
-    .unwrap();
-    test_case.run_test(context).unwrap()
+//! Run test cases and write a machine-readable report.
+//!
+//! Usage: `run_test_cases [--dump PATH] [--format junit|tap|jsonl] [--output PATH] [--baseline PATH] [--threads N] [--all] [--since OLD_DUMP] [--lenient] [--overlay PATH...] [FUNCTION_ZID...]`
+//!
+//! Without FUNCTION_ZID nor `--all`, the tests listed in `./test_case_to_test.json` are run.
+//! With FUNCTION_ZID, every tester of those functions is run against each of their implementations.
+//! With `--since`, only the test cases that transitively depend on an object that changed since the older dump are run.
+//! It can’t be combined with FUNCTION_ZID.
+//!
+//! With `--lenient`, the pages of the dump (and of the older dump) that can’t be loaded are listed and skipped, rather
+//! than failing. With `--since`, the objects of the skipped pages count as removed.
+//!
+//! `--overlay` (which can be repeated) add or replace pages from a directory of `Z12345.json` files, or from stdin
+//! with `-`, on top of the dump. Loading fails if any page can’t be loaded. Pages that replace a different object from
+//! the dump are listed.
+//!
+//! If a baseline (a previous JSON Lines report) is provided, exit with a non-zero code only if a test regressed.
+//! Otherwise, exit with a non-zero code if any test didn’t pass.
+//!
+//! `--threads` (for test cases, not function testers) need the `multithread` feature.
+use anyhow::{Context, bail};
+use interpreter2::{
+    DEFAULT_DUMP_PATH, ExecutionContext, GlobalContext, KeyIndex, RcI, Zid,
+    data_types::{WfData, WfDataType},
+    overlay::{Overlay, OverlayConflict},
+    test_runner::{
+        ReportFormat, TestCaseOutcome, TestStatus, compare_with_baseline, load_baseline,
+        run_function_testers, run_test_case, write_report,
+    },
+    zid,
+};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+struct Arguments {
+    dump: Option<String>,
+    format: ReportFormat,
+    output: Option<String>,
+    baseline: Option<String>,
+    all: bool,
+    since: Option<String>,
+    lenient: bool,
+    overlays: Vec<String>,
+    threads: usize,
+    functions: Vec<Zid>,
+}
+
+fn parse_arguments() -> Result<Arguments, anyhow::Error> {
+    let mut result = Arguments {
+        dump: None,
+        format: ReportFormat::Tap,
+        output: None,
+        baseline: None,
+        all: false,
+        since: None,
+        lenient: false,
+        overlays: Vec::new(),
+        threads: 1,
+        functions: Vec::new(),
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dump" => result.dump = Some(args.next().context("--dump expect a path")?),
+            "--format" => {
+                let format = args.next().context("--format expect a format")?;
+                result.format = match ReportFormat::from_str(&format) {
+                    Some(f) => f,
+                    None => bail!("unknown format {:?}, expected junit, tap or jsonl", format),
+                };
+            }
+            "--output" => result.output = Some(args.next().context("--output expect a path")?),
+            "--baseline" => {
+                result.baseline = Some(args.next().context("--baseline expect a path")?)
+            }
+            "--all" => result.all = true,
+            "--since" => result.since = Some(args.next().context("--since expect a path")?),
+            "--lenient" => result.lenient = true,
+            "--overlay" => result
+                .overlays
+                .push(args.next().context("--overlay expect a path")?),
+            "--threads" => {
+                result.threads = args
+                    .next()
+                    .context("--threads expect a number")?
+                    .parse()
+                    .context("parsing --threads")?;
+                if result.threads > 1 && !cfg!(feature = "multithread") {
+                    bail!("--threads need the multithread feature");
+                }
+            }
+            other => result.functions.push(
+                Zid::from_str(other).with_context(|| format!("parsing function ZID {}", other))?,
+            ),
+        }
+    }
+    if !result.functions.is_empty() && result.since.is_some() {
+        bail!("--since can’t be combined with FUNCTION_ZID");
+    }
+    Ok(result)
+}
+
+fn load_overlay(path: &str, global_context: &mut GlobalContext) -> Result<(), anyhow::Error> {
+    let overlay = if path == "-" {
+        Overlay::from_reader(std::io::stdin().lock(), "stdin")
+    } else {
+        Overlay::from_directory(Path::new(path))?
+    };
+    for error in overlay.errors() {
+        eprintln!("can’t load {}: {:#}", error.origin, error.error);
+    }
+    if !overlay.errors().is_empty() {
+        bail!(
+            "{} pages of the overlay {} can’t be loaded",
+            overlay.errors().len(),
+            path
+        );
+    }
+    for conflict in overlay.conflicts(global_context) {
+        match conflict {
+            OverlayConflict::Base(zid, origin) => {
+                eprintln!("{} replaces {} from the dump", origin, zid)
+            }
+            OverlayConflict::Duplicate(zid, first, second) => {
+                eprintln!(
+                    "{} is defined in both {} and {}, using the latter",
+                    zid, first, second
+                )
+            }
+        }
+    }
+    let changes = overlay.apply(global_context);
+    eprintln!(
+        "overlay {}: {} objects added, {} modified",
+        path,
+        changes.added.len(),
+        changes.modified.len()
+    );
+    Ok(())
 }
 
-fn main() {
-    let file =
-        BufReader::new(File::open("./wikifunctionswiki-20251201-pages-meta-current.xml").unwrap());
-    let global_context = RcI::new(GlobalContext::from_wikifunction_dump(file).unwrap());
+fn run_tests(
+    global_context: RcI<GlobalContext>,
+    tests: &[Zid],
+    threads: usize,
+) -> Vec<TestCaseOutcome> {
+    #[cfg(feature = "multithread")]
+    if threads > 1 {
+        eprintln!("running {} tests on {} threads", tests.len(), threads);
+        return interpreter2::test_runner::run_test_cases_parallel(global_context, tests, threads);
+    }
+    #[cfg(not(feature = "multithread"))]
+    let _ = threads;
+
+    let mut outcomes = Vec::with_capacity(tests.len());
+    for test in tests {
+        eprintln!("running test {}", test);
+        // each test get its own context, so the function call counter is not shared
+        let context = ExecutionContext::default_for_global(global_context.clone());
+        outcomes.push(run_test_case(*test, &context));
+    }
+    outcomes
+}
+
+fn main() -> Result<ExitCode, anyhow::Error> {
+    let arguments = parse_arguments()?;
+
+    let mut affected_test_cases = None;
+    let mut global_context = match &arguments.since {
+        Some(old_dump) => {
+            let (mut global_context, failures) =
+                GlobalContext::load_for_cli(Some(old_dump), None, arguments.lenient)
+                    .context("loading older dump")?;
+            for failure in &failures {
+                eprintln!("skipped {} in the older dump", failure);
+            }
+            let dump = arguments.dump.as_deref().unwrap_or(DEFAULT_DUMP_PATH);
+            let file = BufReader::new(File::open(dump).context("opening dump")?);
+            let (changes, failures) = global_context
+                .apply_dump_delta(file)
+                .context("loading dump")?;
+            for failure in failures {
+                if !arguments.lenient {
+                    return Err(failure
+                        .error
+                        .context(format!("parsing page {}", failure.title))
+                        .context("loading dump"));
+                }
+                eprintln!("skipped {}", failure);
+            }
+            let affected = changes.affected(&global_context);
+            eprintln!(
+                "{} objects added, {} modified, {} removed, affecting {} functions and {} test cases",
+                changes.added.len(),
+                changes.modified.len(),
+                changes.removed.len(),
+                affected.functions.len(),
+                affected.test_cases.len()
+            );
+            affected_test_cases = Some(affected.test_cases);
+            global_context
+        }
+        None => {
+            let (global_context, failures) =
+                GlobalContext::load_for_cli(arguments.dump.as_deref(), None, arguments.lenient)?;
+            for failure in &failures {
+                eprintln!("skipped {}", failure);
+            }
+            global_context
+        }
+    };
+    for overlay in &arguments.overlays {
+        load_overlay(overlay, &mut global_context)?;
+    }
+    let global_context = RcI::new(global_context);
 
     let execution_context = ExecutionContext::default_for_global(global_context.clone());
 
-    let mut test_to_run_file = File::open("./test_case_to_test.json").unwrap();
+    let mut outcomes = Vec::new();
+    if !arguments.functions.is_empty() {
+        for function in &arguments.functions {
+            eprintln!("running testers for {}", function);
+            outcomes.extend(run_function_testers(*function, &execution_context));
+        }
+    } else {
+        let tests: Vec<Zid> = if let Some(affected_test_cases) = affected_test_cases {
+            affected_test_cases.into_iter().collect()
+        } else if arguments.all {
+            // only select by type: test cases that fail to parse are reported by run_test_case
+            global_context
+                .objects
+                .iter()
+                .filter(|(_, value)| {
+                    matches!(
+                        value.get_key(KeyIndex::from_u32s_panic(Some(1), Some(1))),
+                        Some(WfData::WfReference(r#type)) if r#type.to == zid!(20)
+                    )
+                })
+                .map(|(zid, _)| *zid)
+                .collect()
+        } else {
+            let test_to_run_file =
+                File::open("./test_case_to_test.json").context("opening test_case_to_test.json")?;
+            let tests: BTreeMap<String, Vec<u32>> =
+                serde_json::from_reader(BufReader::new(test_to_run_file))?;
+            tests
+                .into_values()
+                .flatten()
+                .map(Zid::from_u32)
+                .collect::<Result<_, _>>()?
+        };
+        outcomes = run_tests(global_context.clone(), &tests, arguments.threads);
+    }
 
-    let tests: BTreeMap<String, Vec<u32>> = serde_json::from_reader(&mut test_to_run_file).unwrap();
+    match &arguments.output {
+        Some(path) => {
+            let mut writer = BufWriter::new(File::create(path).context("creating report")?);
+            write_report(arguments.format, &outcomes, &mut writer)?;
+            writer.flush()?;
+        }
+        None => write_report(arguments.format, &outcomes, &mut std::io::stdout().lock())?,
+    }
+
+    let passed = outcomes
+        .iter()
+        .filter(|o| o.status() == TestStatus::Passed)
+        .count();
+    eprintln!("{}/{} passed", passed, outcomes.len());
 
-    for (test_category, test_list) in tests {
-        println!("running tests for {}", test_category);
-        for test_id in test_list.iter() {
-            println!("    Test Z{} ...", test_id);
-            run_test_case(Zid::from_u32_panic(*test_id), &execution_context);
+    if let Some(baseline_path) = &arguments.baseline {
+        let baseline = load_baseline(BufReader::new(
+            File::open(baseline_path).context("opening baseline")?,
+        ))?;
+        let comparison = compare_with_baseline(&baseline, &outcomes);
+        for key in &comparison.newly_passing {
+            eprintln!("newly passing: {}", key);
+        }
+        for key in &comparison.newly_failing {
+            eprintln!("REGRESSION, newly failing: {}", key);
+        }
+        for key in &comparison.newly_crashing {
+            eprintln!("REGRESSION, newly crashing: {}", key);
         }
+        if comparison.has_regressions() {
+            return Ok(ExitCode::FAILURE);
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if passed != outcomes.len() {
+        return Ok(ExitCode::FAILURE);
     }
+    Ok(ExitCode::SUCCESS)
 }
@@ -2,25 +2,35 @@ use interpreter2::{
     ExecutionContext, GlobalContext, RcI,
     data_types::{WfDataType, WfTestCase},
     replay,
+    util::panic_message,
 };
-use std::{fs::File, io::BufReader};
+use std::panic::{AssertUnwindSafe, catch_unwind};
 
 fn main() {
     println!("{:?}", std::env::current_dir().unwrap());
-    let file =
-        BufReader::new(File::open("./wikifunctionswiki-20251201-pages-meta-current.xml").unwrap());
-    let global_context = RcI::new(GlobalContext::from_wikifunction_dump(file).unwrap());
+    let (global_context, _) = GlobalContext::load_for_cli(None, None, false).unwrap();
+    let global_context = RcI::new(global_context);
 
     let execution_context = ExecutionContext::default_for_global(global_context.clone());
 
     for (key, entry) in execution_context.get_global().objects.iter() {
-        if key.get_z().get() > 20000 {
-            break; // I have some issue with Z10192 where it recursively call the list_equality function, and that’s not the tested behavior. I’ll care about once I have better tracing.
-        }
-        let test_case = match WfTestCase::parse(entry.clone(), &execution_context) {
-            Ok(t) => t,
-            Err(_) => continue,
+        // panics are caught at the function call level by the evaluator, but parsing and replay may still panic
+        let test_case = match catch_unwind(AssertUnwindSafe(|| {
+            WfTestCase::parse(entry.clone(), &execution_context)
+        })) {
+            Ok(Ok(t)) => t,
+            Ok(Err(_)) => continue,
+            Err(payload) => {
+                println!(
+                    "panicked while parsing {}: {}",
+                    key,
+                    panic_message(&*payload)
+                );
+                continue;
+            }
         };
+        // new context for each test, so the function call counter is not shared
+        let execution_context = ExecutionContext::default_for_global(global_context.clone());
 
         println!("{}", key);
         match test_case.clone().run_test(&execution_context) {
@@ -28,9 +38,12 @@ fn main() {
             Err(e) => {
                 println!("------------");
                 println!("{:?}", e);
-                let replay_info =
-                    replay::generate_replay(test_case.into_wf_data(), &execution_context, &e);
-                println!("{}", replay_info.pretty_trace());
+                match catch_unwind(AssertUnwindSafe(|| {
+                    replay::generate_replay(test_case.into_wf_data(), &execution_context, &e)
+                })) {
+                    Ok(replay_info) => println!("{}", replay_info.pretty_trace()),
+                    Err(payload) => println!("replay panicked: {}", panic_message(&*payload)),
+                }
             }
         }
     }
@@ -17,6 +17,9 @@ pub use wf_reference::WfReference;
 mod wf_string;
 pub use wf_string::WfString;
 
+mod wf_code_point;
+pub use wf_code_point::WfCodePoint;
+
 mod wf_untyped;
 pub use wf_untyped::WfUntyped;
 
@@ -24,7 +27,10 @@ mod wf_invalid;
 pub use wf_invalid::WfInvalid;
 
 mod wf_typed_list;
-pub use wf_typed_list::WfTypedList;
+pub use wf_typed_list::{LIST_CHUNK_SIZE, WfTypedList};
+
+mod wf_typed_pair;
+pub use wf_typed_pair::{WfTypedPair, WfTypedPairInner};
 
 mod wf_function;
 pub use wf_function::{WfFunction, WfFunctionInner};
@@ -38,6 +44,12 @@ pub use wf_implementation::{ImplementationByKind, WfImplementation, WfImplementa
 mod wf_argument_reference;
 pub use wf_argument_reference::WfArgumentReference;
 
+mod wf_key_reference;
+pub use wf_key_reference::WfKeyReference;
+
+mod wf_quote;
+pub use wf_quote::WfQuote;
+
 mod wf_test_case;
 pub use wf_test_case::{WfTestCase, WfTestCaseInner};
 
@@ -6,3 +6,6 @@ pub use wf_standard_type::{WfStandardType, WfStandardTypeInner};
 
 mod wf_typed_list_type;
 pub use wf_typed_list_type::WfTypedListType;
+
+mod wf_typed_pair_type;
+pub use wf_typed_pair_type::WfTypedPairType;
@@ -1,6 +1,6 @@
 use crate::{
-    EvalError, ExecutionContext, KeyIndex, RcI, Zid,
-    data_types::{WfData, WfDataType, types_def::WfTypeGeneric},
+    EvalError, EvalErrorKind, ExecutionContext, KeyIndex, RcI, Zid,
+    data_types::{WfBoolean, WfData, WfDataType, WfString, WfTypedList, types_def::WfTypeGeneric},
 };
 
 #[derive(Debug, PartialEq, Clone)]
@@ -67,6 +67,90 @@ impl WfStandardType {
     }
 }
 
+impl WfStandardType {
+    /// The key declared (in Z4K2) as being the identity of the instances (with Z3K4 set to true), as used by enumerations.
+    pub fn get_identity_key(
+        &self,
+        context: &ExecutionContext,
+    ) -> Result<Option<KeyIndex>, EvalError> {
+        let keys = self
+            .inner
+            .keys
+            .clone()
+            .evaluate(context)
+            .and_then(|keys| WfTypedList::parse(keys, context))
+            .map_err(|(e, _)| e.inside_key(keyindex!(4, 2)))?;
+
+        for (pos, key) in keys.iter().enumerate() {
+            let key = key
+                .evaluate(context)
+                .map_err(|(e, _)| e.inside_list(pos).inside_key(keyindex!(4, 2)))?;
+            let is_identity = match key.get_key(keyindex!(3, 4)) {
+                None => false,
+                Some(is_identity) => {
+                    is_identity
+                        .evaluate(context)
+                        .and_then(|v| WfBoolean::parse(v, context))
+                        .map_err(|(e, _)| {
+                            e.inside_key(keyindex!(3, 4))
+                                .inside_list(pos)
+                                .inside_key(keyindex!(4, 2))
+                        })?
+                        .value
+                }
+            };
+            if !is_identity {
+                continue;
+            }
+
+            return Self::parse_key_id(&key, pos, context).map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Every key declared in Z4K2 (their Z3K2), in order.
+    pub fn get_declared_keys(
+        &self,
+        context: &ExecutionContext,
+    ) -> Result<Vec<KeyIndex>, EvalError> {
+        let keys = self
+            .inner
+            .keys
+            .clone()
+            .evaluate(context)
+            .and_then(|keys| WfTypedList::parse(keys, context))
+            .map_err(|(e, _)| e.inside_key(keyindex!(4, 2)))?;
+
+        let mut result = Vec::new();
+        for (pos, key) in keys.iter().enumerate() {
+            let key = key
+                .evaluate(context)
+                .map_err(|(e, _)| e.inside_list(pos).inside_key(keyindex!(4, 2)))?;
+            result.push(Self::parse_key_id(&key, pos, context)?);
+        }
+        Ok(result)
+    }
+
+    /// The Z3K2 of the evaluated key at `pos` in Z4K2
+    fn parse_key_id(
+        key: &WfData,
+        pos: usize,
+        context: &ExecutionContext,
+    ) -> Result<KeyIndex, EvalError> {
+        let key_id = key
+            .get_key_err(keyindex!(3, 2))
+            .and_then(|v| v.evaluate(context).map_err(|(e, _)| e))
+            .and_then(|v| WfString::parse(v, context).map_err(|(e, _)| e))
+            .map_err(|e| e.inside_list(pos).inside_key(keyindex!(4, 2)))?;
+        KeyIndex::from_str(&key_id.text).map_err(|e| {
+            EvalError::from_kind(EvalErrorKind::ParseKeyIndex(e))
+                .inside_key(keyindex!(3, 2))
+                .inside_list(pos)
+                .inside_key(keyindex!(4, 2))
+        })
+    }
+}
+
 impl WfDataType for WfStandardType {
     fn get_identity_zid_key(&self) -> Option<KeyIndex> {
         Some(keyindex!(4, 1))
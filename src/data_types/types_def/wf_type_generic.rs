@@ -2,7 +2,7 @@ use crate::{
     EvalError, EvalErrorKind, ExecutionContext, Zid,
     data_types::{
         WfData, WfDataType, WfFunctionCall,
-        types_def::{WfStandardType, WfTypedListType},
+        types_def::{WfStandardType, WfTypedListType, WfTypedPairType},
         wf_function_call::FunctionCallOrType,
     },
 };
@@ -10,7 +10,8 @@ use crate::{
 #[derive(Debug, Clone, PartialEq)]
 pub enum WfTypeGeneric {
     WfStandardType(WfStandardType),
-    WfTypedListType(WfTypedListType), // TODO: typed pair
+    WfTypedListType(WfTypedListType),
+    WfTypedPairType(WfTypedPairType),
 }
 
 impl WfTypeGeneric {
@@ -63,5 +64,6 @@ impl_wf_data_type!(
     WfTypeGeneric,
     WfData::WfType,
     WfStandardType(d),
-    WfTypedListType(d)
+    WfTypedListType(d),
+    WfTypedPairType(d)
 );
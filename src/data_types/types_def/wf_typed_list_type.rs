@@ -15,6 +15,10 @@ impl WfTypedListType {
         }
     }
 
+    pub fn get_inner_type(&self) -> &WfTypeGeneric {
+        &self.r#type
+    }
+
     pub fn parse(data: WfData, context: &ExecutionContext) -> Result<Self, (EvalError, WfData)> {
         if let WfData::WfType(WfTypeGeneric::WfTypedListType(v)) = data {
             return Ok(v);
@@ -0,0 +1,175 @@
+use crate::{
+    EvalError, ExecutionContext, KeyIndex, RcI,
+    data_types::{WfData, WfDataType, types_def::WfTypeGeneric},
+};
+
+/// The type returned by Z882, for pairs whose two elements have the given types.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WfTypedPairType {
+    first: RcI<WfTypeGeneric>,
+    second: RcI<WfTypeGeneric>,
+}
+
+impl WfTypedPairType {
+    pub fn new(first: WfTypeGeneric, second: WfTypeGeneric) -> Self {
+        Self {
+            first: RcI::new(first),
+            second: RcI::new(second),
+        }
+    }
+
+    pub fn get_first_type(&self) -> &WfTypeGeneric {
+        &self.first
+    }
+
+    pub fn get_second_type(&self) -> &WfTypeGeneric {
+        &self.second
+    }
+
+    pub fn parse(data: WfData, context: &ExecutionContext) -> Result<Self, (EvalError, WfData)> {
+        if let WfData::WfType(WfTypeGeneric::WfTypedPairType(v)) = data {
+            return Ok(v);
+        }
+        data.assert_evaluated();
+        // check type of this
+        match data.get_key_err(keyindex!(1, 1)) {
+            Ok(this_type) => {
+                if let Err((e, _)) = this_type.check_identity_zid(context, zid!(7)) {
+                    return Err((e.inside_key(keyindex!(1, 1)), data));
+                }
+            }
+            Err(e) => return Err((e, data)),
+        };
+
+        // check function to be called
+        match data.get_key_err(keyindex!(7, 1)) {
+            Ok(this_function) => {
+                if let Err((e, _)) = this_function.check_identity_zid(context, zid!(882)) {
+                    return Err((e.inside_key(keyindex!(7, 1)), data));
+                }
+            }
+            Err(e) => return Err((e, data)),
+        };
+
+        // obtain types
+        let mut types = Vec::with_capacity(2);
+        for key in [keyindex!(882, 1), keyindex!(882, 2)] {
+            types.push(match data.get_key_err(key) {
+                Err(e) => return Err((e, data)),
+                Ok(unparsed_type) => match unparsed_type.evaluate(context) {
+                    Err((e, _)) => return Err((e.inside_key(key), data)),
+                    Ok(unparsed_type) => match WfTypeGeneric::parse(unparsed_type, context) {
+                        Err((e, _)) => return Err((e.inside_key(key), data)),
+                        Ok(v) => v,
+                    },
+                },
+            });
+        }
+        let second = types.pop().unwrap();
+        let first = types.pop().unwrap();
+
+        Ok(Self::new(first, second))
+    }
+}
+
+impl WfDataType for WfTypedPairType {
+    fn into_wf_data(self) -> WfData {
+        WfData::WfType(WfTypeGeneric::WfTypedPairType(self))
+    }
+
+    fn is_fully_realised(&self) -> bool {
+        false
+    }
+
+    fn get_identity_zid_key(&self) -> Option<crate::KeyIndex> {
+        None
+    }
+
+    fn get_key(&self, key: KeyIndex) -> Option<WfData> {
+        // map that as a function call
+        if key == keyindex!(1, 1) {
+            Some(WfData::new_reference(zid!(7)))
+        } else if key == keyindex!(7, 1) {
+            Some(WfData::new_reference(zid!(882)))
+        } else if key == keyindex!(882, 1) {
+            Some((*self.first).clone().into_wf_data())
+        } else if key == keyindex!(882, 2) {
+            Some((*self.second).clone().into_wf_data())
+        } else {
+            None
+        }
+    }
+
+    fn list_keys(&self) -> Vec<KeyIndex> {
+        vec![
+            keyindex!(1, 1),
+            keyindex!(7, 1),
+            keyindex!(882, 1),
+            keyindex!(882, 2),
+        ]
+    }
+
+    fn substitute_function_arguments<I: crate::data_types::util::SubstitutionInfo>(
+        self,
+        info: &I,
+        context: &ExecutionContext,
+    ) -> Result<WfData, EvalError> {
+        let mut types = Vec::with_capacity(2);
+        for (key, r#type) in [
+            (keyindex!(882, 1), &*self.first),
+            (keyindex!(882, 2), &*self.second),
+        ] {
+            types.push(
+                match WfTypeGeneric::parse(
+                    r#type
+                        .clone()
+                        .substitute_function_arguments(info, context)
+                        .map_err(|e| e.inside_key(key))?,
+                    context,
+                ) {
+                    Ok(v) => v,
+                    Err((e, _)) => return Err(e.inside_key(key)),
+                },
+            );
+        }
+        let second = types.pop().unwrap();
+        let first = types.pop().unwrap();
+        Ok(Self::new(first, second).into_wf_data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map_macro::btree_map;
+
+    use crate::{
+        ExecutionContext, GlobalContext, RcI,
+        data_types::{
+            WfData, WfDataType,
+            types_def::{WfTypeGeneric, WfTypedPairType},
+        },
+    };
+
+    #[test]
+    fn test_parse() {
+        let global_context = GlobalContext::default_for_test();
+        let context = ExecutionContext::default_for_global(RcI::new(global_context));
+        let type_def = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(7)),
+            keyindex!(7, 1) => WfData::new_reference(zid!(882)),
+            keyindex!(882, 1) => WfData::new_reference(zid!(39)),
+            keyindex!(882, 2) => WfData::new_reference(zid!(40)),
+        });
+
+        let parsed = WfTypedPairType::parse(type_def.clone(), &context).unwrap();
+        assert_eq!(parsed.get_first_type().get_type_zid().unwrap(), zid!(39));
+        assert_eq!(parsed.get_second_type().get_type_zid().unwrap(), zid!(40));
+
+        let evaluated = type_def.clone().evaluate(&context).unwrap();
+        assert_eq!(
+            evaluated,
+            WfTypeGeneric::WfTypedPairType(parsed).into_wf_data()
+        );
+        assert!(evaluated.equality(type_def, &context).unwrap())
+    }
+}
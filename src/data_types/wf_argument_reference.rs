@@ -56,7 +56,7 @@ impl WfDataType for WfArgumentReference {
 
     fn get_key(&self, key: KeyIndex) -> Option<WfData> {
         if key == keyindex!(1, 1) {
-            Some(WfData::new_reference(zid!(14)))
+            Some(WfData::new_reference(zid!(18)))
         } else if key == keyindex!(18, 1) {
             Some(WfString::new(&self.key_id.to_string()).into_wf_data())
         } else {
@@ -73,7 +73,8 @@ impl WfDataType for WfArgumentReference {
     }
 
     fn should_be_evaluated_before_parsing(&self) -> bool {
-        true
+        // evaluating doesn’t change it, only substitution. Outside of a composition, it is just data of the wrong type.
+        false
     }
 
     fn substitute_function_arguments<I: super::util::SubstitutionInfo>(
@@ -98,8 +99,8 @@ mod tests {
     use map_macro::btree_map;
 
     use crate::{
-        ExecutionContext, GlobalContext, RcI,
-        data_types::{WfArgumentReference, WfData, WfDataType, WfString},
+        EvalErrorKind, ExecutionContext, GlobalContext, RcI,
+        data_types::{WfArgumentReference, WfBoolean, WfData, WfDataType, WfString},
     };
 
     #[test]
@@ -119,4 +120,36 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn test_outside_composition() {
+        let global_context = GlobalContext::default_for_test();
+        let context = ExecutionContext::default_for_global(RcI::new(global_context));
+
+        let reference = WfArgumentReference {
+            key_id: keyindex!(881, 1),
+        }
+        .into_wf_data();
+        assert_eq!(
+            reference.get_key(keyindex!(1, 1)),
+            Some(WfData::new_reference(zid!(18)))
+        );
+        // it is just data of the wrong type, whose type Z18 isn’t in the test context
+        let (error, _) = WfBoolean::parse(reference, &context).unwrap_err();
+        assert_eq!(
+            error.get_kind(),
+            &EvalErrorKind::MissingPersistentObject(zid!(18))
+        );
+
+        // the type is dereferenced to check it is a type at all, so the error is about the type mismatch
+        let wrong_type = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(14)),
+            keyindex!(18, 1) => WfString::new("Z881K1").into_wf_data(),
+        });
+        let (error, _) = WfArgumentReference::parse(wrong_type, &context).unwrap_err();
+        assert_eq!(
+            error.get_kind(),
+            &EvalErrorKind::WrongType(zid!(14), zid!(18))
+        );
+    }
 }
@@ -46,8 +46,11 @@ impl WfBoolean {
                     ))
                 }
             }
-            Err((_err, identity)) => match Self::parse(identity, context) {
-                Ok(v) => Ok(v),
+            Err((_err, identity)) => match identity.evaluate(context) {
+                Ok(identity) => match Self::parse(identity, context) {
+                    Ok(v) => Ok(v),
+                    Err((e, _)) => Err((e.inside_key(keyindex!(40, 1)), data)),
+                },
                 Err((e, _)) => Err((e.inside_key(keyindex!(40, 1)), data)),
             },
         }
@@ -134,4 +137,25 @@ mod tests {
         });
         WfBoolean::parse(incorrect_boolean, &context).unwrap_err();
     }
+
+    #[test]
+    fn test_parse_evaluate_identity() {
+        let global_context = RcI::new(GlobalContext::default_for_test());
+        let context = ExecutionContext::default_for_global(global_context);
+
+        // the identity is itself computed
+        let computed_identity = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(40)),
+            keyindex!(40, 1) => WfData::from_map(btree_map! {
+                keyindex!(1, 1) => WfData::new_reference(zid!(7)),
+                keyindex!(7, 1) => WfData::new_reference(zid!(844)),
+                keyindex!(844, 1) => WfData::new_reference(zid!(41)),
+                keyindex!(844, 2) => WfData::new_reference(zid!(41)),
+            })
+        });
+        assert_eq!(
+            WfBoolean::parse(computed_identity, &context).unwrap(),
+            WfBoolean::new(true)
+        );
+    }
 }
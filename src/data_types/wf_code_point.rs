@@ -0,0 +1,122 @@
+use crate::{
+    EvalError, EvalErrorKind, ExecutionContext, KeyIndex,
+    data_types::{WfData, WfDataType, WfString},
+};
+
+/// A Z86 unicode code point. Z86K1 is a string containing just that character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WfCodePoint {
+    pub value: char,
+}
+
+impl WfCodePoint {
+    pub fn new(value: char) -> Self {
+        Self { value }
+    }
+
+    pub fn parse(data: WfData, context: &ExecutionContext) -> Result<Self, (EvalError, WfData)> {
+        if let WfData::WfCodePoint(c) = data {
+            return Ok(c);
+        };
+        data.assert_evaluated();
+        match data.check_z1k1(zid!(86), context) {
+            Ok(()) => (),
+            Err(e) => return Err((e, data)),
+        };
+
+        let text = match get_value_from_data_err_handled!(data, keyindex!(86, 1)).evaluate(context)
+        {
+            Err((e, _)) => return Err((e.inside_key(keyindex!(86, 1)), data)),
+            Ok(v) => match WfString::parse(v, context) {
+                Ok(v) => v,
+                Err((e, _)) => return Err((e.inside_key(keyindex!(86, 1)), data)),
+            },
+        };
+
+        let mut chars = text.text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(value), None) => Ok(Self { value }),
+            _ => Err((
+                EvalError::from_kind(EvalErrorKind::NotASingleCodePoint(text.text.to_string()))
+                    .inside_key(keyindex!(86, 1)),
+                data,
+            )),
+        }
+    }
+}
+
+impl WfDataType for WfCodePoint {
+    fn get_identity_zid_key(&self) -> Option<KeyIndex> {
+        None
+    }
+
+    fn get_key(&self, key: KeyIndex) -> Option<WfData> {
+        if key == keyindex!(1, 1) {
+            Some(WfData::new_reference(zid!(86)))
+        } else if key == keyindex!(86, 1) {
+            Some(WfString::new(self.value.encode_utf8(&mut [0; 4])).into_wf_data())
+        } else {
+            None
+        }
+    }
+
+    fn list_keys(&self) -> Vec<KeyIndex> {
+        vec![keyindex!(1, 1), keyindex!(86, 1)]
+    }
+
+    fn is_fully_realised(&self) -> bool {
+        true
+    }
+
+    fn into_wf_data(self) -> WfData {
+        WfData::WfCodePoint(self)
+    }
+
+    fn substitute_function_arguments<I: super::util::SubstitutionInfo>(
+        self,
+        _info: &I,
+        _context: &ExecutionContext,
+    ) -> Result<WfData, EvalError> {
+        Ok(self.into_wf_data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map_macro::btree_map;
+
+    use crate::{
+        EvalErrorKind, ExecutionContext, GlobalContext, RcI,
+        data_types::{WfCodePoint, WfData, WfDataType, WfString},
+    };
+
+    #[test]
+    fn test_parse() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+
+        let code_point = |text: &str| {
+            WfData::from_map(btree_map! {
+                keyindex!(1, 1) => WfData::new_reference(zid!(86)),
+                keyindex!(86, 1) => WfString::new(text).into_wf_data(),
+            })
+        };
+
+        assert_eq!(
+            code_point("é").evaluate(&context).unwrap(),
+            WfCodePoint::new('é').into_wf_data()
+        );
+        assert_eq!(
+            WfCodePoint::new('🦀').get_key(keyindex!(86, 1)),
+            Some(WfString::new("🦀").into_wf_data())
+        );
+
+        for invalid in ["", "ab"] {
+            let (error, _) = code_point(invalid).evaluate(&context).unwrap_err();
+            assert_eq!(
+                error.get_kind(),
+                &EvalErrorKind::NotASingleCodePoint(invalid.to_string())
+            );
+        }
+    }
+}
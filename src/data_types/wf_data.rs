@@ -1,13 +1,13 @@
 use std::{collections::BTreeMap, num::NonZeroU32};
 
-#[cfg(test)]
+#[cfg(any(test, feature = "fuzzing"))]
 use crate::EvalErrorKind;
 use crate::{
     EvalError, ExecutionContext, KeyIndex, Zid,
     data_types::{
-        WfArgumentReference, WfBoolean, WfDataType, WfFunction, WfFunctionCall, WfImplementation,
-        WfInvalid, WfReference, WfString, WfTestCase, WfTypedList, WfUntyped,
-        types_def::WfTypeGeneric,
+        WfArgumentReference, WfBoolean, WfCodePoint, WfDataType, WfFunction, WfFunctionCall,
+        WfImplementation, WfInvalid, WfKeyReference, WfQuote, WfReference, WfString, WfTestCase,
+        WfTypedList, WfTypedPair, WfUntyped, types_def::WfTypeGeneric,
     },
 };
 
@@ -27,6 +27,10 @@ pub enum WfData {
     WfImplementation(WfImplementation),
     WfArgumentReference(WfArgumentReference),
     WfTestCase(WfTestCase),
+    WfKeyReference(WfKeyReference),
+    WfQuote(WfQuote),
+    WfTypedPair(WfTypedPair),
+    WfCodePoint(WfCodePoint),
 }
 
 impl_wf_data_type!(
@@ -43,7 +47,11 @@ impl_wf_data_type!(
     WfFunctionCall(d),
     WfImplementation(d),
     WfArgumentReference(d),
-    WfTestCase(d)
+    WfTestCase(d),
+    WfKeyReference(d),
+    WfQuote(d),
+    WfTypedPair(d),
+    WfCodePoint(d)
 );
 
 impl WfData {
@@ -61,6 +69,8 @@ impl WfData {
     ) -> Result<WfTypeGeneric, (EvalError, WfData)> {
         match self {
             Self::WfType(ready) => Ok(ready),
+            // WfTypeGeneric::parse expect dereferenced data
+            Self::WfReference(_) => self.evaluate(context)?.parse_type(context),
             other => WfTypeGeneric::parse(other, context),
         }
     }
@@ -75,18 +85,18 @@ impl WfData {
         // fast path before evaluating (for reference equality and the like)
         if self == other {
             return Ok(true);
-        } else if self.is_fully_realised() && self.is_fully_realised() {
+        } else if self.is_fully_realised() && other.is_fully_realised() {
             return Ok(false);
         }
 
         // evaluate
-        let first = self.evaluate(context).unwrap(); //TODO: error handling
-        let other = other.evaluate(context).unwrap(); //TODO: error handling
+        let first = self.evaluate(context).map_err(|(e, _)| (e, true))?;
+        let other = other.evaluate(context).map_err(|(e, _)| (e, false))?;
 
         // fast path after evaluating
         if first == other {
             return Ok(true);
-        } else if first.is_fully_realised() && first.is_fully_realised() {
+        } else if first.is_fully_realised() && other.is_fully_realised() {
             return Ok(false);
         }
 
@@ -99,12 +109,8 @@ impl WfData {
         }
 
         for key in keys_first {
-            let value_first = first
-                .get_key(key)
-                .expect("key should be guaranteed to exist");
-            let value_second = other
-                .get_key(key)
-                .expect("key should be guaranteed to exist");
+            let value_first = first.get_key_err(key).map_err(|e| (e, true))?;
+            let value_second = other.get_key_err(key).map_err(|e| (e, false))?;
 
             let this_equality = match value_first.equality(value_second, context) {
                 Ok(v) => v,
@@ -141,7 +147,7 @@ impl WfData {
         }
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "fuzzing"))]
     pub fn unvalid(reason: EvalErrorKind) -> Self {
         Self::WfInvalid(WfInvalid::new(reason))
     }
@@ -153,7 +159,7 @@ mod tests {
 
     use crate::{
         ExecutionContext, GlobalContext, RcI,
-        data_types::{WfBoolean, WfData, WfDataType},
+        data_types::{WfBoolean, WfData, WfDataType, types_def::WfTypeGeneric},
     };
 
     #[test]
@@ -176,4 +182,15 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn test_parse_type_of_reference() {
+        let global_context = RcI::new(GlobalContext::default_for_test());
+        let context = ExecutionContext::default_for_global(global_context);
+
+        assert!(matches!(
+            WfData::new_reference(zid!(40)).parse_type(&context),
+            Ok(WfTypeGeneric::WfStandardType(_))
+        ));
+    }
 }
@@ -112,8 +112,11 @@ pub trait WfDataType: Debug + Clone {
             .check_identity_zid(context, expected_value)
         {
             Ok(v) => v,
-            Err((e, data)) => match WfStandardType::parse(data, context) {
-                Ok(_) => return Err(e),
+            Err((e, data)) => match data.evaluate(context) {
+                Ok(data) => match WfStandardType::parse(data, context) {
+                    Ok(_) => return Err(e),
+                    Err((e2, _)) => return Err(e2.inside_key(keyindex!(1, 1))),
+                },
                 Err((e2, _)) => return Err(e2.inside_key(keyindex!(1, 1))),
             },
         };
@@ -4,6 +4,7 @@ use crate::{
         ImplementationByKind, WfData, WfDataType, WfImplementation, WfTypedList,
         types_def::WfTypeGeneric, util::SubstitutionInfo,
     },
+    test_runner::{self, TestersMatrix},
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -88,13 +89,56 @@ impl WfFunction {
         })))
     }
 
+    /// The type declared (Z17K1) for the argument at the 0-indexed position pos, or None if the function has fewer arguments.
+    pub fn argument_type(
+        &self,
+        pos: usize,
+        context: &ExecutionContext,
+    ) -> Result<Option<WfTypeGeneric>, EvalError> {
+        let argument = match self.0.arguments.iter().nth(pos) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let trace = |e: EvalError| e.inside_list(pos).inside_key(keyindex!(8, 1));
+        // the Z17 declaration itself is usually inline, and only its Z17K1 need evaluating
+        let argument = if argument.should_be_evaluated_before_parsing() {
+            argument.evaluate(context).map_err(|(e, _)| trace(e))?
+        } else {
+            argument
+        };
+        let r#type = match argument.get_key_err(keyindex!(17, 1)) {
+            Err(e) => return Err(trace(e)),
+            Ok(v) => match v.evaluate(context) {
+                Err((e, _)) => return Err(trace(e.inside_key(keyindex!(17, 1)))),
+                Ok(v) => match WfTypeGeneric::parse(v, context) {
+                    Err((e, _)) => return Err(trace(e.inside_key(keyindex!(17, 1)))),
+                    Ok(v) => v,
+                },
+            },
+        };
+        Ok(Some(r#type))
+    }
+
     pub fn get_preffered_implementation(
         &self,
         context: &ExecutionContext,
     ) -> Result<WfImplementation, EvalError> {
+        if let Some(forced) = context.get_forced_implementation(&self.0.identity) {
+            let implementation = match WfData::new_reference(forced).evaluate(context) {
+                Ok(v) => WfImplementation::parse(v, context).map_err(|(e, _)| e)?,
+                Err((e, _)) => return Err(e),
+            };
+            context.record_implementation_used(forced);
+            return Ok(implementation);
+        }
+
         let mut implementations = Vec::new();
         for (pos, implementation) in self.0.implementations.iter().enumerate() {
-            let implementation = match implementation.clone().evaluate(context) {
+            let implementation_zid = match &implementation {
+                WfData::WfReference(reference) => Some(reference.to),
+                _ => None,
+            };
+            let implementation = match implementation.evaluate(context) {
                 Ok(v) => v,
                 Err((e, _)) => return Err(e.inside_key(keyindex!(8, 4)).inside_list(pos)),
             };
@@ -104,7 +148,7 @@ impl WfFunction {
                 Err((e, _)) => return Err(e.inside_key(keyindex!(8, 4)).inside_list(pos)),
             };
 
-            implementations.push(implementation);
+            implementations.push((implementation_zid, implementation));
         }
 
         let mut best_builtin_implementation = None;
@@ -113,7 +157,7 @@ impl WfFunction {
 
         for implementation in implementations.into_iter() {
             //TODO: better implementation choice. Idea: just pick the first that isn’t code (as it is currently unsupported.)
-            match implementation.0.r#impl {
+            match implementation.1.0.r#impl {
                 ImplementationByKind::Composition(_) => {
                     best_composition_implementation = Some(implementation)
                 }
@@ -124,11 +168,13 @@ impl WfFunction {
             }
         }
 
-        if let Some(r#impl) = best_builtin_implementation {
-            Ok(r#impl)
-        } else if let Some(r#impl) = best_composition_implementation {
-            Ok(r#impl)
-        } else if let Some(r#impl) = best_code_implementation {
+        if let Some((zid, r#impl)) = best_builtin_implementation
+            .or(best_composition_implementation)
+            .or(best_code_implementation)
+        {
+            if let Some(zid) = zid {
+                context.record_implementation_used(zid);
+            }
             Ok(r#impl)
         } else {
             Err(EvalError::from_kind(
@@ -136,6 +182,60 @@ impl WfFunction {
             ))
         }
     }
+
+    /// Return the ZID of each entry of a list stored in this function, which should all be references (as is the case for testers and implementations)
+    fn list_references(
+        list: &WfTypedList,
+        key: KeyIndex,
+        context: &ExecutionContext,
+    ) -> Result<Vec<Zid>, EvalError> {
+        let mut result = Vec::with_capacity(list.len());
+        for (pos, entry) in list.iter().enumerate() {
+            match entry.get_reference(context) {
+                Ok((zid, _)) => result.push(zid),
+                Err((e, _)) => return Err(e.inside_list(pos).inside_key(key)),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Run every tester (Z8K3) of this function against every of its implementations (Z8K4).
+    /// Each run use a new execution context, where this function is forced to use the tested implementation for the tested call only.
+    ///
+    /// Return an error only if the list of testers or implementations can’t be read. Failure of individual tests are stored in the returned matrix.
+    pub fn run_testers(&self, context: &ExecutionContext) -> Result<TestersMatrix, EvalError> {
+        let testers = match self.0.testers.clone().evaluate(context) {
+            Ok(v) => match WfTypedList::parse(v, context) {
+                Ok(v) => v,
+                Err((e, _)) => return Err(e.inside_key(keyindex!(8, 3))),
+            },
+            Err((e, _)) => return Err(e.inside_key(keyindex!(8, 3))),
+        };
+        let testers = Self::list_references(&testers, keyindex!(8, 3), context)?;
+        let implementations =
+            Self::list_references(&self.0.implementations, keyindex!(8, 4), context)?;
+
+        let mut results = Vec::with_capacity(testers.len());
+        for tester in &testers {
+            let mut results_for_tester = Vec::with_capacity(implementations.len());
+            for implementation in &implementations {
+                results_for_tester.push(test_runner::run_test_case_with_forced_implementation(
+                    *tester,
+                    self.0.identity,
+                    *implementation,
+                    &context.new_fresh(),
+                ));
+            }
+            results.push(results_for_tester);
+        }
+
+        Ok(TestersMatrix {
+            function: self.0.identity,
+            testers,
+            implementations,
+            results,
+        })
+    }
 }
 
 impl WfDataType for WfFunction {
@@ -200,7 +300,11 @@ mod tests {
 
     use crate::{
         EvalErrorKind, ExecutionContext, GlobalContext, RcI,
-        data_types::{MaybeEvaluated, WfData, WfDataType, WfFunction, WfTypedList},
+        data_types::{
+            ImplementationByKind, MaybeEvaluated, WfData, WfDataType, WfFunction, WfFunctionInner,
+            WfImplementation, WfImplementationInner, WfString, WfTypedList,
+            types_def::{WfStandardType, WfStandardTypeInner, WfTypeGeneric},
+        },
     };
 
     #[test]
@@ -229,4 +333,119 @@ mod tests {
         //assert_eq!(function.0.implementations, unv.clone());
         assert_eq!(function.0.identity, zid!(u32::MAX));
     }
+
+    #[test]
+    fn test_run_testers() {
+        let mut global_context = GlobalContext::default_for_test();
+        let unv = WfData::unvalid(EvalErrorKind::TestData);
+
+        global_context.add_direct_no_persistent_data(
+            zid!(20),
+            WfStandardType::from(WfStandardTypeInner {
+                identity_ref: zid!(20),
+                keys: unv.clone(),
+                validator: unv.clone(),
+                equality: None,
+                display_function: None,
+                reading_function: None,
+                type_converters_to_code: None,
+                type_converters_from_code: None,
+            })
+            .into_wf_data(),
+        );
+        // a wrong implementation, that always return a string
+        global_context.add_direct_no_persistent_data(
+            zid!(10944),
+            WfImplementation(RcI::new(WfImplementationInner {
+                function: WfData::new_reference(zid!(844)),
+                r#impl: ImplementationByKind::Composition(WfString::new("oops").into_wf_data()),
+            }))
+            .into_wf_data(),
+        );
+        // always return false: right for this tester, but wrong as the validator
+        global_context.add_direct_no_persistent_data(
+            zid!(10945),
+            WfImplementation(RcI::new(WfImplementationInner {
+                function: WfData::new_reference(zid!(844)),
+                r#impl: ImplementationByKind::Composition(WfData::new_reference(zid!(42))),
+            }))
+            .into_wf_data(),
+        );
+        // true == false should be false
+        global_context.add_direct_no_persistent_data(
+            zid!(10845),
+            WfData::from_map(btree_map! {
+                keyindex!(1, 1) => WfData::new_reference(zid!(20)),
+                keyindex!(20, 1) => WfData::new_reference(zid!(844)),
+                keyindex!(20, 2) => WfData::from_map(btree_map! {
+                    keyindex!(1, 1) => WfData::new_reference(zid!(7)),
+                    keyindex!(7, 1) => WfData::new_reference(zid!(844)),
+                    keyindex!(844, 1) => WfData::new_reference(zid!(41)),
+                    keyindex!(844, 2) => WfData::new_reference(zid!(42)),
+                }),
+                keyindex!(20, 3) => WfData::from_map(btree_map! {
+                    keyindex!(1, 1) => WfData::new_reference(zid!(7)),
+                    keyindex!(7, 1) => WfData::new_reference(zid!(844)),
+                    keyindex!(844, 2) => WfData::new_reference(zid!(42)),
+                }),
+            }),
+        );
+
+        let boolean_type = WfStandardType::from(WfStandardTypeInner {
+            identity_ref: zid!(40),
+            keys: unv.clone(),
+            validator: unv.clone(),
+            equality: None,
+            display_function: None,
+            reading_function: None,
+            type_converters_to_code: None,
+            type_converters_from_code: None,
+        });
+        let function = WfFunction(RcI::new(WfFunctionInner {
+            arguments: WfTypedList::new(
+                MaybeEvaluated::Unchecked(WfData::new_reference(zid!(3))),
+                vec![unv.clone(); 2],
+            ),
+            return_type: WfTypeGeneric::WfStandardType(boolean_type),
+            testers: WfTypedList::new(
+                MaybeEvaluated::Unchecked(WfData::new_reference(zid!(20))),
+                vec![WfData::new_reference(zid!(10845))],
+            )
+            .into_wf_data(),
+            implementations: WfTypedList::new(
+                MaybeEvaluated::Unchecked(WfData::new_reference(zid!(14))),
+                vec![
+                    WfData::new_reference(zid!(944)),
+                    WfData::new_reference(zid!(10944)),
+                    WfData::new_reference(zid!(10945)),
+                ],
+            ),
+            identity: zid!(844),
+        }));
+        global_context.add_direct_no_persistent_data(zid!(844), function.clone().into_wf_data());
+
+        let context = ExecutionContext::default_for_global(RcI::new(global_context));
+
+        let matrix = function.run_testers(&context).unwrap();
+        assert_eq!(matrix.testers, vec![zid!(10845)]);
+        assert_eq!(
+            matrix.implementations,
+            vec![zid!(944), zid!(10944), zid!(10945)]
+        );
+        let builtin_outcome = matrix.get(zid!(10845), zid!(944)).unwrap();
+        assert!(builtin_outcome.result.is_ok());
+        assert_eq!(builtin_outcome.function, Some(zid!(844)));
+        assert_eq!(builtin_outcome.implementation, Some(zid!(944)));
+        assert!(
+            matrix
+                .get(zid!(10845), zid!(10944))
+                .unwrap()
+                .result
+                .is_err()
+        );
+        // the validator still use the builtin
+        assert!(matrix.get(zid!(10845), zid!(10945)).unwrap().result.is_ok());
+        assert!(!matrix.all_passed());
+        assert_eq!(matrix.count_passed(), 2);
+    }
 }
@@ -2,12 +2,12 @@ use crate::{
     EvalError, EvalErrorKind, ExecutionContext, KeyIndex, RcI,
     data_types::{
         ImplementationByKind, WfData, WfDataType, WfFunction, WfImplementation,
-        types_def::{WfTypeGeneric, WfTypedListType},
+        types_def::{WfTypeGeneric, WfTypedListType, WfTypedPairType},
         util::SubstitutionInfo,
     },
     eval_error::TraceEntry,
     functions::dispatch_builtins,
-    util::MaybeVec,
+    util::{MaybeVec, catch_panic},
 };
 
 #[derive(Debug)]
@@ -85,6 +85,15 @@ impl WfFunctionCall {
                     }
                     Err((_, d)) => d,
                 };
+            } else if function_reference == zid!(882) {
+                data = match WfTypedPairType::parse(data, context) {
+                    Ok(typed_pair_type) => {
+                        return Ok(FunctionCallOrType::Type(WfTypeGeneric::WfTypedPairType(
+                            typed_pair_type,
+                        )));
+                    }
+                    Err((_, d)) => d,
+                };
             }
         }
 
@@ -134,6 +143,61 @@ impl WfFunctionCall {
         ))))
     }
 
+    /// The actual implementation of evaluate_one_step, that may panic
+    fn evaluate_one_step_may_panic(
+        self,
+        context: &ExecutionContext,
+    ) -> Result<(WfData, bool, MaybeVec<TraceEntry>), (EvalError, Self)> {
+        let _function_recurse_guard = match context.check_can_run_function_and_acquire_guard() {
+            Ok(v) => v,
+            Err(e) => return Err((e, self)),
+        };
+        let implementation = match self.pick_implementation(context) {
+            Ok(i) => i,
+            Err(e) => return Err((e, self)),
+        };
+
+        match &implementation.0.r#impl {
+            ImplementationByKind::Composition(inner) => {
+                let inner_substituted =
+                    match inner.clone().substitute_function_arguments(&self, context) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            return Err((
+                                e.trace(TraceEntry::DuringSubstitution(self.0.function.0.identity)),
+                                self,
+                            ));
+                        }
+                    };
+                Ok((
+                    inner_substituted,
+                    true,
+                    MaybeVec::One(TraceEntry::Substituted(self.0.function.0.identity)),
+                ))
+            }
+            ImplementationByKind::Code(_) => {
+                return Err((
+                    EvalError::unimplemented(format!(
+                        "code implementaiton (for {})",
+                        self.0.function.0.identity
+                    )),
+                    self,
+                ));
+            }
+            ImplementationByKind::Builtin(_) => {
+                match dispatch_builtins(self.0.function.0.identity, &self, context) {
+                    Ok(v) => Ok(v),
+                    Err(e) => Err((
+                        e.trace(TraceEntry::ProcessingNonCompositionFunction(
+                            self.0.function.0.identity,
+                        )),
+                        self,
+                    )),
+                }
+            }
+        }
+    }
+
     pub fn pick_implementation(
         &self,
         context: &ExecutionContext,
@@ -189,57 +253,22 @@ impl WfDataType for WfFunctionCall {
         false
     }
 
+    /// Panics are caught here, so the error trace still point to the function call that panicked.
     fn evaluate_one_step(
         self,
         context: &ExecutionContext,
     ) -> Result<(WfData, bool, MaybeVec<TraceEntry>), (EvalError, Self)> {
-        let _function_recurse_guard = match context.check_can_run_function_and_acquire_guard() {
-            Ok(v) => v,
-            Err(e) => return Err((e, self)),
-        };
-        let implementation = match self.pick_implementation(context) {
-            Ok(i) => i,
-            Err(e) => return Err((e, self)),
-        };
-
-        match &implementation.0.r#impl {
-            ImplementationByKind::Composition(inner) => {
-                let inner_substituted =
-                    match inner.clone().substitute_function_arguments(&self, context) {
-                        Ok(v) => v,
-                        Err(e) => {
-                            return Err((
-                                e.trace(TraceEntry::DuringSubstitution(self.0.function.0.identity)),
-                                self,
-                            ));
-                        }
-                    };
-                Ok((
-                    inner_substituted,
-                    true,
-                    MaybeVec::One(TraceEntry::Substituted(self.0.function.0.identity)),
-                ))
-            }
-            ImplementationByKind::Code(_) => {
-                return Err((
-                    EvalError::unimplemented(format!(
-                        "code implementaiton (for {})",
-                        self.0.function.0.identity
-                    )),
-                    self,
-                ));
-            }
-            ImplementationByKind::Builtin(_) => {
-                match dispatch_builtins(self.0.function.0.identity, &self, context) {
-                    Ok(v) => Ok(v),
-                    Err(e) => Err((
-                        e.trace(TraceEntry::ProcessingNonCompositionFunction(
-                            self.0.function.0.identity,
-                        )),
-                        self,
-                    )),
-                }
-            }
+        let this = self.clone();
+        let mut data_on_error = None;
+        match catch_panic(|| {
+            self.evaluate_one_step_may_panic(context)
+                .map_err(|(e, data)| {
+                    data_on_error = Some(data);
+                    e
+                })
+        }) {
+            Ok(v) => Ok(v),
+            Err(e) => Err((e, data_on_error.unwrap_or(this))),
         }
     }
 
@@ -286,7 +315,7 @@ mod tests {
     use map_macro::btree_map;
 
     use crate::{
-        ExecutionContext, GlobalContext, KeyIndex, RcI,
+        EvalErrorKind, ExecutionContext, GlobalContext, KeyIndex, RcI, TraceEntry,
         data_types::{
             WfBoolean, WfData, WfDataType, WfFunctionCall, wf_function_call::FunctionCallOrType,
         },
@@ -354,4 +383,31 @@ mod tests {
         let evaluated = unparsed.evaluate(&context).unwrap();
         assert_eq!(evaluated, WfBoolean::new(false).into_wf_data());
     }
+
+    #[test]
+    fn test_panic_is_caught() {
+        let global_context = GlobalContext::default_for_test();
+        let context = ExecutionContext::default_for_global(RcI::new(global_context));
+
+        // parsing an object typed with an invalid type definition is not implemented, and panic
+        let panicking = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::from_map(btree_map! {
+                keyindex!(1, 1) => WfData::new_reference(zid!(4)),
+            }),
+        });
+        let mut unparsed_tree = get_unparsed_boolean_equality_true_false();
+        unparsed_tree.insert(keyindex!(844, 1), panicking);
+
+        let (error, _) = WfData::from_map(unparsed_tree)
+            .evaluate(&context)
+            .unwrap_err();
+        assert!(matches!(error.get_kind(), EvalErrorKind::InternalPanic(_)));
+        assert_eq!(
+            error.get_trace(),
+            &vec![
+                TraceEntry::InsideKey(keyindex!(844, 1)),
+                TraceEntry::ProcessingNonCompositionFunction(zid!(844)),
+            ]
+        );
+    }
 }
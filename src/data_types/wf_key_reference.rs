@@ -0,0 +1,125 @@
+use crate::{
+    EvalError, EvalErrorKind, ExecutionContext, KeyIndex,
+    data_types::{WfData, WfDataType, WfString},
+};
+
+/// A Z39 key reference, such as the one taken by Z803 (value by key)
+#[derive(Debug, Clone, PartialEq)]
+pub struct WfKeyReference {
+    pub key: KeyIndex,
+}
+
+impl WfKeyReference {
+    pub fn new(key: KeyIndex) -> Self {
+        Self { key }
+    }
+
+    pub fn parse(data: WfData, context: &ExecutionContext) -> Result<Self, (EvalError, WfData)> {
+        if let WfData::WfKeyReference(kr) = data {
+            return Ok(kr);
+        }
+        data.assert_evaluated();
+
+        match data.check_z1k1(zid!(39), context) {
+            Ok(_) => (),
+            Err(e) => return Err((e, data)),
+        };
+
+        let key_evaluated =
+            match get_value_from_data_err_handled!(data, keyindex!(39, 1)).evaluate(context) {
+                Ok(k) => k,
+                Err((e, _)) => return Err((e.inside_key(keyindex!(39, 1)), data)),
+            };
+
+        let key_as_string = match WfString::parse(key_evaluated, context) {
+            Ok(k) => k,
+            Err((e, _)) => return Err((e.inside_key(keyindex!(39, 1)), data)),
+        };
+
+        let key = match KeyIndex::from_str(&key_as_string.text) {
+            Ok(k) => k,
+            Err(e) => {
+                return Err((
+                    EvalError::from_kind(EvalErrorKind::ParseKeyIndex(e))
+                        .inside_key(keyindex!(39, 1)),
+                    data,
+                ));
+            }
+        };
+
+        Ok(Self { key })
+    }
+}
+
+impl WfDataType for WfKeyReference {
+    fn get_identity_zid_key(&self) -> Option<KeyIndex> {
+        None
+    }
+
+    fn list_keys(&self) -> Vec<KeyIndex> {
+        vec![keyindex!(1, 1), keyindex!(39, 1)]
+    }
+
+    fn get_key(&self, key: KeyIndex) -> Option<WfData> {
+        if key == keyindex!(1, 1) {
+            Some(WfData::new_reference(zid!(39)))
+        } else if key == keyindex!(39, 1) {
+            Some(WfString::new(&self.key.to_string()).into_wf_data())
+        } else {
+            None
+        }
+    }
+
+    fn into_wf_data(self) -> WfData {
+        WfData::WfKeyReference(self)
+    }
+
+    fn is_fully_realised(&self) -> bool {
+        true
+    }
+
+    fn substitute_function_arguments<I: super::util::SubstitutionInfo>(
+        self,
+        _info: &I,
+        _context: &ExecutionContext,
+    ) -> Result<WfData, EvalError> {
+        Ok(self.into_wf_data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map_macro::btree_map;
+
+    use crate::{
+        EvalErrorKind, ExecutionContext, GlobalContext, RcI,
+        data_types::{WfData, WfDataType, WfKeyReference, WfString},
+    };
+
+    #[test]
+    fn test_parse() {
+        let global_context = GlobalContext::default_for_test();
+        let context = ExecutionContext::default_for_global(RcI::new(global_context));
+
+        let unparsed_data = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(39)),
+            keyindex!(39, 1) => WfString::new("Z40K1").into_wf_data(),
+        });
+        assert_eq!(
+            WfKeyReference::parse(unparsed_data.clone(), &context).unwrap(),
+            WfKeyReference::new(keyindex!(40, 1))
+        );
+        // also reachable through evaluation of the untyped object
+        assert_eq!(
+            unparsed_data.evaluate(&context).unwrap(),
+            WfKeyReference::new(keyindex!(40, 1)).into_wf_data()
+        );
+
+        let invalid_key = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(39)),
+            keyindex!(39, 1) => WfString::new("hello").into_wf_data(),
+        });
+        let (error, _) = WfKeyReference::parse(invalid_key, &context).unwrap_err();
+        assert!(matches!(error.get_kind(), EvalErrorKind::ParseKeyIndex(_)));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{
+    EvalError, ExecutionContext, KeyIndex, RcI,
+    data_types::{WfData, WfDataType},
+};
+
+/// A Z99 quote. The quoted data is kept as-is: it is neither evaluated nor substituted into.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WfQuote {
+    pub quoted: RcI<WfData>,
+}
+
+impl WfQuote {
+    pub fn new(quoted: WfData) -> Self {
+        Self {
+            quoted: RcI::new(quoted),
+        }
+    }
+
+    pub fn parse(data: WfData, context: &ExecutionContext) -> Result<Self, (EvalError, WfData)> {
+        if let WfData::WfQuote(quote) = data {
+            return Ok(quote);
+        }
+        data.assert_evaluated();
+
+        match data.check_z1k1(zid!(99), context) {
+            Ok(_) => (),
+            Err(e) => return Err((e, data)),
+        };
+
+        let quoted = get_value_from_data_err_handled!(data, keyindex!(99, 1));
+
+        Ok(Self::new(quoted))
+    }
+}
+
+impl WfDataType for WfQuote {
+    fn get_identity_zid_key(&self) -> Option<KeyIndex> {
+        None
+    }
+
+    fn list_keys(&self) -> Vec<KeyIndex> {
+        vec![keyindex!(1, 1), keyindex!(99, 1)]
+    }
+
+    fn get_key(&self, key: KeyIndex) -> Option<WfData> {
+        if key == keyindex!(1, 1) {
+            Some(WfData::new_reference(zid!(99)))
+        } else if key == keyindex!(99, 1) {
+            Some((*self.quoted).clone())
+        } else {
+            None
+        }
+    }
+
+    fn into_wf_data(self) -> WfData {
+        WfData::WfQuote(self)
+    }
+
+    fn is_fully_realised(&self) -> bool {
+        // the quoted data may itself be in a non-canonical form
+        false
+    }
+
+    fn substitute_function_arguments<I: super::util::SubstitutionInfo>(
+        self,
+        _info: &I,
+        _context: &ExecutionContext,
+    ) -> Result<WfData, EvalError> {
+        Ok(self.into_wf_data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map_macro::btree_map;
+
+    use crate::{
+        EvalError, ExecutionContext, GlobalContext, RcI,
+        data_types::{WfArgumentReference, WfData, WfDataType, WfQuote, util::SubstitutionInfo},
+    };
+
+    struct NoSubstitution;
+
+    impl SubstitutionInfo for NoSubstitution {
+        fn get_for_pos(&self, pos: u32) -> Result<WfData, EvalError> {
+            panic!("tried to substitute argument {} inside a quote", pos)
+        }
+    }
+
+    #[test]
+    fn test_quote_is_left_alone() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+
+        let quoted = WfArgumentReference {
+            key_id: keyindex!(10000, 1),
+        }
+        .into_wf_data();
+        let unparsed_data = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(99)),
+            keyindex!(99, 1) => quoted.clone(),
+        });
+
+        // evaluation stops at the quote
+        let evaluated = unparsed_data.clone().evaluate(&context).unwrap();
+        assert_eq!(evaluated, WfQuote::new(quoted.clone()).into_wf_data());
+
+        // neither the parsed quote nor the untyped one are substituted into
+        for data in [evaluated, unparsed_data] {
+            assert_eq!(
+                data.substitute_function_arguments(&NoSubstitution, &context)
+                    .unwrap()
+                    .get_key(keyindex!(99, 1)),
+                Some(quoted.clone())
+            );
+        }
+    }
+}
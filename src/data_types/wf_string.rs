@@ -23,7 +23,7 @@ impl WfString {
             Err(e) => return Err((e, data)),
         };
         let below_string = match data.get_key_err(keyindex!(6, 1)) {
-            Err(e) => return Err((e.inside_key(keyindex!(6, 1)), data)),
+            Err(e) => return Err((e, data)),
             Ok(v) => match v.evaluate(context) {
                 Err((e, _)) => return Err((e.inside_key(keyindex!(6, 1)), data)),
                 Ok(v) => match WfString::parse(v, context) {
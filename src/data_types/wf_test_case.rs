@@ -1,5 +1,5 @@
 use crate::{
-    EvalError, EvalErrorKind, ExecutionContext, KeyIndex, RcI,
+    EvalError, EvalErrorKind, ExecutionContext, KeyIndex, RcI, Zid,
     data_types::{
         WfBoolean, WfData, WfDataType, WfFunction, WfFunctionCall, util::SubstitutionInfo,
         wf_function_call::FunctionCallOrType,
@@ -65,8 +65,40 @@ impl WfTestCase {
         }
     }
 
+    /// Fetch the test case with the given ZID from the global context and parse it.
+    pub fn load(zid: Zid, context: &ExecutionContext) -> Result<Self, EvalError> {
+        let test_case = match WfData::new_reference(zid).evaluate(context) {
+            Ok(v) => v,
+            Err((e, _)) => return Err(e),
+        };
+        match Self::parse(test_case, context) {
+            Ok(test_case) => Ok(test_case),
+            Err((e, _)) => Err(e.inside_reference_to(zid)),
+        }
+    }
+
     pub fn run_test(self, context: &ExecutionContext) -> Result<(), EvalError> {
-        let test_result = match self.0.call.clone().evaluate(context) {
+        self.run_test_with_call_context(context, context)
+    }
+
+    /// Like run_test, but the call (Z20K2) is evaluated with `function` forced to use `implementation`.
+    /// The validator (Z20K3) still use the preferred implementations, so a broken implementation can’t validate itself.
+    pub fn run_test_with_forced_implementation(
+        self,
+        function: Zid,
+        implementation: Zid,
+        context: &ExecutionContext,
+    ) -> Result<(), EvalError> {
+        let call_context = context.new_with_forced_implementation(function, implementation);
+        self.run_test_with_call_context(&call_context, context)
+    }
+
+    fn run_test_with_call_context(
+        self,
+        call_context: &ExecutionContext,
+        context: &ExecutionContext,
+    ) -> Result<(), EvalError> {
+        let test_result = match self.0.call.clone().evaluate(call_context) {
             Ok(v) => v,
             Err((e, _)) => return Err(e.inside_key(keyindex!(20, 2))),
         };
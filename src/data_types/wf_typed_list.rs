@@ -21,6 +21,9 @@ pub struct WfTypedListInner {
 
 //TODO: study if that is really better than a linked list.
 
+/// Number of entries per group when building a long list by chunks (see WfTypedList::from_chunks)
+pub const LIST_CHUNK_SIZE: usize = 1024;
+
 /// The type may be either evaluated and checked to be valid, or unevaluated.
 /// When evaluating a WfTypedList whose type is unparsed, it parse it, but does not further check the entries correspond to that type until they are themselve evaluated.
 #[derive(Debug, Clone, PartialEq)]
@@ -46,16 +49,115 @@ impl WfTypedList {
         }
     }
 
-    pub fn parse(data: WfData, _context: &ExecutionContext) -> Result<Self, (EvalError, WfData)> {
+    /// Build a list whose entries are split in several chained groups, so a very long list isn’t one single huge allocation.
+    /// Empty chunks are skipped.
+    pub fn from_chunks(r#type: MaybeEvaluated<WfTypeGeneric>, chunks: Vec<Vec<WfData>>) -> Self {
+        let mut next_chain = None;
+        for chunk in chunks.into_iter().rev().filter(|chunk| !chunk.is_empty()) {
+            next_chain = Some(RcI::new(WfTypedListInner {
+                entries: RcI::new(chunk),
+                chain_into: next_chain,
+            }));
+        }
+        Self {
+            inner: next_chain.unwrap_or_else(|| {
+                RcI::new(WfTypedListInner {
+                    entries: RcI::new(Vec::new()),
+                    chain_into: None,
+                })
+            }),
+            inner_type: RcI::new(r#type),
+            start_position: 0,
+        }
+    }
+
+    /// assume the data is dereferenced (but may be untyped, with the K1/K2 linked list form)
+    /// The K2 chain is walked iteratively, checking each tail has the same entry type as the outer list.
+    pub fn parse(data: WfData, context: &ExecutionContext) -> Result<Self, (EvalError, WfData)> {
         match data {
             WfData::WfTypedList(d) => return Ok(d),
             _ => (),
         };
+        data.assert_evaluated();
+
+        let tail_key = KeyIndex::from_u32s_panic(None, Some(2));
+        // wrap an error raised in the cell at that depth of the K2 chain
+        let wrap_at_depth = |mut e: EvalError, depth: usize| {
+            for _ in 0..depth {
+                e = e.inside_key(tail_key);
+            }
+            e
+        };
+        let type_mismatch = |depth: usize| {
+            wrap_at_depth(
+                EvalError::from_kind(EvalErrorKind::TypeDoesNotMatch).inside_key(keyindex!(1, 1)),
+                depth,
+            )
+        };
+
+        let inner_type = match Self::parse_entry_type(&data, context) {
+            Ok(v) => v,
+            Err(e) => return Err((e, data)),
+        };
+        let mut entries = Vec::new();
+        let mut cell: Option<WfData> = None;
+        let mut depth = 0;
+        loop {
+            let current = cell.as_ref().unwrap_or(&data);
+            let head = match current.get_key(KeyIndex::from_u32s_panic(None, Some(1))) {
+                Some(head) => head,
+                None => break,
+            };
+            entries.push(head);
+            let tail = match current.get_key_err(tail_key) {
+                Ok(tail) => tail,
+                Err(e) => return Err((wrap_at_depth(e, depth), data)),
+            };
+            // an untyped cell is walked by this loop rather than evaluated, as evaluating it would parse the rest of the list recursively
+            let tail = if tail.should_be_evaluated_before_parsing() {
+                match tail.evaluate(context) {
+                    Ok(tail) => tail,
+                    Err((e, _)) => return Err((wrap_at_depth(e, depth + 1), data)),
+                }
+            } else {
+                tail
+            };
+            depth += 1;
+            if let WfData::WfTypedList(tail) = &tail {
+                if let MaybeEvaluated::Valid(tail_type) = &*tail.inner_type
+                    && *tail_type != inner_type
+                {
+                    return Err((type_mismatch(depth), data));
+                }
+                entries.extend(tail.iter());
+                break;
+            }
+            match Self::parse_entry_type(&tail, context) {
+                Ok(tail_type) if tail_type == inner_type => (),
+                Ok(_) => {
+                    return Err((type_mismatch(depth), data));
+                }
+                Err(e) => return Err((wrap_at_depth(e, depth), data)),
+            }
+            cell = Some(tail);
+        }
+
+        Ok(Self::new(MaybeEvaluated::Valid(inner_type), entries))
+    }
 
-        return Err((
-            EvalError::unimplemented("parsing typed list from key".to_string()),
-            data,
-        ));
+    /// Parse the Z1K1 of a K1/K2 list cell, returning the type of its entries.
+    fn parse_entry_type(
+        cell: &WfData,
+        context: &ExecutionContext,
+    ) -> Result<WfTypeGeneric, EvalError> {
+        let unparsed_type = cell
+            .get_key_err(keyindex!(1, 1))?
+            .evaluate(context)
+            .map_err(|(e, _)| e.inside_key(keyindex!(1, 1)))?;
+        match WfTypedListType::parse(unparsed_type, context) {
+            Ok(list_type) => Ok(list_type.get_inner_type().clone()),
+            Err((e, _)) => Err(e.inside_key(keyindex!(1, 1))),
+        }
     }
 
     pub fn len(&self) -> usize {
@@ -153,7 +255,16 @@ impl WfDataType for WfTypedList {
     fn get_key(&self, key: KeyIndex) -> Option<WfData> {
         if key == keyindex!(1, 1) {
             match &*self.inner_type {
-                MaybeEvaluated::Unchecked(v) => Some(v.clone()),
+                // the stored type is the one of the entries, not the one of the list
+                MaybeEvaluated::Unchecked(v) => Some(WfData::from_map(
+                    [
+                        (keyindex!(1, 1), WfData::new_reference(zid!(7))),
+                        (keyindex!(7, 1), WfData::new_reference(zid!(881))),
+                        (keyindex!(881, 1), v.clone()),
+                    ]
+                    .into_iter()
+                    .collect(),
+                )),
                 MaybeEvaluated::Valid(v) => Some(WfTypedListType::new(v.clone()).into_wf_data()),
             }
         } else if key == KeyIndex::from_u32s_panic(None, Some(1)) {
@@ -180,7 +291,15 @@ impl WfDataType for WfTypedList {
     }
 
     fn list_keys(&self) -> Vec<KeyIndex> {
-        todo!();
+        if self.is_empty() {
+            vec![keyindex!(1, 1)]
+        } else {
+            vec![
+                keyindex!(1, 1),
+                KeyIndex::from_u32s_panic(None, Some(1)),
+                KeyIndex::from_u32s_panic(None, Some(2)),
+            ]
+        }
     }
 
     fn is_fully_realised(&self) -> bool {
@@ -340,3 +459,78 @@ impl<'l> Iterator for WfTypedListCheckedIterator<'l> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use map_macro::btree_map;
+
+    use crate::{
+        EvalErrorKind, ExecutionContext, GlobalContext, KeyIndex, RcI, Zid,
+        data_types::{
+            WfData, WfDataType, WfTypedList,
+            types_def::{WfTypeGeneric, WfTypedListType},
+        },
+        eval_error::TraceEntry,
+    };
+
+    fn list_type_data(entry_type: Zid) -> WfData {
+        WfTypedListType::new(WfTypeGeneric::WfStandardType(
+            GlobalContext::standard_type_for_test(entry_type),
+        ))
+        .into_wf_data()
+    }
+
+    /// Build the K1/K2 form of a list, with the types of each cell from the innermost one.
+    fn linked_list(cell_types: &[Zid], entry: WfData) -> WfData {
+        let mut cells = cell_types.iter().rev();
+        let mut list = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => list_type_data(*cells.next().unwrap()),
+        });
+        for cell_type in cells {
+            list = WfData::from_map(btree_map! {
+                keyindex!(1, 1) => list_type_data(*cell_type),
+                KeyIndex::from_u32s_panic(None, Some(1)) => entry.clone(),
+                KeyIndex::from_u32s_panic(None, Some(2)) => list,
+            });
+        }
+        list
+    }
+
+    #[test]
+    fn test_parse_long_linked_list() {
+        let global_context = GlobalContext::default_for_test();
+        let context = ExecutionContext::default_for_global(RcI::new(global_context));
+
+        // deep enough for a recursive parse to overflow the stack of a test thread
+        let list = linked_list(&[zid!(40); 1001], WfData::new_reference(zid!(41)));
+        let parsed = WfTypedList::parse(list, &context).unwrap();
+        assert_eq!(parsed.len(), 1000);
+        assert!(
+            parsed
+                .iter()
+                .all(|entry| entry == WfData::new_reference(zid!(41)))
+        );
+    }
+
+    #[test]
+    fn test_parse_tail_type_mismatch() {
+        let global_context = GlobalContext::default_for_test();
+        let context = ExecutionContext::default_for_global(RcI::new(global_context));
+
+        let list = linked_list(
+            &[zid!(40), zid!(40), zid!(6), zid!(6)],
+            WfData::new_reference(zid!(41)),
+        );
+        let tail_key = KeyIndex::from_u32s_panic(None, Some(2));
+        let (error, _) = WfTypedList::parse(list, &context).unwrap_err();
+        assert_eq!(error.get_kind(), &EvalErrorKind::TypeDoesNotMatch);
+        assert_eq!(
+            error.get_trace(),
+            &vec![
+                TraceEntry::InsideKey(keyindex!(1, 1)),
+                TraceEntry::InsideKey(tail_key),
+                TraceEntry::InsideKey(tail_key),
+            ]
+        );
+    }
+}
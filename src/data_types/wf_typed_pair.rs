@@ -0,0 +1,220 @@
+use crate::{
+    EvalError, ExecutionContext, KeyIndex, RcI,
+    data_types::{
+        MaybeEvaluated, WfData, WfDataType, types_def::WfTypedPairType, util::SubstitutionInfo,
+    },
+    eval_error::TraceEntry,
+    util::MaybeVec,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WfTypedPairInner {
+    /// The Z1K1 of the pair (a Z882 function call), checked once evaluated
+    pub r#type: MaybeEvaluated<WfTypedPairType>,
+    pub first: WfData,
+    pub second: WfData,
+}
+
+/// A pair, whose type is created with Z882. The elements are stored in K1 and K2.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WfTypedPair(pub RcI<WfTypedPairInner>);
+
+impl WfTypedPair {
+    pub fn new(r#type: MaybeEvaluated<WfTypedPairType>, first: WfData, second: WfData) -> Self {
+        Self(RcI::new(WfTypedPairInner {
+            r#type,
+            first,
+            second,
+        }))
+    }
+
+    /// Create the type data (not evaluated) for a pair of the two given types
+    pub fn type_data_for(first: WfData, second: WfData) -> WfData {
+        WfData::from_map(
+            [
+                (keyindex!(1, 1), WfData::new_reference(zid!(7))),
+                (keyindex!(7, 1), WfData::new_reference(zid!(882))),
+                (keyindex!(882, 1), first),
+                (keyindex!(882, 2), second),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    pub fn parse(data: WfData, context: &ExecutionContext) -> Result<Self, (EvalError, WfData)> {
+        if let WfData::WfTypedPair(pair) = data {
+            return Ok(pair);
+        }
+        data.assert_evaluated();
+
+        let r#type = match get_value_from_data_err_handled!(data, keyindex!(1, 1)).evaluate(context)
+        {
+            Err((e, _)) => return Err((e.inside_key(keyindex!(1, 1)), data)),
+            Ok(unparsed_type) => match WfTypedPairType::parse(unparsed_type, context) {
+                Err((e, _)) => return Err((e.inside_key(keyindex!(1, 1)), data)),
+                Ok(v) => v,
+            },
+        };
+        let first =
+            get_value_from_data_err_handled!(data, KeyIndex::from_u32s_panic(None, Some(1)));
+        let second =
+            get_value_from_data_err_handled!(data, KeyIndex::from_u32s_panic(None, Some(2)));
+
+        Ok(Self::new(MaybeEvaluated::Valid(r#type), first, second))
+    }
+}
+
+impl WfDataType for WfTypedPair {
+    fn into_wf_data(self) -> WfData {
+        WfData::WfTypedPair(self)
+    }
+
+    fn get_identity_zid_key(&self) -> Option<KeyIndex> {
+        None
+    }
+
+    fn get_key(&self, key: KeyIndex) -> Option<WfData> {
+        if key == keyindex!(1, 1) {
+            match &self.0.r#type {
+                MaybeEvaluated::Unchecked(v) => Some(v.clone()),
+                MaybeEvaluated::Valid(v) => Some(v.clone().into_wf_data()),
+            }
+        } else if key == KeyIndex::from_u32s_panic(None, Some(1)) {
+            Some(self.0.first.clone())
+        } else if key == KeyIndex::from_u32s_panic(None, Some(2)) {
+            Some(self.0.second.clone())
+        } else {
+            None
+        }
+    }
+
+    fn list_keys(&self) -> Vec<KeyIndex> {
+        vec![
+            keyindex!(1, 1),
+            KeyIndex::from_u32s_panic(None, Some(1)),
+            KeyIndex::from_u32s_panic(None, Some(2)),
+        ]
+    }
+
+    fn is_fully_realised(&self) -> bool {
+        false
+    }
+
+    fn should_be_evaluated_before_parsing(&self) -> bool {
+        matches!(self.0.r#type, MaybeEvaluated::Unchecked(_))
+    }
+
+    fn evaluate_one_step(
+        self,
+        context: &ExecutionContext,
+    ) -> Result<(WfData, bool, MaybeVec<TraceEntry>), (EvalError, Self)> {
+        let type_unchecked = match &self.0.r#type {
+            MaybeEvaluated::Unchecked(v) => v.clone(),
+            MaybeEvaluated::Valid(_) => {
+                return Ok((self.into_wf_data(), false, MaybeVec::default()));
+            }
+        };
+
+        let type_evaluated = match type_unchecked.evaluate(context) {
+            Ok(v) => v,
+            Err((e, _)) => return Err((e.inside_key(keyindex!(1, 1)), self)),
+        };
+        let checked_type = match WfTypedPairType::parse(type_evaluated, context) {
+            Ok(v) => v,
+            Err((e, _)) => return Err((e.inside_key(keyindex!(1, 1)), self)),
+        };
+
+        Ok((
+            Self::new(
+                MaybeEvaluated::Valid(checked_type),
+                self.0.first.clone(),
+                self.0.second.clone(),
+            )
+            .into_wf_data(),
+            false,
+            MaybeVec::default(),
+        ))
+    }
+
+    fn substitute_function_arguments<I: SubstitutionInfo>(
+        self,
+        info: &I,
+        context: &ExecutionContext,
+    ) -> Result<WfData, EvalError> {
+        let r#type = match &self.0.r#type {
+            MaybeEvaluated::Unchecked(v) => MaybeEvaluated::Unchecked(
+                v.clone()
+                    .substitute_function_arguments(info, context)
+                    .map_err(|e| e.inside_key(keyindex!(1, 1)))?,
+            ),
+            MaybeEvaluated::Valid(v) => MaybeEvaluated::Valid(
+                match WfTypedPairType::parse(
+                    v.clone()
+                        .substitute_function_arguments(info, context)
+                        .map_err(|e| e.inside_key(keyindex!(1, 1)))?,
+                    context,
+                ) {
+                    Ok(v) => v,
+                    Err((e, _)) => return Err(e.inside_key(keyindex!(1, 1))),
+                },
+            ),
+        };
+        let first = self
+            .0
+            .first
+            .clone()
+            .substitute_function_arguments(info, context)
+            .map_err(|e| e.inside_key(KeyIndex::from_u32s_panic(None, Some(1))))?;
+        let second = self
+            .0
+            .second
+            .clone()
+            .substitute_function_arguments(info, context)
+            .map_err(|e| e.inside_key(KeyIndex::from_u32s_panic(None, Some(2))))?;
+
+        Ok(Self::new(r#type, first, second).into_wf_data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map_macro::btree_map;
+
+    use crate::{
+        ExecutionContext, GlobalContext, KeyIndex, RcI,
+        data_types::{MaybeEvaluated, WfBoolean, WfData, WfDataType, WfTypedPair},
+    };
+
+    #[test]
+    fn test_parse_and_evaluate() {
+        let global_context = GlobalContext::default_for_test();
+        let context = ExecutionContext::default_for_global(RcI::new(global_context));
+
+        let pair_type = WfTypedPair::type_data_for(
+            WfData::new_reference(zid!(40)),
+            WfData::new_reference(zid!(40)),
+        );
+        let unparsed_data = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => pair_type.clone(),
+            KeyIndex::from_u32s_panic(None, Some(1)) => WfData::new_reference(zid!(41)),
+            KeyIndex::from_u32s_panic(None, Some(2)) => WfBoolean::new(false).into_wf_data(),
+        });
+
+        let evaluated = unparsed_data.evaluate(&context).unwrap();
+        let pair = WfTypedPair::parse(evaluated, &context).unwrap();
+        assert!(matches!(pair.0.r#type, MaybeEvaluated::Valid(_)));
+        assert_eq!(pair.0.first, WfData::new_reference(zid!(41)));
+
+        // a pair with an unchecked type get it checked when evaluated
+        let constructed = WfTypedPair::new(
+            MaybeEvaluated::Unchecked(pair_type),
+            pair.0.first.clone(),
+            pair.0.second.clone(),
+        );
+        assert_eq!(
+            constructed.into_wf_data().evaluate(&context).unwrap(),
+            pair.into_wf_data()
+        );
+    }
+}
@@ -3,8 +3,9 @@ use std::collections::BTreeMap;
 use crate::{
     EvalError, ExecutionContext, KeyIndex, RcI, Zid,
     data_types::{
-        WfArgumentReference, WfBoolean, WfData, WfDataType, WfFunction, WfFunctionCall,
-        WfImplementation, WfTestCase,
+        WfArgumentReference, WfBoolean, WfCodePoint, WfData, WfDataType, WfFunction,
+        WfFunctionCall, WfImplementation, WfKeyReference, WfQuote, WfString, WfTestCase,
+        WfTypedList, WfTypedPair,
         types_def::{WfStandardType, WfTypeGeneric},
         wf_function_call::FunctionCallOrType,
     },
@@ -70,13 +71,19 @@ impl WfDataType for WfUntyped {
             _ => return Err((EvalError::missing_key(keyindex!(1, 1)), self)),
         };
         match z1k1.get_type_zid(context) {
-            Ok((type_zid, _z1k1)) => {
+            Ok((type_zid, z1k1)) => {
                 if type_zid == zid!(4) {
                     match WfStandardType::parse(self.into_wf_data(), context) {
                         Ok(v) => return Ok((v.into_wf_data(), true, MaybeVec::default())),
                         Err((e, data)) => return Err((e, WfUntyped::parse(data))),
                     }
                 }
+                if type_zid == zid!(6) {
+                    match WfString::parse(self.into_wf_data(), context) {
+                        Ok(v) => return Ok((v.into_wf_data(), false, MaybeVec::default())),
+                        Err((e, data)) => return Err((e, WfUntyped::parse(data))),
+                    }
+                }
                 if type_zid == zid!(7) {
                     // function call, but may also be one of the typed type.
                     let fc_or_type = match WfFunctionCall::parse(self.into_wf_data(), context) {
@@ -114,11 +121,47 @@ impl WfDataType for WfUntyped {
                         Ok(v) => return Ok((v, false, MaybeVec::default())),
                         Err((e, data)) => return Err((e, WfUntyped::parse(data.into_wf_data()))),
                     };
+                } else if type_zid == zid!(39) {
+                    match WfKeyReference::parse(self.into_wf_data(), context) {
+                        Ok(v) => return Ok((v.into_wf_data(), false, MaybeVec::default())),
+                        Err((e, data)) => return Err((e, WfUntyped::parse(data))),
+                    }
                 } else if type_zid == zid!(40) {
                     match WfBoolean::parse(self.into_wf_data(), context) {
                         Ok(v) => return Ok((v.into_wf_data(), false, MaybeVec::default())),
                         Err((e, data)) => return Err((e, WfUntyped::parse(data))),
                     }
+                } else if type_zid == zid!(86) {
+                    match WfCodePoint::parse(self.into_wf_data(), context) {
+                        Ok(v) => return Ok((v.into_wf_data(), false, MaybeVec::default())),
+                        Err((e, data)) => return Err((e, WfUntyped::parse(data))),
+                    }
+                } else if type_zid == zid!(99) {
+                    match WfQuote::parse(self.into_wf_data(), context) {
+                        Ok(v) => return Ok((v.into_wf_data(), false, MaybeVec::default())),
+                        Err((e, data)) => return Err((e, WfUntyped::parse(data))),
+                    }
+                }
+                if type_zid != zid!(9) {
+                    // instance of a type without specialised representation. It is already a value.
+                    match z1k1.parse_type(context) {
+                        Ok(WfTypeGeneric::WfStandardType(r#type)) => {
+                            // the values aren’t checked (nor the validator run), but every declared key should be there
+                            let declared_keys = match r#type.get_declared_keys(context) {
+                                Ok(v) => v,
+                                Err(e) => return Err((e.inside_key(keyindex!(1, 1)), self)),
+                            };
+                            if let Some(missing) = declared_keys
+                                .into_iter()
+                                .find(|key| !self.entry.contains_key(key))
+                            {
+                                return Err((EvalError::missing_key(missing), self));
+                            }
+                            return Ok((self.into_wf_data(), false, MaybeVec::default()));
+                        }
+                        Ok(_) => (),
+                        Err((e, _)) => return Err((e.inside_key(keyindex!(1, 1)), self)),
+                    }
                 }
                 return Err((
                     EvalError::unimplemented(format!("parsing for {}", type_zid)),
@@ -138,7 +181,18 @@ impl WfDataType for WfUntyped {
                     WfTypeGeneric::WfStandardType(_) => {
                         unreachable!("standard type with zid should be reached earlier!")
                     }
-                    WfTypeGeneric::WfTypedListType(_) => todo!("zobject typed list parsing"),
+                    WfTypeGeneric::WfTypedListType(_) => {
+                        match WfTypedList::parse(self.into_wf_data(), context) {
+                            Ok(v) => Ok((v.into_wf_data(), false, MaybeVec::default())),
+                            Err((e, data)) => Err((e, WfUntyped::parse(data))),
+                        }
+                    }
+                    WfTypeGeneric::WfTypedPairType(_) => {
+                        match WfTypedPair::parse(self.into_wf_data(), context) {
+                            Ok(v) => Ok((v.into_wf_data(), false, MaybeVec::default())),
+                            Err((e, data)) => Err((e, WfUntyped::parse(data))),
+                        }
+                    }
                 }
             }
         }
@@ -201,3 +255,61 @@ impl WfDataType for WfUntyped {
         Ok(Self::new(new_entries).into_wf_data())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use map_macro::btree_map;
+
+    use crate::{
+        EvalErrorKind, ExecutionContext, GlobalContext, RcI,
+        data_types::{
+            MaybeEvaluated, WfData, WfDataType, WfString, WfTypedList,
+            types_def::{WfStandardType, WfStandardTypeInner},
+        },
+    };
+
+    #[test]
+    fn test_standard_type_instance_keys() {
+        let mut global_context = GlobalContext::default_for_test();
+        let key = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(3)),
+            keyindex!(3, 1) => WfData::new_reference(zid!(6)),
+            keyindex!(3, 2) => WfString::new("Z10100K1").into_wf_data(),
+        });
+        global_context.add_direct_no_persistent_data(
+            zid!(10100),
+            WfStandardType::from(WfStandardTypeInner {
+                identity_ref: zid!(10100),
+                keys: WfTypedList::new(
+                    MaybeEvaluated::Unchecked(WfData::new_reference(zid!(3))),
+                    vec![key],
+                )
+                .into_wf_data(),
+                validator: WfData::unvalid(EvalErrorKind::TestData),
+                equality: None,
+                display_function: None,
+                reading_function: None,
+                type_converters_to_code: None,
+                type_converters_from_code: None,
+            })
+            .into_wf_data(),
+        );
+        let context = ExecutionContext::default_for_global(RcI::new(global_context));
+
+        let instance = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(10100)),
+            keyindex!(10100, 1) => WfString::new("value").into_wf_data(),
+        });
+        assert_eq!(instance.clone().evaluate(&context).unwrap(), instance);
+
+        let malformed = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(10100)),
+            keyindex!(10100, 2) => WfString::new("value").into_wf_data(),
+        });
+        let (error, _) = malformed.evaluate(&context).unwrap_err();
+        assert_eq!(
+            error.get_kind(),
+            &EvalErrorKind::MissingKey(keyindex!(10100, 1))
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! References between persistent objects, to find out what is affected when some of them change, or what an object
+//! transitively depends on.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+};
+
+use crate::{
+    GlobalContext, Zid,
+    data_types::{WfData, WfDataType},
+};
+
+static NO_ZID: BTreeSet<Zid> = BTreeSet::new();
+
+/// Add every ZID referenced inside the data (walking `list_keys`/`get_key`) to the result
+pub fn collect_references(data: &WfData, result: &mut BTreeSet<Zid>) {
+    match data {
+        WfData::WfReference(reference) => {
+            result.insert(reference.to);
+        }
+        // their Z6K1 is themselves
+        WfData::WfString(_) => (),
+        // avoid building the tail of the list for every entry
+        WfData::WfTypedList(list) => {
+            if let Some(r#type) = data.get_key(keyindex!(1, 1)) {
+                collect_references(&r#type, result);
+            }
+            for entry in list.iter() {
+                collect_references(&entry, result);
+            }
+        }
+        other => {
+            for key in other.list_keys() {
+                if let Some(value) = other.get_key(key) {
+                    collect_references(&value, result);
+                }
+            }
+        }
+    }
+}
+
+/// The functions and test cases (Z20) that depend on some changed objects
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AffectedObjects {
+    pub functions: BTreeSet<Zid>,
+    pub test_cases: BTreeSet<Zid>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DependencyGraph {
+    references: BTreeMap<Zid, BTreeSet<Zid>>,
+    referenced_by: BTreeMap<Zid, BTreeSet<Zid>>,
+}
+
+impl DependencyGraph {
+    /// The graph of every object of the global context. References to an object from itself (such as a function
+    /// identity) are ignored.
+    pub fn new(global_context: &GlobalContext) -> Self {
+        let mut graph = Self::default();
+        for (zid, value) in global_context.objects.iter() {
+            let mut references = BTreeSet::new();
+            collect_references(value, &mut references);
+            references.remove(zid);
+            for reference in &references {
+                graph
+                    .referenced_by
+                    .entry(*reference)
+                    .or_default()
+                    .insert(*zid);
+            }
+            graph.references.insert(*zid, references);
+        }
+        graph
+    }
+
+    /// The ZIDs directly referenced by this object
+    pub fn references(&self, zid: &Zid) -> &BTreeSet<Zid> {
+        self.references.get(zid).unwrap_or(&NO_ZID)
+    }
+
+    /// The objects that directly reference this ZID (which may not exist)
+    pub fn referenced_by(&self, zid: &Zid) -> &BTreeSet<Zid> {
+        self.referenced_by.get(zid).unwrap_or(&NO_ZID)
+    }
+
+    /// The given ZIDs, and every object that transitively reference one of them
+    pub fn dependents<I: IntoIterator<Item = Zid>>(&self, zids: I) -> BTreeSet<Zid> {
+        let mut result = BTreeSet::new();
+        let mut to_visit: Vec<Zid> = zids.into_iter().collect();
+        while let Some(zid) = to_visit.pop() {
+            if result.insert(zid) {
+                to_visit.extend(self.referenced_by(&zid).iter().copied());
+            }
+        }
+        result
+    }
+
+    /// The given ZIDs, and every object they transitively reference (which may not exist)
+    pub fn dependencies<I: IntoIterator<Item = Zid>>(&self, zids: I) -> BTreeSet<Zid> {
+        let mut result = BTreeSet::new();
+        let mut to_visit: Vec<Zid> = zids.into_iter().collect();
+        while let Some(zid) = to_visit.pop() {
+            if result.insert(zid) {
+                to_visit.extend(self.references(&zid).iter().copied());
+            }
+        }
+        result
+    }
+
+    /// Every ZID of the graph, whether it is an object or only referenced
+    pub fn zids(&self) -> BTreeSet<Zid> {
+        self.references
+            .keys()
+            .chain(self.referenced_by.keys())
+            .copied()
+            .collect()
+    }
+
+    /// The strongly connected components (the objects that transitively reference each other, such as mutually
+    /// recursive functions), with Tarjan’s algorithm. A component only comes after those it references.
+    pub fn strongly_connected_components(&self) -> Vec<BTreeSet<Zid>> {
+        struct NodeState {
+            index: usize,
+            lowlink: usize,
+            on_stack: bool,
+        }
+
+        let mut states: BTreeMap<Zid, NodeState> = BTreeMap::new();
+        let mut stack = Vec::new();
+        let mut result = Vec::new();
+        for root in self.zids() {
+            if states.contains_key(&root) {
+                continue;
+            }
+            // iterative, as the chains of references in a dump are too long for the call stack. Each frame is a node
+            // and the references still to visit.
+            let mut frames = vec![(root, self.references(&root).iter())];
+            let index = states.len();
+            states.insert(
+                root,
+                NodeState {
+                    index,
+                    lowlink: index,
+                    on_stack: true,
+                },
+            );
+            stack.push(root);
+            while let Some((zid, references)) = frames.last_mut() {
+                let zid = *zid;
+                if let Some(reference) = references.next() {
+                    match states.get(reference) {
+                        None => {
+                            let index = states.len();
+                            states.insert(
+                                *reference,
+                                NodeState {
+                                    index,
+                                    lowlink: index,
+                                    on_stack: true,
+                                },
+                            );
+                            stack.push(*reference);
+                            frames.push((*reference, self.references(reference).iter()));
+                        }
+                        Some(state) if state.on_stack => {
+                            let index = state.index;
+                            let current = states.get_mut(&zid).expect("visited");
+                            current.lowlink = current.lowlink.min(index);
+                        }
+                        Some(_) => (),
+                    }
+                    continue;
+                }
+
+                frames.pop();
+                let state = &states[&zid];
+                let lowlink = state.lowlink;
+                if lowlink == state.index {
+                    let mut component = BTreeSet::new();
+                    while let Some(member) = stack.pop() {
+                        states.get_mut(&member).expect("visited").on_stack = false;
+                        component.insert(member);
+                        if member == zid {
+                            break;
+                        }
+                    }
+                    result.push(component);
+                }
+                if let Some((parent, _)) = frames.last() {
+                    let parent = states.get_mut(parent).expect("visited");
+                    parent.lowlink = parent.lowlink.min(lowlink);
+                }
+            }
+        }
+        result
+    }
+
+    /// Write the graph in the DOT format of Graphviz. If `only` is given, only those ZIDs and the references between
+    /// them are written.
+    pub fn write_dot<W: Write>(
+        &self,
+        mut writer: W,
+        only: Option<&BTreeSet<Zid>>,
+    ) -> Result<(), std::io::Error> {
+        let included = |zid: &Zid| only.is_none_or(|only| only.contains(zid));
+        writeln!(writer, "digraph dependencies {{")?;
+        for zid in self.zids().iter().filter(|zid| included(zid)) {
+            writeln!(writer, "    \"{}\";", zid)?;
+        }
+        for (zid, references) in self.references.iter().filter(|(zid, _)| included(zid)) {
+            for reference in references.iter().filter(|zid| included(zid)) {
+                writeln!(writer, "    \"{}\" -> \"{}\";", zid, reference)?;
+            }
+        }
+        writeln!(writer, "}}")
+    }
+
+    /// The functions and test cases among the dependents of the changed ZIDs
+    pub fn affected_by<I: IntoIterator<Item = Zid>>(
+        &self,
+        changed: I,
+        global_context: &GlobalContext,
+    ) -> AffectedObjects {
+        let mut result = AffectedObjects::default();
+        for zid in self.dependents(changed) {
+            let Some(value) = global_context.objects.get(&zid) else {
+                continue;
+            };
+            match value.get_key(keyindex!(1, 1)) {
+                Some(WfData::WfReference(r#type)) if r#type.to == zid!(8) => {
+                    result.functions.insert(zid);
+                }
+                Some(WfData::WfReference(r#type)) if r#type.to == zid!(20) => {
+                    result.test_cases.insert(zid);
+                }
+                _ => (),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{dependency_graph::DependencyGraph, test_support::fuzzing_pages_context};
+
+    #[test]
+    fn test_dependency_graph() {
+        let mut global_context = fuzzing_pages_context();
+        global_context
+            .add_from_json(
+                "Z10021",
+                r#"{"Z1K1": "Z2", "Z2K2": {"Z1K1": "Z20", "Z20K1": "Z10001", "Z20K2": {"Z1K1": "Z7", "Z7K1": "Z10001", "Z10001K1": "Z41"}, "Z20K3": {"Z1K1": "Z7", "Z7K1": "Z844", "Z844K2": "Z42"}}}"#,
+            )
+            .unwrap();
+        let graph = DependencyGraph::new(&global_context);
+
+        assert_eq!(
+            graph.references(&zid!(10011)),
+            &BTreeSet::from([
+                zid!(7),
+                zid!(14),
+                zid!(18),
+                zid!(42),
+                zid!(844),
+                zid!(10001)
+            ])
+        );
+        assert_eq!(
+            graph.referenced_by(&zid!(844)),
+            &BTreeSet::from([zid!(10011), zid!(10012), zid!(10021)])
+        );
+        assert!(graph.referenced_by(&zid!(99999)).is_empty());
+        // the function and its implementation reference each other
+        assert_eq!(
+            graph.dependents([zid!(10011)]),
+            BTreeSet::from([zid!(10001), zid!(10011), zid!(10021)])
+        );
+
+        let affected = graph.affected_by([zid!(10011)], &global_context);
+        assert_eq!(affected.functions, BTreeSet::from([zid!(10001)]));
+        assert_eq!(affected.test_cases, BTreeSet::from([zid!(10021)]));
+        let affected = graph.affected_by([zid!(844)], &global_context);
+        assert_eq!(
+            affected.functions,
+            BTreeSet::from([zid!(10001), zid!(10002)])
+        );
+
+        let dependencies = graph.dependencies([zid!(10021)]);
+        assert!(dependencies.is_superset(&BTreeSet::from([
+            zid!(10001),
+            zid!(10011),
+            zid!(10021),
+            zid!(844)
+        ])));
+        assert!(!dependencies.contains(&zid!(10002)));
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let global_context = fuzzing_pages_context();
+        let graph = DependencyGraph::new(&global_context);
+        let components = graph.strongly_connected_components();
+        // every ZID is in exactly one component
+        assert_eq!(
+            components.iter().map(BTreeSet::len).sum::<usize>(),
+            graph.zids().len()
+        );
+        let cycles: Vec<_> = components
+            .iter()
+            .filter(|component| component.len() > 1)
+            .collect();
+        assert_eq!(
+            cycles,
+            vec![
+                &BTreeSet::from([zid!(10001), zid!(10011)]),
+                &BTreeSet::from([zid!(10002), zid!(10012)])
+            ]
+        );
+        // a component comes after those it references
+        let position = |zid| {
+            components
+                .iter()
+                .position(|component| component.contains(&zid))
+                .unwrap()
+        };
+        for zid in graph.zids() {
+            for reference in graph.references(&zid) {
+                assert!(position(*reference) <= position(zid));
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_dot() {
+        let global_context = fuzzing_pages_context();
+        let graph = DependencyGraph::new(&global_context);
+        let mut dot = Vec::new();
+        graph
+            .write_dot(&mut dot, Some(&BTreeSet::from([zid!(10001), zid!(10011)])))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(dot).unwrap(),
+            "digraph dependencies {\n    \"Z10001\";\n    \"Z10011\";\n    \"Z10001\" -> \"Z10011\";\n    \"Z10011\" -> \"Z10001\";\n}\n"
+        );
+    }
+}
@@ -2,7 +2,10 @@ use std::{error::Error, fmt::Display};
 
 use thiserror::Error;
 
-use crate::{KeyIndex, KeyIndexParseError, Zid, data_types::WfData};
+use crate::{
+    KeyIndex, KeyIndexParseError, Zid,
+    data_types::{WfData, WfDataType, WfKeyReference, WfString},
+};
 
 #[derive(Error, Debug, PartialEq, Clone)]
 pub enum EvalErrorKind {
@@ -16,6 +19,8 @@ pub enum EvalErrorKind {
     WrongType(Zid, Zid),
     #[error("Incorrect identity reference for boolean {0}")]
     IncorrectIdentityForBoolean(Zid),
+    #[error("A code point should contain exactly one character, got {0:?}")]
+    NotASingleCodePoint(String),
     #[error("Persistent object {0} does not exist")]
     MissingPersistentObject(Zid),
     #[error("Not a standard type that can be expressed as just a ZID")]
@@ -61,6 +66,161 @@ pub enum EvalErrorKind {
     FunctionCallCountExceeded,
     #[error("This explictly invalid data shouldn’t be reached outside of unit test")]
     TestData,
+    #[error("Error raised by the evaluated code: {0:?}")]
+    /// A Z5 error object, raised with Z851 (or re-raised after being caught with Z850)
+    Raised(Box<WfData>),
+    #[error("Couldn’t fetch {0} from the object source: {1}")]
+    /// The object source failed (as opposed to not having the object), such as a network error
+    ObjectSourceFailed(Zid, String),
+    #[error("internal error, the interpreter panicked: {0}")]
+    /// A bug in the interpreter. Panic are caught at the function call level, so the trace still point to where it happened.
+    InternalPanic(String),
+}
+
+impl EvalErrorKind {
+    /// Name of the variant, without its content. Stable enough to be used in reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ParseKeyIndex(_) => "ParseKeyIndex",
+            Self::MissingKey(_) => "MissingKey",
+            Self::NotAReference => "NotAReference",
+            Self::WrongType(_, _) => "WrongType",
+            Self::IncorrectIdentityForBoolean(_) => "IncorrectIdentityForBoolean",
+            Self::NotASingleCodePoint(_) => "NotASingleCodePoint",
+            Self::MissingPersistentObject(_) => "MissingPersistentObject",
+            Self::NotStandardType => "NotStandardType",
+            Self::NoIdentity => "NoIdentity",
+            Self::TooManyArgsInFunction => "TooManyArgsInFunction",
+            Self::WrongTypeZidForType => "WrongTypeZidForType",
+            Self::ExpectedTypeGotFunction => "ExpectedTypeGotFunction",
+            Self::ExpectOnlyOneImplementation => "ExpectOnlyOneImplementation",
+            Self::ExpectOneImplementionFoundZero => "ExpectOneImplementionFoundZero",
+            Self::NoImplementationForFunction(_) => "NoImplementationForFunction",
+            Self::TooManyArguments(_, _) => "TooManyArguments",
+            Self::ArgumentReferenceNoKPart(_) => "ArgumentReferenceNoKPart",
+            Self::ArgumentReferenceTooLarge(_) => "ArgumentReferenceTooLarge",
+            Self::NoBuiltin(_) => "NoBuiltin",
+            Self::ExpectedFunctionCallGotType => "ExpectedFunctionCallGotType",
+            Self::TestCaseFailedWithFalse(_) => "TestCaseFailedWithFalse",
+            Self::CantGetHeadOfEmptyList => "CantGetHeadOfEmptyList",
+            Self::TypeDoesNotMatch => "TypeDoesNotMatch",
+            Self::Unimplemented(_) => "Unimplemented",
+            Self::RecursedTooDeep => "RecursedTooDeep",
+            Self::FunctionCallCountExceeded => "FunctionCallCountExceeded",
+            Self::TestData => "TestData",
+            Self::Raised(_) => "Raised",
+            Self::ObjectSourceFailed(_, _) => "ObjectSourceFailed",
+            Self::InternalPanic(_) => "InternalPanic",
+        }
+    }
+
+    /// Whether Z850 (try) can catch this error. Exceeding the limits, failing to fetch objects and interpreter bugs
+    /// can’t be caught, so they still abort the whole evaluation.
+    pub fn is_catchable(&self) -> bool {
+        !matches!(
+            self,
+            Self::RecursedTooDeep
+                | Self::FunctionCallCountExceeded
+                | Self::ObjectSourceFailed(_, _)
+                | Self::InternalPanic(_)
+        )
+    }
+
+    /// The ZID of the Z50 error type this error is reported as. Errors without a more specific standard error type
+    /// are reported as Z500 (generic error).
+    pub fn error_type_zid(&self) -> Zid {
+        match self {
+            Self::Unimplemented(_) | Self::NoBuiltin(_) | Self::NoImplementationForFunction(_) => {
+                zid!(503)
+            }
+            Self::MissingPersistentObject(_) => zid!(504),
+            Self::TooManyArguments(_, _) => zid!(505),
+            Self::MissingKey(_) => zid!(511),
+            Self::WrongType(_, _) => zid!(518),
+            Self::Raised(error) => match error.get_key(keyindex!(5, 1)) {
+                Some(WfData::WfReference(error_type)) => error_type.to,
+                _ => zid!(500),
+            },
+            _ => zid!(500),
+        }
+    }
+
+    /// Convert this error to a Z5 error object, whose Z5K2 is an instance of the Z50 error type (created with Z885).
+    pub fn to_error_object(&self) -> WfData {
+        let error_type = self.error_type_zid();
+        let arguments: Vec<WfData> = match self {
+            Self::Raised(error) => return (**error).clone(),
+            Self::MissingPersistentObject(zid) => {
+                vec![WfString::new(&zid.to_string()).into_wf_data()]
+            }
+            Self::TooManyArguments(provided, expected) => vec![
+                WfString::new(&expected.to_string()).into_wf_data(),
+                WfString::new(&provided.to_string()).into_wf_data(),
+            ],
+            Self::MissingKey(key) => vec![WfKeyReference::new(*key).into_wf_data()],
+            Self::WrongType(_, expected) => vec![WfData::new_reference(*expected)],
+            _ => vec![WfString::new(&self.to_string()).into_wf_data()],
+        };
+
+        let mut error_value = std::collections::BTreeMap::new();
+        error_value.insert(
+            keyindex!(1, 1),
+            WfData::from_map(
+                [
+                    (keyindex!(1, 1), WfData::new_reference(zid!(7))),
+                    (keyindex!(7, 1), WfData::new_reference(zid!(885))),
+                    (keyindex!(885, 1), WfData::new_reference(error_type)),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+        );
+        for (pos, argument) in arguments.into_iter().enumerate() {
+            error_value.insert(
+                KeyIndex::from_u32s_panic(Some(error_type.0.get()), Some(pos as u32 + 1)),
+                argument,
+            );
+        }
+
+        WfData::from_map(
+            [
+                (keyindex!(1, 1), WfData::new_reference(zid!(5))),
+                (keyindex!(5, 1), WfData::new_reference(error_type)),
+                (keyindex!(5, 2), WfData::from_map(error_value)),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    /// Whether this error is raised when interpreting data as the wrong kind of object, rather than by evaluating it.
+    /// In that case, the trace point to the offending data, which may evaluate fine (or fail differently) on its own.
+    pub fn is_raised_on_malformed_data(&self) -> bool {
+        matches!(
+            self,
+            Self::ParseKeyIndex(_)
+                | Self::MissingKey(_)
+                | Self::WrongType(_, _)
+                | Self::WrongTypeZidForType
+                | Self::NotAReference
+                | Self::IncorrectIdentityForBoolean(_)
+                | Self::NotASingleCodePoint(_)
+                | Self::NotStandardType
+                | Self::NoIdentity
+                | Self::ExpectedTypeGotFunction
+                | Self::ExpectedFunctionCallGotType
+                | Self::TypeDoesNotMatch
+        )
+    }
+
+    /// Whether the two errors should be considered the same failure (used to check a bug is still reproduced).
+    /// Same as equality, except the intermediate result of a failed test case is ignored.
+    pub fn is_same_kind(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::TestCaseFailedWithFalse(_), Self::TestCaseFailedWithFalse(_)) => true,
+            _ => self == other,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -80,6 +240,28 @@ pub enum TraceEntry {
     Text(String),
 }
 
+impl TraceEntry {
+    /// Short description of this step, without printing the (potentially huge) data it contains
+    pub fn get_action_text(&self) -> String {
+        match self {
+            Self::InsideKey(key) => format!("inside {}", key),
+            Self::InsideList(pos) => format!("inside list, at 0-indexed position {}", pos),
+            Self::InsideReference(zid) => format!("follow reference to {}", zid),
+            Self::CheckingTestCaseResult(_) => "checking result with validator".to_string(),
+            Self::ConvertingTestCaseValidatorResultToBoolean(_) => {
+                "converting validator result to boolean".to_string()
+            }
+            Self::DuringSubstitution(zid) => format!("doing substitution for function {}", zid),
+            Self::Substituted(zid) => format!("after substitution for function {}", zid),
+            Self::ProcessingNonCompositionFunction(zid) => {
+                format!("inside non-composition implementation for function {}", zid)
+            }
+            Self::ProcessingReconstructedData(_) => "using data generated on-the-fly".to_string(),
+            Self::Text(text) => text.clone(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct EvalError {
     kind: EvalErrorKind,
@@ -110,6 +292,17 @@ impl EvalError {
         return &self.trace;
     }
 
+    /// One line per trace entry, from the outermost to the innermost step, followed by the error itself.
+    pub fn render_trace(&self) -> String {
+        let mut result = String::new();
+        for entry in self.trace.iter().rev() {
+            result.push_str(&entry.get_action_text());
+            result.push_str(" -> ...\n");
+        }
+        result.push_str(&self.kind.to_string());
+        result
+    }
+
     pub fn trace(mut self, of: TraceEntry) -> Self {
         self.trace.push(of);
         self
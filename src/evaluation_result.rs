@@ -0,0 +1,220 @@
+//! Wrap a top-level evaluation into a Z22 evaluation result, in the shape returned by the official orchestrator: the
+//! result in Z22K1 (Z24, void, on failure) and a Z883 map of metadata in Z22K2.
+use std::time::{Duration, Instant};
+
+use sonic_rs::Value;
+
+use crate::{
+    EvalError, ExecutionContext, Zid,
+    data_types::{MaybeEvaluated, WfData, WfDataType, WfString, WfTypedList, WfTypedPair},
+    parsing::serialize_json::{serialize_to_string, serialize_value},
+    util::catch_panic,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluationMetadata {
+    /// Wall time spent evaluating
+    pub duration: Duration,
+    pub function_call_count: usize,
+    pub max_function_call_depth: usize,
+    /// ZID of the persistent implementations that were used
+    pub implementations: Vec<Zid>,
+}
+
+#[derive(Debug)]
+pub struct EvaluationResult {
+    pub result: Result<WfData, EvalError>,
+    pub metadata: EvaluationMetadata,
+}
+
+/// Evaluate the data in a fresh context (sharing the global context and forced implementations of the given one),
+/// so the metadata only cover this evaluation. Panics are caught and reported as errors.
+pub fn evaluate_with_metadata(data: WfData, context: &ExecutionContext) -> EvaluationResult {
+    let evaluation_context = context.new_recording_implementations();
+    let start = Instant::now();
+    let result = catch_panic(|| data.evaluate(&evaluation_context).map_err(|(e, _)| e));
+    let duration = start.elapsed();
+
+    EvaluationResult {
+        result,
+        metadata: EvaluationMetadata {
+            duration,
+            function_call_count: evaluation_context.get_function_call_count(),
+            max_function_call_depth: evaluation_context.get_max_function_call_depth(),
+            implementations: evaluation_context
+                .get_implementations_used()
+                .into_iter()
+                .collect(),
+        },
+    }
+}
+
+impl EvaluationResult {
+    /// The Z22 object. It is meant to be serialized rather than evaluated, as Z883 maps aren’t supported.
+    pub fn to_z22(&self) -> WfData {
+        let string = |text: String| WfString::new(&text).into_wf_data();
+        let mut entries = vec![
+            (
+                "orchestrationDuration",
+                string(format!("{} ms", self.metadata.duration.as_millis())),
+            ),
+            (
+                "functionCallCount",
+                string(self.metadata.function_call_count.to_string()),
+            ),
+            (
+                "maxFunctionCallDepth",
+                string(self.metadata.max_function_call_depth.to_string()),
+            ),
+            (
+                "implementationIds",
+                WfTypedList::new(
+                    MaybeEvaluated::Unchecked(WfData::new_reference(zid!(14))),
+                    self.metadata
+                        .implementations
+                        .iter()
+                        .map(|zid| WfData::new_reference(*zid))
+                        .collect(),
+                )
+                .into_wf_data(),
+            ),
+        ];
+        if let Err(e) = &self.result {
+            entries.push(("errors", e.get_kind().to_error_object()));
+        }
+
+        let pair_type = WfTypedPair::type_data_for(
+            WfData::new_reference(zid!(6)),
+            WfData::new_reference(zid!(1)),
+        );
+        let metadata = WfData::from_map(
+            [
+                (
+                    keyindex!(1, 1),
+                    WfData::from_map(
+                        [
+                            (keyindex!(1, 1), WfData::new_reference(zid!(7))),
+                            (keyindex!(7, 1), WfData::new_reference(zid!(883))),
+                            (keyindex!(883, 1), WfData::new_reference(zid!(6))),
+                            (keyindex!(883, 2), WfData::new_reference(zid!(1))),
+                        ]
+                        .into_iter()
+                        .collect(),
+                    ),
+                ),
+                (
+                    crate::KeyIndex::from_u32s_panic(None, Some(1)),
+                    WfTypedList::new(
+                        MaybeEvaluated::Unchecked(pair_type.clone()),
+                        entries
+                            .into_iter()
+                            .map(|(key, value)| {
+                                WfTypedPair::new(
+                                    MaybeEvaluated::Unchecked(pair_type.clone()),
+                                    WfString::new(key).into_wf_data(),
+                                    value,
+                                )
+                                .into_wf_data()
+                            })
+                            .collect(),
+                    )
+                    .into_wf_data(),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        WfData::from_map(
+            [
+                (keyindex!(1, 1), WfData::new_reference(zid!(22))),
+                (
+                    keyindex!(22, 1),
+                    match &self.result {
+                        Ok(value) => value.clone(),
+                        Err(_) => WfData::new_reference(zid!(24)),
+                    },
+                ),
+                (keyindex!(22, 2), metadata),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    pub fn to_json_value(&self) -> Value {
+        serialize_value(&self.to_z22())
+    }
+
+    pub fn to_json(&self) -> String {
+        serialize_to_string(&self.to_z22())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+
+    use crate::{
+        ExecutionContext, RcI,
+        data_types::{WfBoolean, WfData, WfDataType},
+        evaluation_result::evaluate_with_metadata,
+        test_support::function_call,
+    };
+
+    #[test]
+    fn test_evaluate_with_metadata() {
+        let context = ExecutionContext::default_for_global(RcI::new(
+            crate::fuzzing::fuzzing_global_context(),
+        ));
+
+        let result = evaluate_with_metadata(
+            function_call(
+                zid!(10001),
+                vec![function_call(
+                    zid!(10001),
+                    vec![WfData::new_reference(zid!(41))],
+                )],
+            ),
+            &context,
+        );
+        assert_eq!(
+            result.result.as_ref().unwrap(),
+            &WfBoolean::new(true).into_wf_data()
+        );
+        assert_eq!(
+            result.metadata.implementations,
+            vec![zid!(944), zid!(10011)]
+        );
+        assert!(result.metadata.function_call_count >= 4);
+        assert!(result.metadata.max_function_call_depth >= 2);
+        // the given context isn’t used
+        assert_eq!(context.get_function_call_count(), 0);
+
+        let json = result.to_json_value();
+        assert_eq!(json["Z1K1"].as_str(), Some("Z22"));
+        assert_eq!(json["Z22K1"].as_str(), Some("Z41"));
+        let metadata = json["Z22K2"]["K1"].as_array().unwrap();
+        assert_eq!(metadata[1]["K1"].as_str(), Some("orchestrationDuration"));
+    }
+
+    #[test]
+    fn test_error() {
+        let context = ExecutionContext::default_for_global(RcI::new(
+            crate::fuzzing::fuzzing_global_context(),
+        ));
+
+        let result = evaluate_with_metadata(
+            function_call(zid!(10001), vec![WfData::new_reference(zid!(12345))]),
+            &context,
+        );
+        assert!(result.result.is_err());
+
+        let json = result.to_json_value();
+        assert_eq!(json["Z22K1"].as_str(), Some("Z24"));
+        let metadata = json["Z22K2"]["K1"].as_array().unwrap();
+        let errors = metadata.iter().last().unwrap();
+        assert_eq!(errors["K1"].as_str(), Some("errors"));
+        assert_eq!(errors["K2"]["Z5K1"].as_str(), Some("Z504"));
+    }
+}
@@ -1,39 +1,154 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
-use crate::{EvalError, EvalErrorKind, GlobalContext, RcI};
+use crate::{EvalError, EvalErrorKind, GlobalContext, RcI, Zid};
+
+/// By default, function calls nested deeper than that fail with `EvalErrorKind::RecursedTooDeep`
+pub const MAX_FUNCTION_CALL_DEPTH: usize = 100;
+/// By default, an evaluation calling more functions than that fail with `EvalErrorKind::FunctionCallCountExceeded`
+pub const MAX_FUNCTION_CALL_COUNT: usize = 100_000;
+
+/// Limits of an evaluation, defaulting to `MAX_FUNCTION_CALL_DEPTH` and `MAX_FUNCTION_CALL_COUNT`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExecutionLimits {
+    pub max_function_call_depth: usize,
+    pub max_function_call_count: usize,
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        Self {
+            max_function_call_depth: MAX_FUNCTION_CALL_DEPTH,
+            max_function_call_count: MAX_FUNCTION_CALL_COUNT,
+        }
+    }
+}
 
 pub struct ExecutionContext {
     global_context: RcI<GlobalContext>,
+    limits: ExecutionLimits,
     function_call_depth: AtomicUsize,
     function_call_count: AtomicUsize,
+    /// the highest function_call_depth reached
+    max_function_call_depth: AtomicUsize,
+    /// ZID of the persistent implementations that were picked, for the evaluation metadata. Only collected if set (see new_recording_implementations).
+    implementations_used: Option<Mutex<BTreeSet<Zid>>>,
+    /// function ZID -> implementation ZID that should be used instead of the preffered one (used to test every implementation of a function)
+    forced_implementations: BTreeMap<Zid, Zid>,
 }
 
 impl ExecutionContext {
     pub fn default_for_global(global_context: RcI<GlobalContext>) -> Self {
         Self {
             global_context,
+            limits: ExecutionLimits::default(),
             function_call_depth: AtomicUsize::new(0),
             function_call_count: AtomicUsize::new(0),
+            max_function_call_depth: AtomicUsize::new(0),
+            implementations_used: None,
+            forced_implementations: BTreeMap::new(),
         }
     }
 
+    /// Create a new context sharing the same global context, limits and forced implementations, but with reset function call counters
+    pub fn new_fresh(&self) -> Self {
+        let mut result = Self::default_for_global(self.global_context.clone());
+        result.limits = self.limits;
+        result.forced_implementations = self.forced_implementations.clone();
+        result
+    }
+
+    /// Create a new, fresh context sharing the same global context and forced implementations, but with the given limits
+    pub fn new_with_limits(&self, limits: ExecutionLimits) -> Self {
+        let mut result = self.new_fresh();
+        result.limits = limits;
+        result
+    }
+
+    /// Create a new, fresh context like new_fresh, that also record which persistent implementations are used
+    pub fn new_recording_implementations(&self) -> Self {
+        let mut result = self.new_fresh();
+        result.implementations_used = Some(Mutex::new(BTreeSet::new()));
+        result
+    }
+
+    pub fn get_limits(&self) -> &ExecutionLimits {
+        &self.limits
+    }
+
+    /// Create a new, fresh context sharing the same global context, but where the given function will always use the given implementation
+    pub fn new_with_forced_implementation(&self, function: Zid, implementation: Zid) -> Self {
+        let mut result = self.new_fresh();
+        result
+            .forced_implementations
+            .insert(function, implementation);
+        result
+    }
+
     pub fn get_global(&self) -> &GlobalContext {
         &self.global_context
     }
 
+    pub fn get_forced_implementation(&self, function: &Zid) -> Option<Zid> {
+        self.forced_implementations.get(function).copied()
+    }
+
+    /// Number of function calls currently being evaluated (0 once the evaluation is finished)
+    pub fn get_function_call_depth(&self) -> usize {
+        self.function_call_depth.load(Ordering::Relaxed)
+    }
+
+    /// Number of function calls since this context was created (including the one that were refused due to the limit)
+    pub fn get_function_call_count(&self) -> usize {
+        self.function_call_count.load(Ordering::Relaxed)
+    }
+
+    /// Highest number of nested function calls reached since this context was created
+    pub fn get_max_function_call_depth(&self) -> usize {
+        self.max_function_call_depth.load(Ordering::Relaxed)
+    }
+
+    /// Does nothing unless the context was created with new_recording_implementations
+    pub fn record_implementation_used(&self, implementation: Zid) {
+        if let Some(implementations_used) = &self.implementations_used {
+            implementations_used.lock().unwrap().insert(implementation);
+        }
+    }
+
+    /// ZID of the persistent implementations picked since this context was created (inline implementations have no ZID).
+    /// Always empty unless the context was created with new_recording_implementations.
+    pub fn get_implementations_used(&self) -> BTreeSet<Zid> {
+        match &self.implementations_used {
+            Some(implementations_used) => implementations_used.lock().unwrap().clone(),
+            None => BTreeSet::new(),
+        }
+    }
+
     pub fn check_can_run_function_and_acquire_guard<'l>(
         &'l self,
     ) -> Result<FunctionCallDepthGuard<'l>, EvalError> {
-        if self.function_call_depth.fetch_add(1, Ordering::Relaxed) > 100 {
+        let previous_depth = self.function_call_depth.fetch_add(1, Ordering::Relaxed);
+        if previous_depth >= self.limits.max_function_call_depth {
             self.function_call_depth.fetch_sub(1, Ordering::Relaxed);
             return Err(EvalError::from_kind(EvalErrorKind::RecursedTooDeep));
         }
-        if self.function_call_count.fetch_add(1, Ordering::Relaxed) > 100_000 {
+        if self.function_call_count.fetch_add(1, Ordering::Relaxed)
+            >= self.limits.max_function_call_count
+        {
+            self.function_call_depth.fetch_sub(1, Ordering::Relaxed);
             return Err(EvalError::from_kind(
                 EvalErrorKind::FunctionCallCountExceeded,
             ));
         }
 
+        self.max_function_call_depth
+            .fetch_max(previous_depth + 1, Ordering::Relaxed);
+
         //TODO: also just do a timeout. Probably won’t need a function call count then.
 
         return Ok(FunctionCallDepthGuard {
@@ -51,3 +166,74 @@ impl<'l> Drop for FunctionCallDepthGuard<'l> {
         self.value.fetch_sub(1, Ordering::Relaxed);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EvalErrorKind, ExecutionContext, ExecutionLimits, GlobalContext, MAX_FUNCTION_CALL_COUNT,
+        RcI,
+    };
+
+    #[test]
+    fn test_depth_restored_when_count_exceeded() {
+        let context = ExecutionContext::default_for_global(RcI::new(GlobalContext::default()));
+        for _ in 0..MAX_FUNCTION_CALL_COUNT {
+            let _guard = context.check_can_run_function_and_acquire_guard().unwrap();
+        }
+        assert_eq!(
+            context
+                .check_can_run_function_and_acquire_guard()
+                .err()
+                .unwrap()
+                .get_kind(),
+            &EvalErrorKind::FunctionCallCountExceeded
+        );
+        assert_eq!(context.get_function_call_depth(), 0);
+    }
+
+    #[test]
+    fn test_custom_limits() {
+        let context = ExecutionContext::default_for_global(RcI::new(GlobalContext::default()))
+            .new_with_limits(ExecutionLimits {
+                max_function_call_depth: 2,
+                max_function_call_count: 100,
+            });
+        // exactly max_function_call_depth nested calls are allowed
+        let _guards: Vec<_> = (0..2)
+            .map(|_| context.check_can_run_function_and_acquire_guard().unwrap())
+            .collect();
+        assert_eq!(
+            context
+                .check_can_run_function_and_acquire_guard()
+                .err()
+                .unwrap()
+                .get_kind(),
+            &EvalErrorKind::RecursedTooDeep
+        );
+        assert_eq!(context.get_max_function_call_depth(), 2);
+        // kept by new_fresh
+        assert_eq!(context.new_fresh().get_limits().max_function_call_depth, 2);
+    }
+
+    #[test]
+    fn test_recording_implementations_is_opt_in() {
+        let context = ExecutionContext::default_for_global(RcI::new(GlobalContext::default()));
+        context.record_implementation_used(zid!(10011));
+        assert!(context.get_implementations_used().is_empty());
+
+        let recording = context.new_recording_implementations();
+        recording.record_implementation_used(zid!(10011));
+        assert_eq!(
+            recording
+                .get_implementations_used()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![zid!(10011)]
+        );
+        // not kept by new_fresh
+        recording
+            .new_fresh()
+            .record_implementation_used(zid!(10011));
+        assert!(recording.new_fresh().get_implementations_used().is_empty());
+    }
+}
@@ -1,11 +1,12 @@
 use crate::{
     EvalError, EvalErrorKind, ExecutionContext, KeyIndex, Zid,
     data_types::{
-        WfBoolean, WfData, WfDataType, WfFunction, WfFunctionCall, WfString, WfTypedList,
+        WfBoolean, WfData, WfDataType, WfFunction, WfFunctionCall, WfKeyReference, WfQuote,
+        WfString, WfTypedList,
     },
     eval_error::TraceEntry,
-    functions::{boolean, list, logic, string},
-    util::MaybeVec,
+    functions::{boolean, equality, error, list, logic, object, quote, string},
+    util::{MaybeVec, catch_panic},
 };
 
 fn assert_args_count(expected_size: usize, list: &Vec<WfData>) -> Result<(), EvalError> {
@@ -29,14 +30,32 @@ pub fn dispatch_builtins(
     //TODO: only evaluate necessary input (for when some are discarded, such as the if function)
     let mut args_evaluated = Vec::new();
     for (pos, arg) in call.0.args.iter().enumerate() {
-        args_evaluated.push(arg.clone().evaluate(context).map_err(|(e, _)| {
-            e.inside_key(KeyIndex::from_u32s_panic(
-                Some(function_zid.0.get()),
-                Some(pos as u32 + 1),
-            ))
-        })?);
+        // the expression of a try is evaluated by the builtin itself, so its errors can be caught
+        if function_zid == zid!(850) && pos == 0 {
+            args_evaluated.push(arg.clone());
+            continue;
+        }
+        // catch panics here too, so the trace point to the argument
+        args_evaluated.push(
+            catch_panic(|| arg.clone().evaluate(context).map_err(|(e, _)| e)).map_err(|e| {
+                e.inside_key(KeyIndex::from_u32s_panic(
+                    Some(function_zid.0.get()),
+                    Some(pos as u32 + 1),
+                ))
+            })?,
+        );
     }
 
+    dispatch_evaluated(function_zid, args_evaluated, context)
+}
+
+/// Kept separate from dispatch_builtins, so the stack frame that stay alive while arguments are evaluated (possibly recursively) is small
+#[inline(never)]
+fn dispatch_evaluated(
+    function_zid: Zid,
+    mut args_evaluated: Vec<WfData>,
+    context: &ExecutionContext,
+) -> Result<(WfData, bool, MaybeVec<TraceEntry>), EvalError> {
     match function_zid.0.get() {
         802 => {
             assert_args_count(3, &args_evaluated)?;
@@ -46,6 +65,23 @@ pub fn dispatch_builtins(
                 WfBoolean::parse(args_evaluated.pop().unwrap(), context).map_err(|(e, _)| e)?;
             return Ok(logic::if_function(boolean, r#then, r#else));
         }
+        803 => {
+            assert_args_count(2, &args_evaluated)?;
+            let object = args_evaluated.pop().unwrap();
+            let key_reference = WfKeyReference::parse(args_evaluated.pop().unwrap(), context)
+                .map_err(|(e, _)| e.inside_key(keyindex!(803, 1)))?;
+            object::value_by_key(key_reference, object)
+        }
+        805 => {
+            assert_args_count(1, &args_evaluated)?;
+            object::reify(args_evaluated.pop().unwrap())
+        }
+        808 => {
+            assert_args_count(1, &args_evaluated)?;
+            let list = WfTypedList::parse(args_evaluated.pop().unwrap(), context)
+                .map_err(|(e, _)| e.inside_key(keyindex!(808, 1)))?;
+            object::abstract_object(list, context)
+        }
         811 => {
             assert_args_count(1, &args_evaluated)?;
             let list1 =
@@ -77,6 +113,84 @@ pub fn dispatch_builtins(
                 MaybeVec::default(),
             ));
         }
+        850 => {
+            assert_args_count(2, &args_evaluated)?;
+            let handler = WfFunction::parse(args_evaluated.pop().unwrap(), context)
+                .map_err(|(e, _)| e.inside_key(keyindex!(850, 2)))?;
+            let expression = args_evaluated.pop().unwrap();
+            error::try_catch(expression, handler, context)
+        }
+        851 => {
+            assert_args_count(1, &args_evaluated)?;
+            let error_object = args_evaluated.pop().unwrap();
+            error_object
+                .check_z1k1(zid!(5), context)
+                .map_err(|e| e.inside_key(keyindex!(851, 1)))?;
+            Ok(error::raise(error_object))
+        }
+        868 => {
+            assert_args_count(1, &args_evaluated)?;
+            let string = WfString::parse(args_evaluated.pop().unwrap(), context)
+                .map_err(|(e, _)| e.inside_key(keyindex!(868, 1)))?;
+            Ok((
+                string::string_to_code_points(string).into_wf_data(),
+                true,
+                MaybeVec::default(),
+            ))
+        }
+        886 => {
+            assert_args_count(1, &args_evaluated)?;
+            let list = WfTypedList::parse(args_evaluated.pop().unwrap(), context)
+                .map_err(|(e, _)| e.inside_key(keyindex!(886, 1)))?;
+            string::code_points_to_string(list, context)
+                .map(|v| (v.into_wf_data(), false, MaybeVec::default()))
+        }
+        872 | 873 => {
+            assert_args_count(2, &args_evaluated)?;
+            let list =
+                WfTypedList::parse(args_evaluated.pop().unwrap(), context).map_err(|(e, _)| {
+                    e.inside_key(KeyIndex::from_u32s_panic(
+                        Some(function_zid.0.get()),
+                        Some(2),
+                    ))
+                })?;
+            let function =
+                WfFunction::parse(args_evaluated.pop().unwrap(), context).map_err(|(e, _)| {
+                    e.inside_key(KeyIndex::from_u32s_panic(
+                        Some(function_zid.0.get()),
+                        Some(1),
+                    ))
+                })?;
+            if function_zid == zid!(873) {
+                list::map(function, list, context)
+            } else {
+                list::filter(function, list, context)
+            }
+            .map(|v| (v.into_wf_data(), false, MaybeVec::default()))
+        }
+        887 => {
+            assert_args_count(3, &args_evaluated)?;
+            let initial = args_evaluated.pop().unwrap();
+            let list = WfTypedList::parse(args_evaluated.pop().unwrap(), context)
+                .map_err(|(e, _)| e.inside_key(keyindex!(887, 2)))?;
+            let function = WfFunction::parse(args_evaluated.pop().unwrap(), context)
+                .map_err(|(e, _)| e.inside_key(keyindex!(887, 1)))?;
+            list::reduce(function, list, initial, context).map(|v| (v, false, MaybeVec::default()))
+        }
+        888 => {
+            assert_args_count(2, &args_evaluated)?;
+            let second = args_evaluated.pop().unwrap();
+            let first = args_evaluated.pop().unwrap();
+            equality::same(first, second, context)
+                .map(|v| (WfBoolean::new(v).into_wf_data(), false, MaybeVec::default()))
+                .map_err(|(e, from_first)| {
+                    e.inside_key(if from_first {
+                        keyindex!(888, 1)
+                    } else {
+                        keyindex!(888, 2)
+                    })
+                })
+        }
         889 => {
             assert_args_count(3, &args_evaluated)?;
             let equality_function = WfFunction::parse(args_evaluated.pop().unwrap(), context)
@@ -88,6 +202,12 @@ pub fn dispatch_builtins(
             return list::list_equality(list1, list2, equality_function, context)
                 .map(|v| (v.into_wf_data(), true, MaybeVec::default()));
         }
+        899 => {
+            assert_args_count(1, &args_evaluated)?;
+            let to_unquote = WfQuote::parse(args_evaluated.pop().unwrap(), context)
+                .map_err(|(e, _)| e.inside_key(keyindex!(899, 1)))?;
+            Ok(quote::unquote(to_unquote))
+        }
         _ => return Err(EvalError::from_kind(EvalErrorKind::NoBuiltin(function_zid))),
     }
 }
@@ -0,0 +1,244 @@
+use crate::{
+    EvalError, ExecutionContext, KeyIndex,
+    data_types::{WfData, WfDataType, WfFunction, types_def::WfTypeGeneric},
+    functions::list::call_predicate,
+};
+
+/// Equality between two values of any type, as used by Z888.
+///
+/// Builtin types are compared structurally. Instances of persistent types use the Z4K4 equality function of their type
+/// when defined, or else compare the value of the identity key (for enumerations), falling back to structural equality.
+///
+/// Error return: The last bool is true if the error originate from first, false if it originate from second.
+pub fn same(
+    first: WfData,
+    second: WfData,
+    context: &ExecutionContext,
+) -> Result<bool, (EvalError, bool)> {
+    let first = first.evaluate(context).map_err(|(e, _)| (e, true))?;
+    let second = second.evaluate(context).map_err(|(e, _)| (e, false))?;
+
+    match (&first, &second) {
+        (WfData::WfTypedList(list1), WfData::WfTypedList(list2)) => {
+            if list1.len() != list2.len() {
+                return Ok(false);
+            }
+            for (pos, (ele1, ele2)) in list1.iter().zip(list2.iter()).enumerate() {
+                if !same(ele1, ele2, context).map_err(|(e, side)| (e.inside_list(pos), side))? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        (WfData::WfTypedPair(pair1), WfData::WfTypedPair(pair2)) => {
+            for (key, ele1, ele2) in [
+                (
+                    KeyIndex::from_u32s_panic(None, Some(1)),
+                    &pair1.0.first,
+                    &pair2.0.first,
+                ),
+                (
+                    KeyIndex::from_u32s_panic(None, Some(2)),
+                    &pair1.0.second,
+                    &pair2.0.second,
+                ),
+            ] {
+                if !same(ele1.clone(), ele2.clone(), context)
+                    .map_err(|(e, side)| (e.inside_key(key), side))?
+                {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        (WfData::WfUntyped(_), WfData::WfUntyped(_)) => {
+            persistent_type_equality(first, second, context)
+        }
+        _ => first.equality(second, context),
+    }
+}
+
+/// Both data are evaluated instances of a type defined by a Z4 object
+fn persistent_type_equality(
+    first: WfData,
+    second: WfData,
+    context: &ExecutionContext,
+) -> Result<bool, (EvalError, bool)> {
+    let mut types = Vec::with_capacity(2);
+    for (data, side) in [(&first, true), (&second, false)] {
+        let r#type = data
+            .get_key_err(keyindex!(1, 1))
+            .and_then(|t| {
+                t.parse_type(context)
+                    .map_err(|(e, _)| e.inside_key(keyindex!(1, 1)))
+            })
+            .map_err(|e| (e, side))?;
+        types.push(r#type);
+    }
+    let (Some(WfTypeGeneric::WfStandardType(type2)), Some(WfTypeGeneric::WfStandardType(type1))) =
+        (types.pop(), types.pop())
+    else {
+        return first.equality(second, context);
+    };
+    if type1.inner.identity_ref != type2.inner.identity_ref {
+        return Ok(false);
+    }
+
+    if let Some(equality_function) = &type1.inner.equality {
+        let equality_function = equality_function
+            .clone()
+            .evaluate(context)
+            .and_then(|f| WfFunction::parse(f, context))
+            .map_err(|(e, _)| {
+                (
+                    e.inside_key(keyindex!(4, 4)).inside_key(keyindex!(1, 1)),
+                    true,
+                )
+            })?;
+        return call_predicate(&equality_function, vec![first, second], context)
+            .map_err(|e| (e, true));
+    }
+
+    let identity_key = type1
+        .get_identity_key(context)
+        .map_err(|e| (e.inside_key(keyindex!(1, 1)), true))?;
+    let Some(identity_key) = identity_key else {
+        return first.equality(second, context);
+    };
+    let identity1 = first.get_key_err(identity_key).map_err(|e| (e, true))?;
+    let identity2 = second.get_key_err(identity_key).map_err(|e| (e, false))?;
+    match (&identity1, &identity2) {
+        // the instances of an enumeration usually refer to themselves, so don’t dereference them
+        (WfData::WfReference(ref1), WfData::WfReference(ref2)) => Ok(ref1.to == ref2.to),
+        _ => same(identity1, identity2, context)
+            .map_err(|(e, side)| (e.inside_key(identity_key), side)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExecutionContext, RcI,
+        data_types::{MaybeEvaluated, WfBoolean, WfData, WfDataType, WfString, WfTypedList},
+        functions::equality::same,
+        test_support::function_call,
+    };
+
+    fn test_context() -> ExecutionContext {
+        let mut global_context = crate::fuzzing::fuzzing_global_context();
+        for (title, body) in [
+            // an enumeration, identified by Z10100K1
+            (
+                "Z10100",
+                r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10100"}, "Z2K2": {"Z1K1": "Z4", "Z4K1": "Z10100", "Z4K2": ["Z3", {"Z1K1": "Z3", "Z3K1": "Z10100", "Z3K2": "Z10100K1", "Z3K3": {"Z1K1": "Z12", "Z12K1": ["Z11"]}, "Z3K4": "Z41"}], "Z4K3": "Z10001"}}"#,
+            ),
+            (
+                "Z10101",
+                r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10101"}, "Z2K2": {"Z1K1": "Z10100", "Z10100K1": "Z10101"}}"#,
+            ),
+            (
+                "Z10102",
+                r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10102"}, "Z2K2": {"Z1K1": "Z10100", "Z10100K1": "Z10102"}}"#,
+            ),
+            // a type whose equality function (Z10002, "not") consider the instances equal when the first key is false
+            (
+                "Z10110",
+                r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10110"}, "Z2K2": {"Z1K1": "Z4", "Z4K1": "Z10110", "Z4K2": ["Z3"], "Z4K3": "Z10001", "Z4K4": {"Z1K1": "Z8", "Z8K1": ["Z17", {"Z1K1": "Z17", "Z17K1": "Z10110", "Z17K2": "Z10111K1", "Z17K3": {"Z1K1": "Z12", "Z12K1": ["Z11"]}}, {"Z1K1": "Z17", "Z17K1": "Z10110", "Z17K2": "Z10111K2", "Z17K3": {"Z1K1": "Z12", "Z12K1": ["Z11"]}}], "Z8K2": "Z40", "Z8K3": ["Z20"], "Z8K4": ["Z14", {"Z1K1": "Z14", "Z14K1": "Z10111", "Z14K2": {"Z1K1": "Z7", "Z7K1": "Z844", "Z844K1": {"Z1K1": "Z7", "Z7K1": "Z10001", "Z10001K1": {"Z1K1": "Z7", "Z7K1": "Z803", "Z803K1": {"Z1K1": "Z39", "Z39K1": "Z10110K1"}, "Z803K2": {"Z1K1": "Z18", "Z18K1": "Z10111K1"}}}, "Z844K2": "Z41"}}], "Z8K5": "Z10111"}}}"#,
+            ),
+        ] {
+            global_context.add_from_json(title, body).unwrap();
+        }
+        ExecutionContext::default_for_global(RcI::new(global_context))
+    }
+
+    #[test]
+    fn test_builtin_types() {
+        let context = test_context();
+        let hello = WfString::new("hello").into_wf_data();
+        assert!(same(hello.clone(), hello.clone(), &context).unwrap());
+        assert!(
+            !same(
+                hello.clone(),
+                WfString::new("world").into_wf_data(),
+                &context
+            )
+            .unwrap()
+        );
+        // a reference to true is the same as true
+        assert!(
+            same(
+                WfData::new_reference(zid!(41)),
+                WfBoolean::new(true).into_wf_data(),
+                &context
+            )
+            .unwrap()
+        );
+
+        let list = |entries: Vec<WfData>| {
+            WfTypedList::new(
+                MaybeEvaluated::Unchecked(WfData::new_reference(zid!(40))),
+                entries,
+            )
+            .into_wf_data()
+        };
+        assert!(
+            same(
+                list(vec![
+                    WfData::new_reference(zid!(41)),
+                    WfBoolean::new(false).into_wf_data()
+                ]),
+                list(vec![
+                    WfBoolean::new(true).into_wf_data(),
+                    WfData::new_reference(zid!(42))
+                ]),
+                &context
+            )
+            .unwrap()
+        );
+        assert!(
+            !same(
+                list(vec![WfData::new_reference(zid!(41))]),
+                list(vec![WfData::new_reference(zid!(42))]),
+                &context
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_enumeration() {
+        let context = test_context();
+        let first = WfData::new_reference(zid!(10101));
+        let second = WfData::new_reference(zid!(10102));
+        assert!(same(first.clone(), first.clone(), &context).unwrap());
+        assert!(!same(first.clone(), second.clone(), &context).unwrap());
+        // the dereferenced value is the same as the reference
+        let first_value = context.get_global().get_object_value(&zid!(10101)).unwrap();
+        assert!(same(first_value, first, &context).unwrap());
+
+        // through the builtin
+        let call = function_call(zid!(888), vec![second.clone(), second]);
+        assert_eq!(
+            call.evaluate(&context).unwrap(),
+            WfBoolean::new(true).into_wf_data()
+        );
+    }
+
+    #[test]
+    fn test_equality_function() {
+        let context = test_context();
+        let instance = |value: bool| {
+            WfData::from_map(
+                [
+                    (keyindex!(1, 1), WfData::new_reference(zid!(10110))),
+                    (keyindex!(10110, 1), WfBoolean::new(value).into_wf_data()),
+                ]
+                .into_iter()
+                .collect(),
+            )
+        };
+        // structurally different, but equal according to Z4K4
+        assert!(same(instance(false), instance(true), &context).unwrap());
+        assert!(!same(instance(true), instance(false), &context).unwrap());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{
+    EvalError, EvalErrorKind, ExecutionContext, RcI,
+    data_types::{WfData, WfDataType, WfFunction, WfFunctionCall, WfFunctionCallInner, WfInvalid},
+    eval_error::TraceEntry,
+    util::MaybeVec,
+};
+
+/// Z850: try. Evaluate the expression, that is passed unevaluated (but substituted into, so it can use the arguments of
+/// the enclosing composition). If it fails with a catchable error, the handler is called with the corresponding Z5 error
+/// object instead. The returned data still need to be evaluated.
+pub fn try_catch(
+    expression: WfData,
+    handler: WfFunction,
+    context: &ExecutionContext,
+) -> Result<(WfData, bool, MaybeVec<TraceEntry>), EvalError> {
+    match expression.evaluate(context) {
+        Ok(value) => Ok((
+            value.clone(),
+            false,
+            MaybeVec::One(TraceEntry::ProcessingReconstructedData(value)),
+        )),
+        Err((e, _)) if e.get_kind().is_catchable() => {
+            let handler_call = WfFunctionCall(RcI::new(WfFunctionCallInner {
+                function: handler,
+                args: vec![e.get_kind().to_error_object()],
+            }))
+            .into_wf_data();
+            Ok((
+                handler_call.clone(),
+                true,
+                MaybeVec::One(TraceEntry::ProcessingReconstructedData(handler_call)),
+            ))
+        }
+        Err((e, _)) => Err(e.inside_key(keyindex!(850, 1))),
+    }
+}
+
+/// Z851: raise. The error is carried by invalid data, failing once evaluated.
+pub fn raise(error: WfData) -> (WfData, bool, MaybeVec<TraceEntry>) {
+    let invalid = WfInvalid::new(EvalErrorKind::Raised(Box::new(error))).into_wf_data();
+    (
+        invalid.clone(),
+        true,
+        MaybeVec::One(TraceEntry::ProcessingReconstructedData(invalid)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EvalErrorKind, ExecutionContext, GlobalContext, RcI,
+        data_types::{WfBoolean, WfData, WfDataType},
+        test_support::function_call,
+    };
+
+    fn test_context() -> ExecutionContext {
+        let mut global_context = crate::fuzzing::fuzzing_global_context();
+        // return the error type of the caught error
+        global_context
+            .add_from_json(
+                "Z10200",
+                r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10200"}, "Z2K2": {"Z1K1": "Z8", "Z8K1": ["Z17", {"Z1K1": "Z17", "Z17K1": "Z5", "Z17K2": "Z10200K1", "Z17K3": {"Z1K1": "Z12", "Z12K1": ["Z11"]}}], "Z8K2": "Z1", "Z8K3": ["Z20"], "Z8K4": ["Z14", {"Z1K1": "Z14", "Z14K1": "Z10200", "Z14K2": {"Z1K1": "Z7", "Z7K1": "Z803", "Z803K1": {"Z1K1": "Z39", "Z39K1": "Z5K1"}, "Z803K2": {"Z1K1": "Z18", "Z18K1": "Z10200K1"}}}], "Z8K5": "Z10200"}}"#,
+            )
+            .unwrap();
+        // try its argument, with Z10200 as the handler
+        global_context
+            .add_from_json(
+                "Z10201",
+                r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10201"}, "Z2K2": {"Z1K1": "Z8", "Z8K1": ["Z17", {"Z1K1": "Z17", "Z17K1": "Z1", "Z17K2": "Z10201K1", "Z17K3": {"Z1K1": "Z12", "Z12K1": ["Z11"]}}], "Z8K2": "Z1", "Z8K3": ["Z20"], "Z8K4": ["Z14", {"Z1K1": "Z14", "Z14K1": "Z10201", "Z14K2": {"Z1K1": "Z7", "Z7K1": "Z850", "Z850K1": {"Z1K1": "Z18", "Z18K1": "Z10201K1"}, "Z850K2": "Z10200"}}], "Z8K5": "Z10201"}}"#,
+            )
+            .unwrap();
+        global_context.add_direct_no_persistent_data(
+            zid!(504),
+            GlobalContext::standard_type_for_test(zid!(504)).into_wf_data(),
+        );
+        ExecutionContext::default_for_global(RcI::new(global_context))
+    }
+
+    #[test]
+    fn test_try_catch() {
+        let context = test_context();
+
+        // no error, the handler isn’t called
+        let call = function_call(
+            zid!(850),
+            vec![
+                function_call(
+                    zid!(844),
+                    vec![
+                        WfData::new_reference(zid!(41)),
+                        WfData::new_reference(zid!(41)),
+                    ],
+                ),
+                WfData::new_reference(zid!(10200)),
+            ],
+        );
+        assert_eq!(
+            call.evaluate(&context).unwrap(),
+            WfBoolean::new(true).into_wf_data()
+        );
+
+        // a missing persistent object is caught as a Z504
+        let call = function_call(
+            zid!(850),
+            vec![
+                WfData::new_reference(zid!(12345)),
+                WfData::new_reference(zid!(10200)),
+            ],
+        );
+        assert_eq!(
+            call.evaluate(&context).unwrap(),
+            GlobalContext::standard_type_for_test(zid!(504)).into_wf_data()
+        );
+    }
+
+    #[test]
+    fn test_try_catch_in_composition() {
+        let context = test_context();
+
+        // the argument of the composition is substituted into the tried expression
+        let call = function_call(
+            zid!(10201),
+            vec![function_call(
+                zid!(844),
+                vec![
+                    WfData::new_reference(zid!(41)),
+                    WfData::new_reference(zid!(42)),
+                ],
+            )],
+        );
+        assert_eq!(
+            call.evaluate(&context).unwrap(),
+            WfBoolean::new(false).into_wf_data()
+        );
+
+        let call = function_call(zid!(10201), vec![WfData::new_reference(zid!(12345))]);
+        assert_eq!(
+            call.evaluate(&context).unwrap(),
+            GlobalContext::standard_type_for_test(zid!(504)).into_wf_data()
+        );
+    }
+
+    #[test]
+    fn test_raise() {
+        let context = test_context();
+        let error_object = EvalErrorKind::MissingPersistentObject(zid!(12345)).to_error_object();
+
+        let raise = function_call(zid!(851), vec![error_object.clone()]);
+        let (error, _) = raise.clone().evaluate(&context).unwrap_err();
+        assert_eq!(
+            error.get_kind(),
+            &EvalErrorKind::Raised(Box::new(error_object))
+        );
+        assert_eq!(error.get_kind().error_type_zid(), zid!(504));
+
+        // and a raised error can be caught again
+        let call = function_call(zid!(850), vec![raise, WfData::new_reference(zid!(10200))]);
+        assert_eq!(
+            call.evaluate(&context).unwrap(),
+            GlobalContext::standard_type_for_test(zid!(504)).into_wf_data()
+        );
+
+        // only error objects can be raised
+        let (error, _) = function_call(zid!(851), vec![WfData::new_reference(zid!(41))])
+            .evaluate(&context)
+            .unwrap_err();
+        assert!(matches!(error.get_kind(), EvalErrorKind::WrongType(_, _)));
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
     EvalError, ExecutionContext, RcI,
     data_types::{
-        WfBoolean, WfData, WfDataType, WfFunction, WfFunctionCall, WfFunctionCallInner, WfTypedList,
+        MaybeEvaluated, WfBoolean, WfData, WfDataType, WfFunction, WfFunctionCall,
+        WfFunctionCallInner, WfTypedList,
     },
     eval_error::TraceEntry,
     util::MaybeVec,
@@ -16,6 +17,37 @@ pub fn first_element(
         .map_err(|(e, _)| e)
 }
 
+/// Call a function value with already evaluated arguments, and fully evaluate the result.
+/// Errors are traced as coming from the reconstructed function call.
+pub(super) fn call_function_value(
+    function: &WfFunction,
+    args: Vec<WfData>,
+    context: &ExecutionContext,
+) -> Result<WfData, EvalError> {
+    let function_call = WfFunctionCall(RcI::new(WfFunctionCallInner {
+        function: function.clone(),
+        args,
+    }));
+
+    function_call.clone().evaluate(context).map_err(|(e, _)| {
+        e.trace(TraceEntry::ProcessingReconstructedData(
+            function_call.into_wf_data(),
+        ))
+    })
+}
+
+/// Like call_function_value, but also parse the result as a boolean
+pub(super) fn call_predicate(
+    function: &WfFunction,
+    args: Vec<WfData>,
+    context: &ExecutionContext,
+) -> Result<bool, EvalError> {
+    let evaluated = call_function_value(function, args, context)?;
+    WfBoolean::parse(evaluated.clone(), context)
+        .map(|v| v.value)
+        .map_err(|(e, _)| e.trace(TraceEntry::ProcessingReconstructedData(evaluated)))
+}
+
 pub fn list_equality(
     list1: WfTypedList,
     list2: WfTypedList,
@@ -35,22 +67,286 @@ pub fn list_equality(
         let ele1 = ele1.map_err(|e| e.inside_list(pos).inside_key(keyindex!(889, 1)))?;
         let ele2 = ele2.map_err(|e| e.inside_list(pos).inside_key(keyindex!(889, 2)))?;
 
-        let function_call = WfFunctionCall(RcI::new(WfFunctionCallInner {
-            function: equality_function.clone(),
-            args: vec![ele1, ele2],
-        }));
-
-        //TODO: trace
-        let evaluated = function_call.clone().evaluate(context).map_err(|(e, _)| {
-            e.trace(TraceEntry::ProcessingReconstructedData(
-                function_call.into_wf_data(),
-            ))
-        })?;
-        let as_boolean = WfBoolean::parse(evaluated, context).map_err(|(e, _)| todo!())?;
-        if !as_boolean.value {
+        if !call_predicate(&equality_function, vec![ele1, ele2], context)? {
             return Ok(WfBoolean::new(false));
         }
     }
 
     Ok(WfBoolean::new(true))
 }
+
+/// Z873: apply the function to each element. The type of the resulting list is the return type of the function.
+pub fn map(
+    function: WfFunction,
+    list: WfTypedList,
+    context: &ExecutionContext,
+) -> Result<WfTypedList, EvalError> {
+    let mut result = Vec::with_capacity(list.len());
+    for (pos, element) in list.iter_checked(context).enumerate() {
+        let element = element.map_err(|e| e.inside_list(pos).inside_key(keyindex!(873, 2)))?;
+        result.push(
+            call_function_value(&function, vec![element], context)
+                .map_err(|e| e.inside_list(pos).inside_key(keyindex!(873, 2)))?,
+        );
+    }
+    Ok(WfTypedList::new(
+        MaybeEvaluated::Valid(function.0.return_type.clone()),
+        result,
+    ))
+}
+
+/// Z872: keep the elements for which the function return true. The type of the list is kept.
+pub fn filter(
+    function: WfFunction,
+    list: WfTypedList,
+    context: &ExecutionContext,
+) -> Result<WfTypedList, EvalError> {
+    let mut result = Vec::new();
+    for (pos, element) in list.iter_checked(context).enumerate() {
+        let element = element.map_err(|e| e.inside_list(pos).inside_key(keyindex!(872, 2)))?;
+        if call_predicate(&function, vec![element.clone()], context)
+            .map_err(|e| e.inside_list(pos).inside_key(keyindex!(872, 2)))?
+        {
+            result.push(element);
+        }
+    }
+    Ok(WfTypedList::new((*list.inner_type).clone(), result))
+}
+
+/// Z887: fold the list from the start, calling the function with the accumulator then the element.
+/// The accumulator is evaluated after each step, so that a long list does not build a deep chain of calls.
+/// It may be of any type, but the initial value should match the type of the first argument of the function, and each result its return type.
+pub fn reduce(
+    function: WfFunction,
+    list: WfTypedList,
+    initial: WfData,
+    context: &ExecutionContext,
+) -> Result<WfData, EvalError> {
+    if let Some(accumulator_type) = function
+        .argument_type(0, context)
+        .map_err(|e| e.inside_key(keyindex!(887, 1)))?
+    {
+        initial
+            .check_type_compatibility(accumulator_type, context)
+            .map_err(|e| e.inside_key(keyindex!(887, 3)))?;
+    }
+    let mut accumulator = initial;
+    for (pos, element) in list.iter_checked(context).enumerate() {
+        let trace = |e: EvalError| e.inside_list(pos).inside_key(keyindex!(887, 2));
+        let element = element.map_err(trace)?;
+        accumulator =
+            call_function_value(&function, vec![accumulator, element], context).map_err(trace)?;
+        accumulator
+            .check_type_compatibility(function.0.return_type.clone(), context)
+            .map_err(|e| {
+                trace(e.trace(TraceEntry::ProcessingReconstructedData(accumulator.clone())))
+            })?;
+    }
+    Ok(accumulator)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EvalErrorKind, ExecutionContext, RcI, TraceEntry,
+        data_types::{MaybeEvaluated, WfBoolean, WfData, WfDataType, WfKeyReference, WfTypedList},
+        fuzzing::fuzzing_global_context,
+        test_support::function_call,
+    };
+
+    fn boolean_list(values: &[bool]) -> WfData {
+        WfTypedList::new(
+            MaybeEvaluated::Unchecked(WfData::new_reference(zid!(40))),
+            values
+                .iter()
+                .map(|v| WfBoolean::new(*v).into_wf_data())
+                .collect(),
+        )
+        .into_wf_data()
+    }
+
+    fn evaluate_to_booleans(data: WfData, context: &ExecutionContext) -> Vec<bool> {
+        WfTypedList::parse(data.evaluate(context).unwrap(), context)
+            .unwrap()
+            .iter_checked(context)
+            .map(|v| WfBoolean::parse(v.unwrap(), context).unwrap().value)
+            .collect()
+    }
+
+    #[test]
+    fn test_map_and_filter() {
+        let context = ExecutionContext::default_for_global(RcI::new(fuzzing_global_context()));
+        // Z10001 is a composition for "not"
+        let not = WfData::new_reference(zid!(10001));
+
+        let mapped = function_call(
+            zid!(873),
+            vec![not.clone(), boolean_list(&[true, false, false])],
+        );
+        assert_eq!(
+            evaluate_to_booleans(mapped, &context),
+            vec![false, true, true]
+        );
+
+        let filtered = function_call(
+            zid!(872),
+            vec![not.clone(), boolean_list(&[true, false, true, false])],
+        );
+        assert_eq!(evaluate_to_booleans(filtered, &context), vec![false, false]);
+
+        let empty = function_call(zid!(873), vec![not, boolean_list(&[])]);
+        assert_eq!(evaluate_to_booleans(empty, &context), Vec::<bool>::new());
+    }
+
+    #[test]
+    fn test_reduce() {
+        let context = ExecutionContext::default_for_global(RcI::new(fuzzing_global_context()));
+
+        // boolean equality, folded: true == true == false == false
+        let reduced = function_call(
+            zid!(887),
+            vec![
+                WfData::new_reference(zid!(844)),
+                boolean_list(&[true, false, false]),
+                WfData::new_reference(zid!(41)),
+            ],
+        );
+        assert_eq!(
+            reduced.evaluate(&context).unwrap(),
+            WfBoolean::new(true).into_wf_data()
+        );
+
+        // the accumulator doesn’t need to be of the type of the elements (here, a key reference for Z803)
+        let reduced = function_call(
+            zid!(887),
+            vec![
+                WfData::new_reference(zid!(803)),
+                boolean_list(&[false]),
+                WfKeyReference::new(keyindex!(40, 1)).into_wf_data(),
+            ],
+        );
+        assert_eq!(
+            reduced.evaluate(&context).unwrap(),
+            WfBoolean::new(false).into_wf_data()
+        );
+
+        // long lists don’t nest the calls
+        let reduced = function_call(
+            zid!(887),
+            vec![
+                WfData::new_reference(zid!(844)),
+                boolean_list(&[true; 1000]),
+                WfData::new_reference(zid!(41)),
+            ],
+        );
+        assert_eq!(
+            reduced.evaluate(&context).unwrap(),
+            WfBoolean::new(true).into_wf_data()
+        );
+        assert_eq!(context.get_function_call_depth(), 0);
+    }
+
+    #[test]
+    fn test_error_traces() {
+        let context = ExecutionContext::default_for_global(RcI::new(fuzzing_global_context()));
+
+        let list_with_missing = WfTypedList::new(
+            MaybeEvaluated::Unchecked(WfData::new_reference(zid!(40))),
+            vec![
+                WfData::new_reference(zid!(41)),
+                WfData::new_reference(zid!(99999)),
+            ],
+        )
+        .into_wf_data();
+        let (error, _) = function_call(
+            zid!(873),
+            vec![WfData::new_reference(zid!(10001)), list_with_missing],
+        )
+        .evaluate(&context)
+        .unwrap_err();
+        assert_eq!(
+            error.get_kind(),
+            &EvalErrorKind::MissingPersistentObject(zid!(99999))
+        );
+        assert_eq!(
+            error.get_trace(),
+            &vec![
+                TraceEntry::InsideList(1),
+                TraceEntry::InsideKey(keyindex!(873, 2)),
+                TraceEntry::ProcessingNonCompositionFunction(zid!(873)),
+            ]
+        );
+
+        // errors raised by the called function point to the element it was called on
+        for (function, list_key) in [
+            (zid!(873), keyindex!(873, 2)),
+            (zid!(872), keyindex!(872, 2)),
+        ] {
+            let (error, _) = function_call(
+                function,
+                vec![
+                    WfData::new_reference(zid!(844)),
+                    boolean_list(&[true, false]),
+                ],
+            )
+            .evaluate(&context)
+            .unwrap_err();
+            assert!(matches!(
+                error.get_trace()[..],
+                [
+                    ..,
+                    TraceEntry::ProcessingReconstructedData(_),
+                    TraceEntry::InsideList(0),
+                    TraceEntry::InsideKey(key),
+                    TraceEntry::ProcessingNonCompositionFunction(_)
+                ] if key == list_key
+            ));
+        }
+
+        // the initial accumulator should match the first argument of the reducer
+        let (error, _) = function_call(
+            zid!(887),
+            vec![
+                WfData::new_reference(zid!(844)),
+                boolean_list(&[true]),
+                WfKeyReference::new(keyindex!(40, 1)).into_wf_data(),
+            ],
+        )
+        .evaluate(&context)
+        .unwrap_err();
+        assert_eq!(error.get_kind(), &EvalErrorKind::TypeDoesNotMatch);
+        assert_eq!(
+            error.get_trace(),
+            &vec![
+                TraceEntry::InsideKey(keyindex!(887, 3)),
+                TraceEntry::ProcessingNonCompositionFunction(zid!(887)),
+            ]
+        );
+
+        // the "equality" function of Z889 returning something else than a boolean
+        let key_references = WfTypedList::new(
+            MaybeEvaluated::Unchecked(WfData::new_reference(zid!(39))),
+            vec![WfKeyReference::new(keyindex!(1, 1)).into_wf_data()],
+        )
+        .into_wf_data();
+        let (error, _) = function_call(
+            zid!(889),
+            vec![
+                key_references,
+                boolean_list(&[true]),
+                WfData::new_reference(zid!(803)),
+            ],
+        )
+        .evaluate(&context)
+        .unwrap_err();
+        assert!(error.get_kind().is_raised_on_malformed_data());
+        assert!(matches!(
+            error.get_trace()[..],
+            [
+                ..,
+                TraceEntry::ProcessingReconstructedData(_),
+                TraceEntry::ProcessingNonCompositionFunction(_)
+            ]
+        ));
+    }
+}
@@ -1,8 +1,12 @@
 //! All the functon here, expect the dispatch function, assume the arguments have already be evaluated.
 
 pub mod boolean;
+pub mod equality;
+pub mod error;
 pub mod list;
 pub mod logic;
+pub mod object;
+pub mod quote;
 pub mod string;
 
 mod dispatch;
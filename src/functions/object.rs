@@ -0,0 +1,375 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    EvalError, ExecutionContext, KeyIndex,
+    data_types::{MaybeEvaluated, WfData, WfDataType, WfKeyReference, WfTypedList, WfTypedPair},
+    eval_error::TraceEntry,
+    util::MaybeVec,
+};
+
+/// Z803: value by key. Work on any object, wether it was parsed to a specialised type or not.
+pub fn value_by_key(
+    key_reference: WfKeyReference,
+    object: WfData,
+) -> Result<(WfData, bool, MaybeVec<TraceEntry>), EvalError> {
+    match object.get_key(key_reference.key) {
+        Some(value) => Ok((
+            value.clone(),
+            true,
+            MaybeVec::One(TraceEntry::ProcessingReconstructedData(value)),
+        )),
+        None => Err(EvalError::missing_key(key_reference.key).inside_key(keyindex!(803, 2))),
+    }
+}
+
+/// Z805: reify. Turn any object into a list of (Z39 key reference, value) pairs, in the order of list_keys.
+pub fn reify(object: WfData) -> Result<(WfData, bool, MaybeVec<TraceEntry>), EvalError> {
+    let pair_type = WfTypedPair::type_data_for(
+        WfData::new_reference(zid!(39)),
+        WfData::new_reference(zid!(1)),
+    );
+    let entries = object
+        .list_keys()
+        .into_iter()
+        .map(|key| {
+            let value = object
+                .get_key_err(key)
+                .map_err(|e| e.inside_key(keyindex!(805, 1)))?;
+            Ok(WfTypedPair::new(
+                MaybeEvaluated::Unchecked(pair_type.clone()),
+                WfKeyReference::new(key).into_wf_data(),
+                value,
+            )
+            .into_wf_data())
+        })
+        .collect::<Result<_, EvalError>>()?;
+    let result = WfTypedList::new(MaybeEvaluated::Unchecked(pair_type), entries).into_wf_data();
+    Ok((
+        result.clone(),
+        true,
+        MaybeVec::One(TraceEntry::ProcessingReconstructedData(result)),
+    ))
+}
+
+/// Z808: abstract. The reverse of reify: build an object from a list of (Z39 key reference, value) pairs.
+pub fn abstract_object(
+    list: WfTypedList,
+    context: &ExecutionContext,
+) -> Result<(WfData, bool, MaybeVec<TraceEntry>), EvalError> {
+    let mut result = BTreeMap::new();
+    for (pos, entry) in list.iter().enumerate() {
+        let pair = entry
+            .evaluate(context)
+            .and_then(|entry| WfTypedPair::parse(entry, context))
+            .map_err(|(e, _)| e.inside_list(pos).inside_key(keyindex!(808, 1)))?;
+        let key = pair
+            .0
+            .first
+            .clone()
+            .evaluate(context)
+            .and_then(|key| WfKeyReference::parse(key, context))
+            .map_err(|(e, _)| {
+                e.inside_key(KeyIndex::from_u32s_panic(None, Some(1)))
+                    .inside_list(pos)
+                    .inside_key(keyindex!(808, 1))
+            })?;
+        result.insert(key.key, pair.0.second.clone());
+    }
+    let result = WfData::from_map(result);
+    Ok((
+        result.clone(),
+        true,
+        MaybeVec::One(TraceEntry::ProcessingReconstructedData(result)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use map_macro::btree_map;
+
+    use crate::{
+        EvalErrorKind, ExecutionContext, GlobalContext, KeyIndex, RcI, TraceEntry,
+        data_types::{
+            MaybeEvaluated, WfArgumentReference, WfBoolean, WfData, WfDataType, WfFunction,
+            WfFunctionCall, WfFunctionCallInner, WfKeyReference, WfQuote, WfString, WfTestCase,
+            WfTestCaseInner, WfTypedList, WfTypedPair, WfUntyped,
+            types_def::{WfTypeGeneric, WfTypedListType, WfTypedPairType},
+        },
+        functions::object::{abstract_object, reify},
+        test_support::function_call,
+    };
+
+    #[test]
+    fn test_value_by_key() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+
+        // specialised boolean, obtained from a reference, with the key reference as a Z39 object
+        let key_as_object = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(39)),
+            keyindex!(39, 1) => WfString::new("Z40K1").into_wf_data(),
+        });
+        let call = function_call(
+            zid!(803),
+            vec![key_as_object, WfData::new_reference(zid!(41))],
+        );
+        assert_eq!(
+            call.evaluate(&context).unwrap(),
+            WfBoolean::new(true).into_wf_data()
+        );
+
+        // specialised function
+        let call = function_call(
+            zid!(803),
+            vec![
+                WfKeyReference::new(keyindex!(8, 5)).into_wf_data(),
+                WfData::new_reference(zid!(844)),
+            ],
+        );
+        assert_eq!(
+            call.evaluate(&context).unwrap(),
+            WfData::new_reference(zid!(844)).evaluate(&context).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_value_by_key_untyped() {
+        let mut global = GlobalContext::default_for_test();
+        global.add_direct_no_persistent_data(
+            zid!(10000),
+            GlobalContext::standard_type_for_test(zid!(10000)).into_wf_data(),
+        );
+        let context = ExecutionContext::default_for_global(RcI::new(global));
+
+        // an instance of a type without specialised representation is already a value
+        let untyped = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(10000)),
+            keyindex!(10000, 1) => WfString::new("hello").into_wf_data(),
+        });
+        assert_eq!(untyped.clone().evaluate(&context).unwrap(), untyped);
+
+        let call = function_call(
+            zid!(803),
+            vec![
+                WfKeyReference::new(keyindex!(10000, 1)).into_wf_data(),
+                untyped,
+            ],
+        );
+        assert_eq!(
+            call.evaluate(&context).unwrap(),
+            WfString::new("hello").into_wf_data()
+        );
+
+        // but not an instance of something that isn’t a standard type
+        let not_a_type = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(844)),
+        });
+        assert!(not_a_type.evaluate(&context).is_err());
+    }
+
+    #[test]
+    fn test_value_by_key_missing() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+
+        let call = function_call(
+            zid!(803),
+            vec![
+                WfKeyReference::new(keyindex!(6, 1)).into_wf_data(),
+                WfData::new_reference(zid!(42)),
+            ],
+        );
+        let (error, _) = call.evaluate(&context).unwrap_err();
+        assert_eq!(
+            error.get_kind(),
+            &EvalErrorKind::MissingKey(keyindex!(6, 1))
+        );
+    }
+
+    /// One sample of each variant (except WfReference, which is always followed before reaching Z805)
+    fn samples_of_every_variant(context: &ExecutionContext) -> Vec<WfData> {
+        let boolean_type = WfTypeGeneric::parse(
+            WfData::new_reference(zid!(40)).evaluate(context).unwrap(),
+            context,
+        )
+        .unwrap();
+        let key_reference_type = WfTypeGeneric::parse(
+            WfData::new_reference(zid!(39)).evaluate(context).unwrap(),
+            context,
+        )
+        .unwrap();
+        let function = WfFunction::parse(
+            WfData::new_reference(zid!(803)).evaluate(context).unwrap(),
+            context,
+        )
+        .unwrap();
+        let function_call = WfFunctionCall(RcI::new(WfFunctionCallInner {
+            function: function.clone(),
+            args: vec![
+                WfKeyReference::new(keyindex!(40, 1)).into_wf_data(),
+                WfData::new_reference(zid!(41)),
+            ],
+        }))
+        .into_wf_data();
+
+        vec![
+            WfBoolean::new(true).into_wf_data(),
+            WfString::new("hello").into_wf_data(),
+            WfData::from_map(btree_map! {
+                keyindex!(1, 1) => WfData::new_reference(zid!(10000)),
+                keyindex!(10000, 1) => WfString::new("hello").into_wf_data(),
+            }),
+            boolean_type.clone().into_wf_data(),
+            WfTypedListType::new(boolean_type.clone()).into_wf_data(),
+            WfTypedPairType::new(key_reference_type, boolean_type.clone()).into_wf_data(),
+            WfData::unvalid(EvalErrorKind::TestData),
+            WfTypedList::new(
+                MaybeEvaluated::Valid(boolean_type.clone()),
+                vec![
+                    WfData::new_reference(zid!(41)),
+                    WfBoolean::new(false).into_wf_data(),
+                ],
+            )
+            .into_wf_data(),
+            WfTypedList::new(
+                MaybeEvaluated::Unchecked(WfData::new_reference(zid!(40))),
+                Vec::new(),
+            )
+            .into_wf_data(),
+            function.clone().into_wf_data(),
+            function_call.clone(),
+            WfData::new_reference(zid!(944)).evaluate(context).unwrap(),
+            WfArgumentReference {
+                key_id: keyindex!(803, 2),
+            }
+            .into_wf_data(),
+            WfTestCase(RcI::new(WfTestCaseInner {
+                function,
+                call: function_call.clone(),
+                validation: WfData::new_reference(zid!(41)),
+            }))
+            .into_wf_data(),
+            WfKeyReference::new(keyindex!(40, 1)).into_wf_data(),
+            WfQuote::new(function_call).into_wf_data(),
+            WfTypedPair::new(
+                MaybeEvaluated::Unchecked(WfTypedPair::type_data_for(
+                    WfData::new_reference(zid!(40)),
+                    WfData::new_reference(zid!(6)),
+                )),
+                WfBoolean::new(true).into_wf_data(),
+                WfString::new("true").into_wf_data(),
+            )
+            .into_wf_data(),
+        ]
+    }
+
+    #[test]
+    fn test_reify_abstract_round_trip() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+
+        for sample in samples_of_every_variant(&context) {
+            let (reified, _, _) = reify(sample.clone()).unwrap();
+            let reified = WfTypedList::parse(reified.evaluate(&context).unwrap(), &context)
+                .unwrap_or_else(|(e, _)| panic!("reifying {:?}: {}", sample, e));
+            assert_eq!(reified.len(), sample.list_keys().len());
+            for (entry, key) in reified.iter_checked(&context).zip(sample.list_keys()) {
+                let pair = WfTypedPair::parse(entry.unwrap(), &context).unwrap();
+                assert_eq!(pair.0.first, WfKeyReference::new(key).into_wf_data());
+            }
+
+            let (abstracted, _, _) = abstract_object(reified, &context).unwrap();
+            assert_eq!(abstracted, WfUntyped::parse(sample).into_wf_data());
+        }
+    }
+
+    #[test]
+    fn test_reify_abstract_evaluated() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+
+        let reify_call = |object: WfData| function_call(zid!(805), vec![object]);
+        let abstract_call = |list: WfData| function_call(zid!(808), vec![list]);
+
+        // the reference is followed before reifying
+        let reified = reify_call(WfData::new_reference(zid!(41)))
+            .evaluate(&context)
+            .unwrap();
+        let reified = WfTypedList::parse(reified, &context).unwrap();
+        let second = WfTypedPair::parse(reified.iter().nth(1).unwrap(), &context).unwrap();
+        assert_eq!(
+            second.0.first,
+            WfKeyReference::new(keyindex!(40, 1)).into_wf_data()
+        );
+        assert_eq!(second.0.second, WfData::new_reference(zid!(41)));
+
+        for sample in [
+            WfBoolean::new(false).into_wf_data(),
+            WfString::new("hello").into_wf_data(),
+            WfKeyReference::new(keyindex!(40, 1)).into_wf_data(),
+            WfQuote::new(WfData::new_reference(zid!(42))).into_wf_data(),
+        ] {
+            assert_eq!(
+                abstract_call(reify_call(sample.clone()))
+                    .evaluate(&context)
+                    .unwrap(),
+                sample
+            );
+        }
+
+        // a typed list survive going through its K1/K2 form
+        let list = WfTypedList::new(
+            MaybeEvaluated::Unchecked(WfData::new_reference(zid!(40))),
+            vec![
+                WfData::new_reference(zid!(41)),
+                WfBoolean::new(false).into_wf_data(),
+            ],
+        )
+        .into_wf_data();
+        let round_tripped = WfTypedList::parse(
+            abstract_call(reify_call(list.clone()))
+                .evaluate(&context)
+                .unwrap(),
+            &context,
+        )
+        .unwrap();
+        assert_eq!(
+            round_tripped.iter().collect::<Vec<_>>(),
+            WfTypedList::parse(list, &context)
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>()
+        );
+
+        // the keys of the pairs must be key references
+        let wrong_key = WfTypedList::new(
+            MaybeEvaluated::Unchecked(WfData::new_reference(zid!(1))),
+            vec![
+                WfTypedPair::new(
+                    MaybeEvaluated::Unchecked(WfTypedPair::type_data_for(
+                        WfData::new_reference(zid!(1)),
+                        WfData::new_reference(zid!(1)),
+                    )),
+                    WfString::new("Z1K1").into_wf_data(),
+                    WfData::new_reference(zid!(40)),
+                )
+                .into_wf_data(),
+            ],
+        )
+        .into_wf_data();
+        let (error, _) = abstract_call(wrong_key).evaluate(&context).unwrap_err();
+        assert_eq!(
+            error.get_kind(),
+            &EvalErrorKind::WrongType(zid!(6), zid!(39))
+        );
+        assert_eq!(
+            error.get_trace(),
+            &vec![
+                TraceEntry::InsideKey(KeyIndex::from_u32s_panic(None, Some(1))),
+                TraceEntry::InsideList(0),
+                TraceEntry::InsideKey(keyindex!(808, 1)),
+                TraceEntry::ProcessingNonCompositionFunction(zid!(808)),
+            ]
+        );
+    }
+}
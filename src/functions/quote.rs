@@ -0,0 +1,49 @@
+use crate::{
+    data_types::{WfData, WfQuote},
+    eval_error::TraceEntry,
+    util::MaybeVec,
+};
+
+/// Z899: unquote. The returned data still need to be evaluated.
+pub fn unquote(quote: WfQuote) -> (WfData, bool, MaybeVec<TraceEntry>) {
+    let quoted = (*quote.quoted).clone();
+    (
+        quoted.clone(),
+        true,
+        MaybeVec::One(TraceEntry::ProcessingReconstructedData(quoted)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExecutionContext, GlobalContext, RcI,
+        data_types::{WfBoolean, WfData, WfDataType, WfQuote},
+        test_support::function_call,
+    };
+
+    #[test]
+    fn test_unquote() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+
+        let inner_call = function_call(
+            zid!(844),
+            vec![
+                WfData::new_reference(zid!(41)),
+                WfData::new_reference(zid!(42)),
+            ],
+        );
+        let quote = WfQuote::new(inner_call).into_wf_data();
+
+        // a quote evaluate to itself
+        assert_eq!(quote.clone().evaluate(&context).unwrap(), quote);
+
+        // while unquoting evaluate the quoted function call
+        let call = function_call(zid!(899), vec![quote]);
+        assert_eq!(
+            call.evaluate(&context).unwrap(),
+            WfBoolean::new(false).into_wf_data()
+        );
+    }
+}
@@ -1,5 +1,127 @@
-use crate::data_types::{WfBoolean, WfString};
+use crate::{
+    EvalError, ExecutionContext,
+    data_types::{
+        LIST_CHUNK_SIZE, MaybeEvaluated, WfBoolean, WfCodePoint, WfData, WfDataType, WfString,
+        WfTypedList,
+    },
+};
 
 pub fn string_equality(arg1: WfString, arg2: WfString) -> WfBoolean {
     WfBoolean::new(arg1.text == arg2.text)
 }
+
+/// Z868: convert a string to the list of its code points
+pub fn string_to_code_points(string: WfString) -> WfTypedList {
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::with_capacity(LIST_CHUNK_SIZE);
+    for c in string.text.chars() {
+        if chunk.len() == LIST_CHUNK_SIZE {
+            chunks.push(chunk);
+            chunk = Vec::with_capacity(LIST_CHUNK_SIZE);
+        }
+        chunk.push(WfCodePoint::new(c).into_wf_data());
+    }
+    chunks.push(chunk);
+    WfTypedList::from_chunks(
+        MaybeEvaluated::Unchecked(WfData::new_reference(zid!(86))),
+        chunks,
+    )
+}
+
+/// Z886: convert a list of code points to a string
+pub fn code_points_to_string(
+    list: WfTypedList,
+    context: &ExecutionContext,
+) -> Result<WfString, EvalError> {
+    let mut result = String::with_capacity(list.len());
+    for (pos, entry) in list.iter().enumerate() {
+        let code_point = entry
+            .evaluate(context)
+            .and_then(|entry| WfCodePoint::parse(entry, context))
+            .map_err(|(e, _)| e.inside_list(pos).inside_key(keyindex!(886, 1)))?;
+        result.push(code_point.value);
+    }
+    Ok(WfString::new(&result))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EvalErrorKind, ExecutionContext, GlobalContext, RcI, TraceEntry,
+        data_types::{
+            LIST_CHUNK_SIZE, MaybeEvaluated, WfCodePoint, WfData, WfDataType, WfString, WfTypedList,
+        },
+        functions::string::string_to_code_points,
+        test_support::function_call,
+    };
+
+    #[test]
+    fn test_code_points_round_trip() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+
+        for text in ["", "a", "héllo 🦀", &"ab€".repeat(LIST_CHUNK_SIZE)] {
+            let list = function_call(zid!(868), vec![WfString::new(text).into_wf_data()])
+                .evaluate(&context)
+                .unwrap();
+            let parsed = WfTypedList::parse(list.clone(), &context).unwrap();
+            assert_eq!(parsed.len(), text.chars().count());
+            assert_eq!(
+                parsed.iter().next(),
+                text.chars()
+                    .next()
+                    .map(|c| WfCodePoint::new(c).into_wf_data())
+            );
+
+            assert_eq!(
+                function_call(zid!(886), vec![list])
+                    .evaluate(&context)
+                    .unwrap(),
+                WfString::new(text).into_wf_data()
+            );
+        }
+    }
+
+    #[test]
+    fn test_long_string_is_chunked() {
+        let text = "x".repeat(LIST_CHUNK_SIZE * 2 + 1);
+        let list = string_to_code_points(WfString::new(&text));
+        assert_eq!(list.len(), text.len());
+        assert_eq!(list.iter().count(), text.len());
+
+        // the tail after the first chunk is still the right length
+        let mut tail = list;
+        for _ in 0..LIST_CHUNK_SIZE {
+            tail = tail.split_first_element(None).unwrap().1;
+        }
+        assert_eq!(tail.len(), LIST_CHUNK_SIZE + 1);
+    }
+
+    #[test]
+    fn test_code_points_to_string_invalid_entry() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+
+        let list = WfTypedList::new(
+            MaybeEvaluated::Unchecked(WfData::new_reference(zid!(86))),
+            vec![
+                WfCodePoint::new('a').into_wf_data(),
+                WfString::new("b").into_wf_data(),
+            ],
+        );
+        let (error, _) = function_call(zid!(886), vec![list.into_wf_data()])
+            .evaluate(&context)
+            .unwrap_err();
+        assert_eq!(
+            error.get_kind(),
+            &EvalErrorKind::WrongType(zid!(6), zid!(86))
+        );
+        assert_eq!(
+            error.get_trace()[..2],
+            [
+                TraceEntry::InsideList(1),
+                TraceEntry::InsideKey(keyindex!(886, 1))
+            ]
+        );
+    }
+}
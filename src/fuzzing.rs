@@ -0,0 +1,322 @@
+//! Helpers shared by the fuzz targets (in `fuzz/`) and their regression tests. Need the `fuzzing` feature outside of tests.
+//!
+//! Random ZObject trees are built from the raw fuzzer input with [`ByteSource`], rather than with `arbitrary`, so the same
+//! input always produce the same tree, and the unit tests can run it without extra dependencies.
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use sonic_rs::Value;
+
+use crate::{
+    EvalErrorKind, ExecutionContext, GlobalContext, KeyIndex, MAX_FUNCTION_CALL_COUNT, RcI, Zid,
+    data_types::{
+        MaybeEvaluated, WfBoolean, WfData, WfDataType, WfString, WfTypedList,
+        types_def::{WfStandardType, WfStandardTypeInner},
+    },
+    parsing::parse_json::parse_value,
+    replay::generate_replay,
+    util::panic_message,
+};
+
+/// Pages (in the dump format) added on top of `GlobalContext::default_for_test` for fuzzing.
+/// Z10001 is a composition (boolean not, as `Z844(K1, false)`) and Z10002 a composition that infinitely recurse (as `Z844(Z10002(K1), true)`).
+pub const FUZZING_PAGES: &[(&str, &str)] = &[
+    (
+        "Z10001",
+        r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10001"}, "Z2K2": {"Z1K1": "Z8", "Z8K1": ["Z17", {"Z1K1": "Z17", "Z17K1": "Z40", "Z17K2": "Z10001K1", "Z17K3": {"Z1K1": "Z12", "Z12K1": ["Z11"]}}], "Z8K2": "Z40", "Z8K3": ["Z20"], "Z8K4": ["Z14", "Z10011"], "Z8K5": "Z10001"}}"#,
+    ),
+    (
+        "Z10011",
+        r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10011"}, "Z2K2": {"Z1K1": "Z14", "Z14K1": "Z10001", "Z14K2": {"Z1K1": "Z7", "Z7K1": "Z844", "Z844K1": {"Z1K1": "Z18", "Z18K1": "Z10001K1"}, "Z844K2": "Z42"}}}"#,
+    ),
+    (
+        "Z10002",
+        r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10002"}, "Z2K2": {"Z1K1": "Z8", "Z8K1": ["Z17", {"Z1K1": "Z17", "Z17K1": "Z40", "Z17K2": "Z10002K1", "Z17K3": {"Z1K1": "Z12", "Z12K1": ["Z11"]}}], "Z8K2": "Z40", "Z8K3": ["Z20"], "Z8K4": ["Z14", "Z10012"], "Z8K5": "Z10002"}}"#,
+    ),
+    (
+        "Z10012",
+        r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10012"}, "Z2K2": {"Z1K1": "Z14", "Z14K1": "Z10002", "Z14K2": {"Z1K1": "Z7", "Z7K1": "Z844", "Z844K1": {"Z1K1": "Z7", "Z7K1": "Z10002", "Z10002K1": {"Z1K1": "Z18", "Z18K1": "Z10002K1"}}, "Z844K2": "Z41"}}}"#,
+    ),
+];
+
+/// A small global context, with the core types, a built-in function (Z844), and a few compositions (see [`FUZZING_PAGES`]).
+pub fn fuzzing_global_context() -> GlobalContext {
+    let mut global_context = GlobalContext::default_for_test();
+    // core types that are always present in the dump (or errors would be about them missing, rather than about the evaluated data)
+    for zid in [
+        zid!(1),
+        zid!(4),
+        zid!(6),
+        zid!(7),
+        zid!(8),
+        zid!(11),
+        zid!(12),
+        zid!(17),
+        zid!(18),
+        zid!(20),
+    ] {
+        global_context.add_direct_no_persistent_data(
+            zid,
+            WfStandardType::from(WfStandardTypeInner {
+                identity_ref: zid,
+                keys: WfTypedList::new(
+                    MaybeEvaluated::Unchecked(WfData::new_reference(zid!(3))),
+                    Vec::new(),
+                )
+                .into_wf_data(),
+                validator: WfData::unvalid(EvalErrorKind::TestData),
+                equality: None,
+                display_function: None,
+                reading_function: None,
+                type_converters_to_code: None,
+                type_converters_from_code: None,
+            })
+            .into_wf_data(),
+        );
+    }
+    for (title, body) in FUZZING_PAGES {
+        global_context
+            .add_from_json(title, body)
+            .expect("fuzzing pages should be valid");
+    }
+    global_context
+}
+
+/// Read the fuzzer input as a stream of choice. Once exhausted, always return 0, so every input build a finite tree.
+pub struct ByteSource<'l> {
+    bytes: &'l [u8],
+    position: usize,
+}
+
+impl<'l> ByteSource<'l> {
+    pub fn new(bytes: &'l [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub fn next_byte(&mut self) -> u8 {
+        let result = self.bytes.get(self.position).copied().unwrap_or(0);
+        self.position += 1;
+        result
+    }
+
+    /// A number between 0 and `count` (excluded)
+    pub fn choose(&mut self, count: usize) -> usize {
+        self.next_byte() as usize % count
+    }
+
+    pub fn choose_from<T: Copy>(&mut self, choices: &[T]) -> T {
+        choices[self.choose(choices.len())]
+    }
+}
+
+const MAX_TREE_DEPTH: usize = 6;
+
+/// ZID that exist in the fuzzing context, plus a few that don’t.
+const INTERESTING_ZIDS: &[u32] = &[
+    1, 3, 6, 7, 14, 18, 40, 41, 42, 844, 944, 10001, 10002, 10011, 99999,
+];
+
+fn build_zid(source: &mut ByteSource) -> Zid {
+    Zid::from_u32_panic(source.choose_from(INTERESTING_ZIDS))
+}
+
+fn build_key(source: &mut ByteSource, zid: Zid) -> KeyIndex {
+    // mostly correct keys for the given ZID, but sometimes from another ZID or not a global key at all
+    let zid = match source.choose(8) {
+        0 => Some(build_zid(source).0.get()),
+        1 => None,
+        _ => Some(zid.0.get()),
+    };
+    KeyIndex::from_u32s_panic(zid, Some(source.choose(4) as u32 + 1))
+}
+
+fn build_string(source: &mut ByteSource) -> WfData {
+    WfString::new(source.choose_from(&["", "hello", "Z41", "Z10001K1", "K1"])).into_wf_data()
+}
+
+/// Build a random ZObject tree, following the choices given by the source.
+pub fn build_tree(source: &mut ByteSource) -> WfData {
+    build_tree_inner(source, 0)
+}
+
+fn build_tree_inner(source: &mut ByteSource, depth: usize) -> WfData {
+    // only leaves once the maximum depth is reached
+    let kind = if depth >= MAX_TREE_DEPTH {
+        source.choose(4)
+    } else {
+        source.choose(9)
+    };
+    match kind {
+        0 => WfData::new_reference(build_zid(source)),
+        1 => build_string(source),
+        2 => WfBoolean::new(source.choose(2) == 0).into_wf_data(),
+        3 => WfData::unvalid(EvalErrorKind::TestData),
+        4 => {
+            // mostly existing functions, so the evaluation go further than parsing the call
+            let function = match source.choose(4) {
+                0 => build_zid(source),
+                _ => source.choose_from(&[zid!(844), zid!(10001), zid!(10002)]),
+            };
+            let mut map = btree_map_with_type(zid!(7));
+            map.insert(keyindex!(7, 1), WfData::new_reference(function));
+            for pos in 0..source.choose(4) {
+                let key = match source.choose(8) {
+                    0 => build_key(source, function),
+                    _ => KeyIndex::from_u32s_panic(Some(function.0.get()), Some(pos as u32 + 1)),
+                };
+                map.insert(key, build_tree_inner(source, depth + 1));
+            }
+            WfData::from_map(map)
+        }
+        5 => {
+            let r#type = WfData::new_reference(source.choose_from(&[zid!(1), zid!(6), zid!(40)]));
+            let entries = (0..source.choose(4))
+                .map(|_| build_tree_inner(source, depth + 1))
+                .collect();
+            WfTypedList::new(MaybeEvaluated::Unchecked(r#type), entries).into_wf_data()
+        }
+        6 => {
+            let mut map = btree_map_with_type(zid!(18));
+            map.insert(keyindex!(18, 1), build_string(source));
+            WfData::from_map(map)
+        }
+        7 => {
+            let mut map = btree_map_with_type(build_zid(source));
+            if source.choose(4) == 0 {
+                map.insert(keyindex!(1, 1), build_tree_inner(source, depth + 1));
+            }
+            for _ in 0..source.choose(3) {
+                let zid = build_zid(source);
+                let key = build_key(source, zid);
+                map.insert(key, build_tree_inner(source, depth + 1));
+            }
+            WfData::from_map(map)
+        }
+        _ => {
+            let mut map = btree_map_with_type(zid!(6));
+            map.insert(keyindex!(6, 1), build_tree_inner(source, depth + 1));
+            WfData::from_map(map)
+        }
+    }
+}
+
+fn btree_map_with_type(r#type: Zid) -> std::collections::BTreeMap<KeyIndex, WfData> {
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(keyindex!(1, 1), WfData::new_reference(r#type));
+    map
+}
+
+/// Arbitrary bytes must never make JSON parsing panic (only return a `LoadError`).
+pub fn check_parse_json(bytes: &[u8]) {
+    if let Ok(value) = sonic_rs::from_slice::<Value>(bytes) {
+        let _ = parse_value(&value);
+    }
+}
+
+/// Evaluate the data, and check that:
+/// - evaluation never panics (including panics caught and converted to `EvalErrorKind::InternalPanic`),
+/// - the limits of the `ExecutionContext` are respected,
+/// - if it failed, `generate_replay` does not panic (it check by itself it reach data that fail with the same error kind).
+///
+/// Panics if any of those properties does not hold.
+pub fn check_evaluation_properties(data: WfData, global_context: RcI<GlobalContext>) {
+    let context = ExecutionContext::default_for_global(global_context.clone());
+    let result = match catch_unwind(AssertUnwindSafe(|| data.clone().evaluate(&context))) {
+        Ok(r) => r,
+        Err(payload) => panic!("evaluation panicked: {}", panic_message(&*payload)),
+    };
+
+    assert_eq!(
+        context.get_function_call_depth(),
+        0,
+        "function call depth should be back to 0 after evaluation"
+    );
+    // the call that exceed the limit is still counted
+    assert!(context.get_function_call_count() <= MAX_FUNCTION_CALL_COUNT + 1);
+
+    let error = match result {
+        Ok(_) => return,
+        Err((error, _)) => error,
+    };
+    if let EvalErrorKind::InternalPanic(message) = error.get_kind() {
+        panic!("evaluation panicked: {}\n{}", message, error.render_trace());
+    }
+
+    // generate_replay check by itself that the data it reach fail with the same error kind
+    let replay_context = ExecutionContext::default_for_global(global_context);
+    if let Err(payload) = catch_unwind(AssertUnwindSafe(|| {
+        generate_replay(data, &replay_context, &error)
+    })) {
+        panic!(
+            "replay panicked: {}\nerror was: {}\n{}",
+            panic_message(&*payload),
+            error,
+            error.render_trace()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EvalErrorKind, ExecutionContext, RcI,
+        data_types::{WfData, WfDataType},
+        fuzzing::{
+            ByteSource, build_tree, check_evaluation_properties, check_parse_json,
+            fuzzing_global_context,
+        },
+    };
+
+    fn parse_str_value(json: &str) -> WfData {
+        crate::parsing::parse_json::parse_value(&sonic_rs::from_str(json).unwrap()).unwrap()
+    }
+
+    /// Deterministic pseudo-random inputs, so the properties are also checked without running the fuzzer.
+    fn pseudo_random_inputs(count: usize) -> impl Iterator<Item = Vec<u8>> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        (0..count).map(move |_| {
+            (0..128)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    (state >> 33) as u8
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_fuzzing_context_loads() {
+        let global_context = fuzzing_global_context();
+        assert!(global_context.objects.contains_key(&zid!(10001)));
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        let global_context = RcI::new(fuzzing_global_context());
+        let call = parse_str_value(r#"{"Z1K1": "Z7", "Z7K1": "Z10002", "Z10002K1": "Z41"}"#);
+        let context = ExecutionContext::default_for_global(global_context.clone());
+        let (error, _) = call.clone().evaluate(&context).unwrap_err();
+        assert_eq!(error.get_kind(), &EvalErrorKind::RecursedTooDeep);
+        assert_eq!(context.get_function_call_depth(), 0);
+        check_evaluation_properties(call, global_context);
+    }
+
+    #[test]
+    fn test_evaluation_properties() {
+        let global_context = RcI::new(fuzzing_global_context());
+        for input in pseudo_random_inputs(2000) {
+            let tree = build_tree(&mut ByteSource::new(&input));
+            check_evaluation_properties(tree, global_context.clone());
+        }
+    }
+
+    #[test]
+    fn test_parse_json_properties() {
+        for input in pseudo_random_inputs(200) {
+            check_parse_json(&input);
+        }
+        check_parse_json(br#"{"Z1K1": "Z7", "Z7K1": [], "Z6K1": {"": 1.5}}"#);
+        check_parse_json(br#"["Z6", "Z41", "z41", null, true]"#);
+    }
+}
@@ -1,35 +1,214 @@
-use std::{collections::BTreeMap, io::BufRead};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::Mutex,
+};
 
-use anyhow::Context;
-use sonic_rs::Object;
+use anyhow::{Context, bail};
+use bzip2::bufread::MultiBzDecoder;
+use flate2::bufread::MultiGzDecoder;
+use serde::Serialize;
+use sonic_rs::{JsonValueTrait, Object, Value};
 
-use crate::{EvalError, EvalErrorKind, Zid, data_types::WfData, parsing::parse_json};
+use crate::{
+    EvalError, EvalErrorKind, Zid,
+    data_types::WfData,
+    dependency_graph::{AffectedObjects, DependencyGraph},
+    object_source::ObjectSource,
+    overlay::Overlay,
+    parsing::{JsonPathSegment, LoadError, parse_json, serialize_json::SerializeWfData},
+};
+
+/// A line of a snapshot
+#[derive(Serialize)]
+struct SnapshotEntry<'l> {
+    zid: String,
+    value: SerializeWfData<'l>,
+}
+
+/// Pages read between two calls to the progress callback when loading a dump
+pub const PROGRESS_INTERVAL: usize = 10_000;
+
+/// Dump loaded by the command line tools when none is given
+pub const DEFAULT_DUMP_PATH: &str = "./wikifunctionswiki-20251201-pages-meta-current.xml";
+
+/// How far loading a dump went
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoadProgress {
+    pub pages_read: usize,
+    pub zobjects_loaded: usize,
+}
+
+impl Display for LoadProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} pages read, {} zobjects loaded",
+            self.pages_read, self.zobjects_loaded
+        )
+    }
+}
+
+/// A page of the dump that couldn’t be loaded
+#[derive(Debug)]
+pub struct PageFailure {
+    pub title: String,
+    pub error: anyhow::Error,
+    /// Where the `LoadError` happened, from the root of the page. Empty if the page failed for another reason.
+    pub path: Vec<JsonPathSegment>,
+}
+
+impl PageFailure {
+    fn new(title: String, error: anyhow::Error) -> Self {
+        let path = match LoadError::find_in(&error) {
+            // `parse_page` converts the value of the persistent object
+            Some(load_error) => std::iter::once(JsonPathSegment::Key(keyindex!(2, 2)))
+                .chain(load_error.path())
+                .collect(),
+            None => Vec::new(),
+        };
+        Self { title, error, path }
+    }
+
+    /// The error converting the JSON to a ZObject, if that is what failed
+    pub fn load_error(&self) -> Option<&LoadError> {
+        LoadError::find_in(&self.error)
+    }
+
+    /// The path as text, such as `$.Z2K2.Z8K1[1]`
+    pub fn path_text(&self) -> String {
+        let mut result = "$".to_string();
+        for segment in &self.path {
+            result.push_str(&segment.to_string());
+        }
+        result
+    }
+}
+
+impl Display for PageFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path_text())?;
+        }
+        write!(f, ": {:#}", self.error)
+    }
+}
+
+/// What happened when loading a dump leniently
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    pub progress: LoadProgress,
+    pub failures: Vec<PageFailure>,
+}
+
+/// Decompress the dump (as it is distributed) while it is read, if it is gzip or bzip2 compressed. The compression is
+/// detected with the magic bytes.
+pub fn decompress<'l, F: BufRead + 'l>(
+    mut reader: F,
+) -> Result<Box<dyn BufRead + 'l>, std::io::Error> {
+    let start = reader.fill_buf()?;
+    if start.starts_with(&[0x1f, 0x8b]) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else if start.starts_with(b"BZh") {
+        Ok(Box::new(BufReader::new(MultiBzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// The objects that changed when applying a newer version of some pages
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ObjectChanges {
+    pub added: BTreeSet<Zid>,
+    pub modified: BTreeSet<Zid>,
+    pub removed: BTreeSet<Zid>,
+}
+
+impl ObjectChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+
+    /// Added, modified and removed ZIDs
+    pub fn changed(&self) -> BTreeSet<Zid> {
+        self.added
+            .iter()
+            .chain(self.modified.iter())
+            .chain(self.removed.iter())
+            .copied()
+            .collect()
+    }
+
+    /// The functions and test cases of the (updated) global context that transitively depend on a changed object,
+    /// and so whose tests should be run again
+    pub fn affected(&self, global_context: &GlobalContext) -> AffectedObjects {
+        DependencyGraph::new(global_context).affected_by(self.changed(), global_context)
+    }
+}
 
 #[derive(Default)]
 pub struct GlobalContext {
-    //TODO: I’m not sure wether I wan’t this to request page like an executor or store all data locally. For now, take the second option, it’s simpler. This should be kept in the future, for testing purposes.
     //TODO: persistent objects
+    /// Objects loaded upfront. Tools that walk every object (such as `write_snapshot`) only see those.
     pub objects: BTreeMap<Zid, WfData>,
+    /// Where objects absent from `objects` are fetched from, on demand
+    source: Option<Box<dyn ObjectSource>>,
+    /// Objects fetched from the source, None if the source doesn’t have them
+    cache: Mutex<BTreeMap<Zid, Option<WfData>>>,
+}
+
+/// The value (Z2K2) of a page, in the dump format
+pub(crate) fn parse_page(body: &str) -> Result<WfData, anyhow::Error> {
+    let body_value: Object = sonic_rs::from_str(body).context("parsing json of a page")?;
+
+    parse_json::parse_value(
+        body_value
+            .get(&"Z2K2") // TODO: directly store the persistent object (evaluated?). Or maybe put the persistent object data in a separate map? They won’t be needed often, after all? No. The WfData will be directly accessible and will still need to be cloned, it’s likely just a matter of an pointer arithmetic, which is negligeable.
+            .context("trying to get the persistent object’s value")?,
+    )
+    .context("convert page to IR")
 }
 
 impl GlobalContext {
+    /// A global context without any object loaded upfront, fetching them from the source when first needed
+    pub fn from_source<S: ObjectSource + 'static>(source: S) -> Self {
+        Self {
+            source: Some(Box::new(source)),
+            ..Default::default()
+        }
+    }
+
     pub fn get_object_value(&self, zid: &Zid) -> Result<WfData, EvalError> {
-        self.objects
-            .get(zid)
-            .ok_or_else(|| EvalError::from_kind(EvalErrorKind::MissingPersistentObject(*zid)))
-            .cloned()
+        let missing = || EvalError::from_kind(EvalErrorKind::MissingPersistentObject(*zid));
+        if let Some(value) = self.objects.get(zid) {
+            return Ok(value.clone());
+        }
+        let Some(source) = &self.source else {
+            return Err(missing());
+        };
+        if let Some(cached) = self.cache.lock().unwrap().get(zid) {
+            return cached.clone().ok_or_else(missing);
+        }
+        // not holding the lock while fetching. Another thread may fetch the same object in the meantime, which is harmless.
+        let fetched = source.fetch(*zid).map_err(|e| {
+            EvalError::from_kind(EvalErrorKind::ObjectSourceFailed(*zid, format!("{:#}", e)))
+        })?;
+        self.cache.lock().unwrap().insert(*zid, fetched.clone());
+        fetched.ok_or_else(missing)
+    }
+
+    /// The ZIDs requested from the source so far (including those it doesn’t have)
+    pub fn fetched_zids(&self) -> Vec<Zid> {
+        self.cache.lock().unwrap().keys().copied().collect()
     }
 
     pub fn add_from_json(&mut self, page_title: &str, body: &str) -> Result<(), anyhow::Error> {
         let zid = Zid::from_str(page_title).context("parsing a page title")?;
-        let body_value: Object = sonic_rs::from_str(body).context("parsing json of a page")?;
-
-        let data = parse_json::parse_value(
-            body_value
-                .get(&"Z2K2") // TODO: directly store the persistent object (evaluated?). Or maybe put the persistent object data in a separate map? They won’t be needed often, after all? No. The WfData will be directly accessible and will still need to be cloned, it’s likely just a matter of an pointer arithmetic, which is negligeable.
-                .context("trying to get the persistent object’s value")?,
-        )
-        .context("convert page to IR")?;
+        let data = parse_page(body)?;
 
         self.objects.insert(zid, data);
 
@@ -40,81 +219,624 @@ impl GlobalContext {
         self.objects.insert(zid, data);
     }
 
+    /// Load every zobject page of a dump, possibly compressed (see `decompress`)
     pub fn from_wikifunction_dump<F: BufRead>(reader: F) -> Result<Self, anyhow::Error> {
+        Self::from_wikifunction_dump_with_progress(reader, |_| ())
+    }
+
+    /// Same as `from_wikifunction_dump`, calling `progress` every `PROGRESS_INTERVAL` pages and once at the end
+    pub fn from_wikifunction_dump_with_progress<F: BufRead, P: FnMut(LoadProgress)>(
+        reader: F,
+        progress: P,
+    ) -> Result<Self, anyhow::Error> {
+        Self::load_dump(reader, progress, |failure| {
+            Err(failure
+                .error
+                .context(format!("parsing page {}", failure.title)))
+        })
+        .map(|(global_context, _)| global_context)
+    }
+
+    /// Load every zobject page that can be, recording the others in the report rather than failing. Only failing to
+    /// read the dump itself is an error.
+    pub fn from_wikifunction_dump_lenient<F: BufRead, P: FnMut(LoadProgress)>(
+        reader: F,
+        progress: P,
+    ) -> Result<(Self, LoadReport), anyhow::Error> {
+        let mut failures = Vec::new();
+        let (global_context, progress) = Self::load_dump(reader, progress, |failure| {
+            failures.push(failure);
+            Ok(())
+        })?;
+        Ok((global_context, LoadReport { progress, failures }))
+    }
+
+    /// Load the global context as the command line tools do: from the snapshot if one is given, otherwise from the
+    /// dump (`DEFAULT_DUMP_PATH` if none is given, possibly compressed), printing the progress on stderr.
+    ///
+    /// With `lenient`, the pages of the dump that can’t be loaded are returned rather than failing. There are never
+    /// any when loading a snapshot.
+    pub fn load_for_cli(
+        dump: Option<&str>,
+        snapshot: Option<&str>,
+        lenient: bool,
+    ) -> Result<(Self, Vec<PageFailure>), anyhow::Error> {
+        if let Some(snapshot) = snapshot {
+            let file = BufReader::new(
+                File::open(snapshot)
+                    .with_context(|| format!("opening the snapshot {}", snapshot))?,
+            );
+            return Ok((
+                Self::from_snapshot(file).context("loading snapshot")?,
+                Vec::new(),
+            ));
+        }
+        let dump = dump.unwrap_or(DEFAULT_DUMP_PATH);
+        let file =
+            BufReader::new(File::open(dump).with_context(|| format!("opening the dump {}", dump))?);
+        let progress = |progress| eprintln!("{}", progress);
+        if lenient {
+            let (global_context, report) =
+                Self::from_wikifunction_dump_lenient(file, progress).context("loading dump")?;
+            Ok((global_context, report.failures))
+        } else {
+            let global_context = Self::from_wikifunction_dump_with_progress(file, progress)
+                .context("loading dump")?;
+            Ok((global_context, Vec::new()))
+        }
+    }
+
+    /// `on_failure` decides whether to go on after a page that can’t be loaded
+    fn load_dump<
+        F: BufRead,
+        P: FnMut(LoadProgress),
+        E: FnMut(PageFailure) -> Result<(), anyhow::Error>,
+    >(
+        reader: F,
+        mut progress: P,
+        mut on_failure: E,
+    ) -> Result<(Self, LoadProgress), anyhow::Error> {
         let mut global_context = Self::default();
+        let mut current = LoadProgress::default();
+        let reader = decompress(reader).context("reading the dump")?;
         for result in parse_mediawiki_dump_reboot::parse(reader) {
-            let result = result.unwrap();
-            if result.model.unwrap() == "zobject" {
-                global_context
-                    .add_from_json(&result.title, &result.text)
-                    .with_context(|| format!("parsing page {}", result.title))?;
+            let page =
+                result.map_err(|e| anyhow::anyhow!("reading the dump after {}: {}", current, e))?;
+            current.pages_read += 1;
+            // other pages (such as talk pages) are wikitext
+            if page.model.as_deref() == Some("zobject") {
+                match global_context.add_from_json(&page.title, &page.text) {
+                    Ok(()) => current.zobjects_loaded += 1,
+                    Err(error) => on_failure(PageFailure::new(page.title, error))?,
+                }
+            }
+            if current.pages_read % PROGRESS_INTERVAL == 0 {
+                progress(current);
+            }
+        }
+        progress(current);
+        Ok((global_context, current))
+    }
+
+    /// Replace the objects by those of a newer complete dump. Objects absent from it are removed, including those whose
+    /// page can’t be loaded anymore: those pages are returned rather than failing (as `from_wikifunction_dump_lenient`).
+    pub fn apply_dump_delta<F: BufRead>(
+        &mut self,
+        reader: F,
+    ) -> Result<(ObjectChanges, Vec<PageFailure>), anyhow::Error> {
+        let (newer, report) = Self::from_wikifunction_dump_lenient(reader, |_| ())?;
+        let mut changes = ObjectChanges::default();
+        for zid in self.objects.keys() {
+            if !newer.objects.contains_key(zid) {
+                changes.removed.insert(*zid);
+            }
+        }
+        for zid in &changes.removed {
+            self.objects.remove(zid);
+        }
+        for (zid, data) in newer.objects {
+            self.update_object(zid, data, &mut changes);
+        }
+        // what was fetched from the source may be outdated too
+        let cache = self.cache.get_mut().unwrap();
+        for zid in changes.changed() {
+            cache.remove(&zid);
+        }
+        Ok((changes, report.failures))
+    }
+
+    /// Add or replace objects with the pages of a directory, as `Z12345.json` files (containing the Z2 page, as in the
+    /// dump). Other files are ignored, and no object is removed. Fails on the first page that can’t be loaded, see
+    /// `Overlay` to collect every error instead.
+    pub fn apply_page_directory(&mut self, path: &Path) -> Result<ObjectChanges, anyhow::Error> {
+        let overlay = Overlay::from_directory(path)?;
+        if let Some(error) = overlay.errors().first() {
+            bail!("loading {}: {:#}", error.origin, error.error);
+        }
+        Ok(overlay.apply(self))
+    }
+
+    pub(crate) fn update_object(&mut self, zid: Zid, data: WfData, changes: &mut ObjectChanges) {
+        match self.objects.insert(zid, data) {
+            None => {
+                changes.added.insert(zid);
+            }
+            Some(previous) if previous != self.objects[&zid] => {
+                changes.modified.insert(zid);
+            }
+            Some(_) => (),
+        }
+    }
+
+    /// Load a snapshot written by `write_snapshot`. Much faster to load than the XML dump.
+    pub fn from_snapshot<F: BufRead>(reader: F) -> Result<Self, anyhow::Error> {
+        let mut global_context = Self::default();
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line.context("reading the snapshot")?;
+            if line.trim().is_empty() {
+                continue;
             }
+            let entry: Value = sonic_rs::from_str(&line)
+                .with_context(|| format!("parsing json of snapshot line {}", line_number + 1))?;
+            let zid = entry
+                .get("zid")
+                .and_then(|zid| zid.as_str())
+                .with_context(|| format!("missing zid at snapshot line {}", line_number + 1))?;
+            let zid = Zid::from_str(zid)
+                .with_context(|| format!("parsing zid at snapshot line {}", line_number + 1))?;
+            let value = entry
+                .get("value")
+                .with_context(|| format!("missing value for {}", zid))?;
+            let data =
+                parse_json::parse_value(value).with_context(|| format!("convert {} to IR", zid))?;
+            global_context.objects.insert(zid, data);
         }
         Ok(global_context)
     }
 
-    #[cfg(test)]
+    /// Write every persistent object value as JSON Lines, in the `{"zid": "Z...", "value": ...}` format.
+    ///
+    /// Specialised data with an identity (such as functions) are serialized as a reference to themselves, so this should
+    /// only be used on data loaded from a dump or a snapshot, which are not specialised.
+    pub fn write_snapshot<W: Write>(&self, mut writer: W) -> Result<(), anyhow::Error> {
+        for (zid, value) in self.objects.iter() {
+            serde_json::to_writer(
+                &mut writer,
+                &SnapshotEntry {
+                    zid: zid.to_string(),
+                    value: SerializeWfData(value),
+                },
+            )?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
     pub fn default_for_test() -> Self {
         use map_macro::btree_map;
 
-        use crate::{
-            RcI,
-            data_types::{
-                ImplementationByKind, MaybeEvaluated, WfBoolean, WfDataType, WfFunction,
-                WfFunctionInner, WfImplementation, WfImplementationInner, WfTypedList,
-                types_def::{WfStandardType, WfStandardTypeInner, WfTypeGeneric},
+        use crate::data_types::{WfBoolean, WfDataType, types_def::WfTypeGeneric};
+
+        let boolean_type = Self::standard_type_for_test(zid!(40));
+
+        let mut result = Self {
+            objects: btree_map! {
+                zid!(1) => Self::standard_type_for_test(zid!(1)).into_wf_data(),
+                zid!(14) => Self::standard_type_for_test(zid!(14)).into_wf_data(),
+                zid!(3) => Self::standard_type_for_test(zid!(3)).into_wf_data(),
+                zid!(5) => Self::standard_type_for_test(zid!(5)).into_wf_data(),
+                zid!(6) => Self::standard_type_for_test(zid!(6)).into_wf_data(),
+                zid!(39) => Self::standard_type_for_test(zid!(39)).into_wf_data(),
+                zid!(40) => boolean_type.clone().into_wf_data(),
+                zid!(86) => Self::standard_type_for_test(zid!(86)).into_wf_data(),
+                zid!(41) => WfBoolean::new(true).into_wf_data(),
+                zid!(42) => WfBoolean::new(false).into_wf_data(),
             },
+            ..Default::default()
+        };
+        // builtin boolean equality
+        result.add_builtin_for_test(
+            zid!(844),
+            zid!(944),
+            &[zid!(40), zid!(40)],
+            WfTypeGeneric::WfStandardType(boolean_type.clone()),
+        );
+        // builtin value by key
+        result.add_builtin_for_test(
+            zid!(803),
+            zid!(903),
+            &[zid!(39), zid!(1)],
+            WfTypeGeneric::WfStandardType(Self::standard_type_for_test(zid!(1))),
+        );
+        // builtin reify and abstract
+        result.add_builtin_for_test(
+            zid!(805),
+            zid!(905),
+            &[zid!(1)],
+            WfTypeGeneric::WfStandardType(Self::standard_type_for_test(zid!(1))),
+        );
+        result.add_builtin_for_test(
+            zid!(808),
+            zid!(908),
+            &[zid!(1)],
+            WfTypeGeneric::WfStandardType(Self::standard_type_for_test(zid!(1))),
+        );
+        // builtin try and raise
+        result.add_builtin_for_test(
+            zid!(850),
+            zid!(950),
+            &[zid!(1), zid!(1)],
+            WfTypeGeneric::WfStandardType(Self::standard_type_for_test(zid!(1))),
+        );
+        result.add_builtin_for_test(
+            zid!(851),
+            zid!(951),
+            &[zid!(1)],
+            WfTypeGeneric::WfStandardType(Self::standard_type_for_test(zid!(1))),
+        );
+        // builtin string to code points, and back
+        result.add_builtin_for_test(
+            zid!(868),
+            zid!(968),
+            &[zid!(6)],
+            WfTypeGeneric::WfStandardType(Self::standard_type_for_test(zid!(1))),
+        );
+        result.add_builtin_for_test(
+            zid!(886),
+            zid!(986),
+            &[zid!(1)],
+            WfTypeGeneric::WfStandardType(Self::standard_type_for_test(zid!(6))),
+        );
+        // builtin generic equality
+        result.add_builtin_for_test(
+            zid!(888),
+            zid!(988),
+            &[zid!(1), zid!(1)],
+            WfTypeGeneric::WfStandardType(boolean_type),
+        );
+        // builtin higher-order list functions
+        for (function, implementation, arguments_count) in [
+            (zid!(872), zid!(972), 2),
+            (zid!(873), zid!(973), 2),
+            (zid!(887), zid!(987), 3),
+            (zid!(889), zid!(989), 3),
+        ] {
+            result.add_builtin_for_test(
+                function,
+                implementation,
+                &vec![zid!(1); arguments_count],
+                WfTypeGeneric::WfStandardType(Self::standard_type_for_test(zid!(1))),
+            );
+        }
+        // builtin unquote
+        result.add_builtin_for_test(
+            zid!(899),
+            zid!(999),
+            &[zid!(1)],
+            WfTypeGeneric::WfStandardType(Self::standard_type_for_test(zid!(1))),
+        );
+        result
+    }
+
+    /// A standard type with the given identity, declaring no key, whose other keys are all invalid test data
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn standard_type_for_test(identity: Zid) -> crate::data_types::types_def::WfStandardType {
+        use crate::data_types::{
+            MaybeEvaluated, WfDataType, WfTypedList,
+            types_def::{WfStandardType, WfStandardTypeInner},
         };
 
-        let boolean_type = <WfStandardType>::from(WfStandardTypeInner {
-            identity_ref: zid!(40),
-            keys: WfData::unvalid(EvalErrorKind::TestData),
+        <WfStandardType>::from(WfStandardTypeInner {
+            identity_ref: identity,
+            keys: WfTypedList::new(
+                MaybeEvaluated::Unchecked(WfData::new_reference(zid!(3))),
+                Vec::new(),
+            )
+            .into_wf_data(),
             validator: WfData::unvalid(EvalErrorKind::TestData),
             equality: Some(WfData::unvalid(EvalErrorKind::TestData)),
             display_function: Some(WfData::unvalid(EvalErrorKind::TestData)),
             reading_function: Some(WfData::unvalid(EvalErrorKind::TestData)),
             type_converters_to_code: Some(WfData::unvalid(EvalErrorKind::TestData)),
             type_converters_from_code: Some(WfData::unvalid(EvalErrorKind::TestData)),
-        });
+        })
+    }
 
-        Self {
-            objects: btree_map! {
-                zid!(14) => <WfStandardType>::from(WfStandardTypeInner {
-                    identity_ref: zid!(14),
-                    keys: WfData::unvalid(EvalErrorKind::TestData),
-                    validator: WfData::unvalid(EvalErrorKind::TestData),
-                    equality: Some(WfData::unvalid(EvalErrorKind::TestData)),
-                    display_function: Some(WfData::unvalid(EvalErrorKind::TestData)),
-                    reading_function: Some(WfData::unvalid(EvalErrorKind::TestData)),
-                    type_converters_to_code: Some(WfData::unvalid(EvalErrorKind::TestData)),
-                    type_converters_from_code: Some(WfData::unvalid(EvalErrorKind::TestData)),
-                }).into_wf_data(),
-                zid!(3) => <WfStandardType>::from(WfStandardTypeInner {
-                    identity_ref: zid!(3),
-                    keys: WfData::unvalid(EvalErrorKind::TestData),
-                    validator: WfData::unvalid(EvalErrorKind::TestData),
-                    equality: Some(WfData::unvalid(EvalErrorKind::TestData)),
-                    display_function: Some(WfData::unvalid(EvalErrorKind::TestData)),
-                    reading_function: Some(WfData::unvalid(EvalErrorKind::TestData)),
-                    type_converters_to_code: Some(WfData::unvalid(EvalErrorKind::TestData)),
-                    type_converters_from_code: Some(WfData::unvalid(EvalErrorKind::TestData)),
-                }).into_wf_data(),
-                zid!(40) => boolean_type.clone().into_wf_data(),
-                zid!(41) => WfBoolean::new(true).into_wf_data(),
-                zid!(42) => WfBoolean::new(false).into_wf_data(),
-                zid!(844) => WfFunction(RcI::new(WfFunctionInner {
-                    arguments: WfTypedList::new(MaybeEvaluated::Unchecked(WfData::new_reference(zid!(3))), vec![WfData::unvalid(EvalErrorKind::TestData); 2]),
-                    identity: zid!(844),
-                    implementations: WfTypedList::new(MaybeEvaluated::Unchecked(WfData::new_reference(zid!(14))), vec![WfData::new_reference(zid!(944))]),
-                    return_type: WfTypeGeneric::WfStandardType(boolean_type),
-                    testers: WfData::unvalid(EvalErrorKind::TestData)
-                })).into_wf_data(),
-                // builtin boolean equality
-                zid!(944) => WfImplementation(RcI::new(WfImplementationInner {
-                    function: WfData::new_reference(zid!(844)),
-                    r#impl: ImplementationByKind::Builtin(WfData::new_reference(zid!(844)))
-                })).into_wf_data()
+    /// Add a function with a single builtin implementation. The implementation is dispatched on the function ZID.
+    /// Each argument is declared (as a Z17) with the given type.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn add_builtin_for_test(
+        &mut self,
+        function: Zid,
+        implementation: Zid,
+        argument_types: &[Zid],
+        return_type: crate::data_types::types_def::WfTypeGeneric,
+    ) {
+        use map_macro::btree_map;
+
+        use crate::{
+            RcI,
+            data_types::{
+                ImplementationByKind, MaybeEvaluated, WfDataType, WfFunction, WfFunctionInner,
+                WfImplementation, WfImplementationInner, WfString, WfTypedList,
             },
+        };
+
+        self.add_direct_no_persistent_data(
+            function,
+            WfFunction(RcI::new(WfFunctionInner {
+                arguments: WfTypedList::new(
+                    MaybeEvaluated::Unchecked(WfData::new_reference(zid!(3))),
+                    argument_types
+                        .iter()
+                        .enumerate()
+                        .map(|(pos, r#type)| {
+                            WfData::from_map(btree_map! {
+                                keyindex!(1, 1) => WfData::new_reference(zid!(17)),
+                                keyindex!(17, 1) => WfData::new_reference(*r#type),
+                                keyindex!(17, 2) => WfString::new(&format!("{}K{}", function, pos + 1)).into_wf_data(),
+                            })
+                        })
+                        .collect(),
+                ),
+                identity: function,
+                implementations: WfTypedList::new(
+                    MaybeEvaluated::Unchecked(WfData::new_reference(zid!(14))),
+                    vec![WfData::new_reference(implementation)],
+                ),
+                return_type,
+                testers: WfData::unvalid(EvalErrorKind::TestData),
+            }))
+            .into_wf_data(),
+        );
+        self.add_direct_no_persistent_data(
+            implementation,
+            WfImplementation(RcI::new(WfImplementationInner {
+                function: WfData::new_reference(function),
+                r#impl: ImplementationByKind::Builtin(WfData::new_reference(function)),
+            }))
+            .into_wf_data(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, fs, io::Write};
+
+    use crate::{
+        GlobalContext, LoadProgress,
+        data_types::WfData,
+        fuzzing::FUZZING_PAGES,
+        parsing::{JsonPathSegment, LoadError},
+        test_support::{TempDirectory, dump_xml, fuzzing_pages_context},
+    };
+
+    const TEST_CASE_PAGE: (&str, &str) = (
+        "Z10021",
+        r#"{"Z1K1": "Z2", "Z2K2": {"Z1K1": "Z20", "Z20K1": "Z10001", "Z20K2": {"Z1K1": "Z7", "Z7K1": "Z10001", "Z10001K1": "Z41"}, "Z20K3": {"Z1K1": "Z7", "Z7K1": "Z844", "Z844K2": "Z42"}}}"#,
+    );
+    /// Z10011 (the implementation of Z10001) as `Z844(K1, true)`
+    const MODIFIED_IMPLEMENTATION: &str = r#"{"Z1K1": "Z2", "Z2K2": {"Z1K1": "Z14", "Z14K1": "Z10001", "Z14K2": {"Z1K1": "Z7", "Z7K1": "Z844", "Z844K1": {"Z1K1": "Z18", "Z18K1": "Z10001K1"}, "Z844K2": "Z41"}}}"#;
+
+    #[test]
+    fn test_compressed_dump() {
+        let mut xml = dump_xml(FUZZING_PAGES);
+        // a page without a model, and a wikitext page
+        xml.insert_str(
+            xml.len() - "</mediawiki>".len(),
+            "<page><title>Talk:Z10001</title><ns>1</ns><revision><text>hello</text></revision></page><page><title>Main Page</title><ns>0</ns><revision><model>wikitext</model><format>text/x-wiki</format><text>hello</text></revision></page>",
+        );
+        let mut progress = Vec::new();
+        let expected = GlobalContext::from_wikifunction_dump_with_progress(xml.as_bytes(), |p| {
+            progress.push(p)
+        })
+        .unwrap();
+        assert_eq!(expected.objects.len(), FUZZING_PAGES.len());
+        assert_eq!(
+            progress,
+            vec![LoadProgress {
+                pages_read: FUZZING_PAGES.len() + 2,
+                zobjects_loaded: FUZZING_PAGES.len()
+            }]
+        );
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(xml.as_bytes()).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let mut bzip2 = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bzip2.write_all(xml.as_bytes()).unwrap();
+        let bzip2 = bzip2.finish().unwrap();
+        for compressed in [gzip, bzip2] {
+            assert_ne!(&compressed[..], xml.as_bytes());
+            let global_context = GlobalContext::from_wikifunction_dump(&compressed[..]).unwrap();
+            assert_eq!(global_context.objects, expected.objects);
         }
+
+        // a truncated dump is an error, not a panic
+        assert!(GlobalContext::from_wikifunction_dump(&xml.as_bytes()[..100]).is_err());
+    }
+
+    #[test]
+    fn test_lenient_dump() {
+        let mut pages = FUZZING_PAGES.to_vec();
+        pages.push(("Z10022", "{"));
+        pages.push(("Main Page", FUZZING_PAGES[0].1));
+        pages.push((
+            "Z10023",
+            r#"{"Z1K1": "Z2", "Z2K2": {"Z1K1": "Z12", "Z12K1": ["Z11", {"Z1K1": "Z11", "Z11K2": []}]}}"#,
+        ));
+        let xml = dump_xml(&pages);
+        let Err(error) = GlobalContext::from_wikifunction_dump(xml.as_bytes()) else {
+            panic!("loading should stop at the first broken page");
+        };
+        assert!(error.to_string().contains("Z10022"));
+
+        let (global_context, report) =
+            GlobalContext::from_wikifunction_dump_lenient(xml.as_bytes(), |_| ()).unwrap();
+        assert_eq!(global_context.objects.len(), FUZZING_PAGES.len());
+        assert_eq!(
+            report.progress,
+            LoadProgress {
+                pages_read: pages.len(),
+                zobjects_loaded: FUZZING_PAGES.len()
+            }
+        );
+        let failures = &report.failures;
+        assert_eq!(
+            failures
+                .iter()
+                .map(|failure| failure.title.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Z10022", "Main Page", "Z10023"]
+        );
+        assert!(failures[0].path.is_empty());
+        assert_eq!(failures[0].load_error(), None);
+        assert_eq!(
+            failures[2].path,
+            vec![
+                JsonPathSegment::Key(keyindex!(2, 2)),
+                JsonPathSegment::Key(keyindex!(12, 1)),
+                JsonPathSegment::Index(1),
+                JsonPathSegment::Key(keyindex!(11, 2)),
+            ]
+        );
+        assert_eq!(failures[2].path_text(), "$.Z2K2.Z12K1[1].Z11K2");
+        assert_eq!(
+            failures[2].load_error().map(LoadError::innermost),
+            Some(&LoadError::EmptyArray)
+        );
+        assert!(
+            failures[2]
+                .to_string()
+                .starts_with("Z10023 at $.Z2K2.Z12K1[1].Z11K2: ")
+        );
+    }
+
+    #[test]
+    fn test_load_for_cli() {
+        let temp_directory = TempDirectory::new("load-for-cli");
+        let directory = temp_directory.path();
+        let path = |name: &str| directory.join(name).to_str().unwrap().to_string();
+
+        let mut pages = FUZZING_PAGES.to_vec();
+        pages.push(("Z10022", "{"));
+        fs::write(directory.join("dump.xml"), dump_xml(&pages)).unwrap();
+        assert!(GlobalContext::load_for_cli(Some(&path("dump.xml")), None, false).is_err());
+        let (global_context, failures) =
+            GlobalContext::load_for_cli(Some(&path("dump.xml")), None, true).unwrap();
+        assert_eq!(global_context.objects.len(), FUZZING_PAGES.len());
+        assert_eq!(failures.len(), 1);
+
+        // the snapshot is used rather than the dump
+        let mut snapshot = fs::File::create(directory.join("snapshot.jsonl")).unwrap();
+        global_context.write_snapshot(&mut snapshot).unwrap();
+        snapshot.flush().unwrap();
+        let (loaded, failures) = GlobalContext::load_for_cli(
+            Some(&path("missing.xml")),
+            Some(&path("snapshot.jsonl")),
+            false,
+        )
+        .unwrap();
+        assert_eq!(loaded.objects, global_context.objects);
+        assert!(failures.is_empty());
+
+        let Err(error) = GlobalContext::load_for_cli(Some(&path("missing.xml")), None, false)
+        else {
+            panic!("the dump doesn’t exist");
+        };
+        assert!(error.to_string().contains("missing.xml"));
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let global_context = fuzzing_pages_context();
+
+        let mut snapshot = Vec::new();
+        global_context.write_snapshot(&mut snapshot).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&snapshot).lines().count(),
+            FUZZING_PAGES.len()
+        );
+
+        let loaded = GlobalContext::from_snapshot(&snapshot[..]).unwrap();
+        assert_eq!(loaded.objects, global_context.objects);
+    }
+
+    #[test]
+    fn test_apply_dump_delta() {
+        let mut pages = FUZZING_PAGES.to_vec();
+        pages.push(TEST_CASE_PAGE);
+        let mut global_context =
+            GlobalContext::from_wikifunction_dump(dump_xml(&pages).as_bytes()).unwrap();
+        assert_eq!(global_context.objects.len(), 5);
+
+        // Z10002 and Z10012 are removed, Z10011 is modified, Z10003 is added
+        let newer_pages = [
+            pages[0],
+            ("Z10011", MODIFIED_IMPLEMENTATION),
+            TEST_CASE_PAGE,
+            ("Z10003", r#"{"Z1K1": "Z2", "Z2K2": "text"}"#),
+        ];
+        global_context
+            .cache
+            .lock()
+            .unwrap()
+            .insert(zid!(10002), Some(WfData::new_reference(zid!(41))));
+        let (changes, failures) = global_context
+            .apply_dump_delta(dump_xml(&newer_pages).as_bytes())
+            .unwrap();
+        assert!(failures.is_empty());
+        assert!(global_context.fetched_zids().is_empty());
+        assert_eq!(changes.added, BTreeSet::from([zid!(10003)]));
+        assert_eq!(changes.modified, BTreeSet::from([zid!(10011)]));
+        assert_eq!(changes.removed, BTreeSet::from([zid!(10002), zid!(10012)]));
+        assert_eq!(global_context.objects.len(), 4);
+
+        let affected = changes.affected(&global_context);
+        assert_eq!(affected.functions, BTreeSet::from([zid!(10001)]));
+        assert_eq!(affected.test_cases, BTreeSet::from([zid!(10021)]));
+
+        // applying the same dump again change nothing
+        let (changes, _) = global_context
+            .apply_dump_delta(dump_xml(&newer_pages).as_bytes())
+            .unwrap();
+        assert!(changes.is_empty());
+
+        // a page that can’t be loaded anymore is reported, and its object removed
+        let broken_pages = [
+            pages[0],
+            ("Z10011", r#"{"Z1K1": "Z2"}"#),
+            TEST_CASE_PAGE,
+            newer_pages[3],
+        ];
+        let (changes, failures) = global_context
+            .apply_dump_delta(dump_xml(&broken_pages).as_bytes())
+            .unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].title, "Z10011");
+        assert_eq!(changes.removed, BTreeSet::from([zid!(10011)]));
+    }
+
+    #[test]
+    fn test_apply_page_directory() {
+        let mut global_context = fuzzing_pages_context();
+
+        let temp_directory = TempDirectory::new("page-directory");
+        let directory = temp_directory.path();
+        fs::write(directory.join("Z10011.json"), MODIFIED_IMPLEMENTATION).unwrap();
+        fs::write(directory.join("Z10021.json"), TEST_CASE_PAGE.1).unwrap();
+        fs::write(directory.join("Z10012.json"), FUZZING_PAGES[3].1).unwrap();
+        fs::write(directory.join("README.md"), "not a page").unwrap();
+        let changes = global_context.apply_page_directory(directory).unwrap();
+
+        assert_eq!(changes.added, BTreeSet::from([zid!(10021)]));
+        assert_eq!(changes.modified, BTreeSet::from([zid!(10011)]));
+        assert!(changes.removed.is_empty());
+        assert_eq!(
+            changes.affected(&global_context).test_cases,
+            BTreeSet::from([zid!(10021)])
+        );
     }
 }
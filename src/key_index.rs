@@ -5,6 +5,8 @@ use std::{
 
 use thiserror::Error;
 
+use crate::Zid;
+
 #[derive(Error, Debug, PartialEq, Clone)]
 pub enum KeyIndexParseError {
     #[error("the input key reference is empty")]
@@ -25,6 +27,21 @@ pub enum KeyIndexParseError {
     PartZZero(#[source] TryFromIntError),
     #[error("K-part shouldn’t be 0")]
     PartKZero(#[source] TryFromIntError),
+    #[error("numbers shouldn’t have leading zeros")]
+    LeadingZero,
+    #[error("numbers shouldn’t have a sign")]
+    SignNotAllowed,
+}
+
+/// Reject what u32::from_str accept but isn’t part of the key (and ZID) syntax
+pub(crate) fn check_number_syntax(text: &str) -> Result<(), KeyIndexParseError> {
+    if text.starts_with('+') {
+        return Err(KeyIndexParseError::SignNotAllowed);
+    }
+    if text.len() > 1 && text.starts_with('0') {
+        return Err(KeyIndexParseError::LeadingZero);
+    }
+    Ok(())
 }
 
 #[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -40,8 +57,46 @@ impl KeyIndex {
         self.1
     }
 
+    /// A key of a type, such as `Z1K1`
+    pub fn is_global_key(&self) -> bool {
+        self.0.is_some() && self.1.is_some()
+    }
+
+    /// A key without the type part, such as `K1` (used for typed list, pair, etc)
+    pub fn is_local_key(&self) -> bool {
+        self.0.is_none()
+    }
+
+    /// Return the ZID if this is only a ZID (such as `Z6`)
+    pub fn as_zid(&self) -> Option<Zid> {
+        match (self.0, self.1) {
+            (Some(z), None) => Some(Zid(z)),
+            _ => None,
+        }
+    }
+
+    /// Return the ZID of the type this key belong to, if this is a global key
+    pub fn get_type_zid(&self) -> Option<Zid> {
+        if self.is_global_key() {
+            self.0.map(Zid)
+        } else {
+            None
+        }
+    }
+
+    pub fn global(r#type: Zid, k: NonZeroU32) -> Self {
+        Self(Some(r#type.0), Some(k))
+    }
+
+    pub fn local(k: NonZeroU32) -> Self {
+        Self(None, Some(k))
+    }
+
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(text: &str) -> Result<Self, KeyIndexParseError> {
+        if text.is_empty() {
+            return Err(KeyIndexParseError::InputEmpty);
+        }
         let mut k_splitted = text.split('K');
 
         let before_key = k_splitted.next().ok_or(KeyIndexParseError::InputEmpty)?;
@@ -55,6 +110,7 @@ impl KeyIndex {
             {
                 return Err(KeyIndexParseError::FirstNotZOrK);
             }
+            check_number_syntax(char_id_iter.as_str())?;
             Some(
                 char_id_iter
                     .as_str()
@@ -66,6 +122,7 @@ impl KeyIndex {
         };
 
         let k = if let Some(second_part) = k_splitted.next() {
+            check_number_syntax(second_part)?;
             Some(
                 second_part
                     .parse()
@@ -159,6 +216,10 @@ macro_rules! keyindex {
 
 #[cfg(test)]
 mod tests {
+    use std::num::IntErrorKind;
+
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -193,4 +254,116 @@ mod tests {
             KeyIndex::from_u32s(Some(6), Some(2)).unwrap()
         )
     }
+
+    #[test]
+    fn test_reject_non_canonical() {
+        assert_eq!(
+            KeyIndex::from_str("Z01K1"),
+            Err(KeyIndexParseError::LeadingZero)
+        );
+        assert_eq!(
+            KeyIndex::from_str("Z1K01"),
+            Err(KeyIndexParseError::LeadingZero)
+        );
+        assert_eq!(
+            KeyIndex::from_str("Z+1"),
+            Err(KeyIndexParseError::SignNotAllowed)
+        );
+        assert_eq!(
+            KeyIndex::from_str("K+1"),
+            Err(KeyIndexParseError::SignNotAllowed)
+        );
+        assert_eq!(KeyIndex::from_str(""), Err(KeyIndexParseError::InputEmpty));
+    }
+
+    #[test]
+    fn test_key_kinds() {
+        let global = KeyIndex::from_str("Z1K1").unwrap();
+        assert!(global.is_global_key() && !global.is_local_key());
+        assert_eq!(global.get_type_zid(), Some(crate::zid!(1)));
+        assert_eq!(global.as_zid(), None);
+        assert_eq!(
+            global,
+            KeyIndex::global(crate::zid!(1), NonZeroU32::new(1).unwrap())
+        );
+
+        let local = KeyIndex::from_str("K2").unwrap();
+        assert!(local.is_local_key() && !local.is_global_key());
+        assert_eq!(local.get_type_zid(), None);
+        assert_eq!(local, KeyIndex::local(NonZeroU32::new(2).unwrap()));
+
+        let zid = KeyIndex::from_str("Z6").unwrap();
+        assert!(!zid.is_local_key() && !zid.is_global_key());
+        assert_eq!(zid.as_zid(), Some(crate::zid!(6)));
+    }
+
+    fn key_index_parts() -> impl Strategy<Value = (Option<u32>, Option<u32>)> {
+        (
+            proptest::option::of(1..=u32::MAX),
+            proptest::option::of(1..=u32::MAX),
+        )
+            .prop_filter("z and k can’t be both None", |(z, k)| {
+                z.is_some() || k.is_some()
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trip((z, k) in key_index_parts()) {
+            let key = KeyIndex::from_u32s(z, k).unwrap();
+            let text = key.to_string();
+            prop_assert_eq!(KeyIndex::from_str(&text), Ok(key));
+            prop_assert_eq!(key.get_z().map(|v| v.get()), z);
+            prop_assert_eq!(key.get_k().map(|v| v.get()), k);
+        }
+
+        #[test]
+        fn prop_valid_string_round_trip(text in "(Z[1-9][0-9]{0,8})?(K[1-9][0-9]{0,8})?") {
+            prop_assume!(!text.is_empty());
+            let key = KeyIndex::from_str(&text).unwrap();
+            prop_assert_eq!(key.to_string(), text.clone());
+            prop_assert_eq!(key.is_global_key(), text.starts_with('Z') && text.contains('K'));
+            prop_assert_eq!(key.is_local_key(), text.starts_with('K'));
+        }
+
+        /// Anything accepted is written the same way it was parsed (there is only one way to write a key)
+        #[test]
+        fn prop_accepted_is_canonical(text in "[ZK0-9+\\-a]{0,12}") {
+            if let Ok(key) = KeyIndex::from_str(&text) {
+                prop_assert_eq!(key.to_string(), text);
+            }
+        }
+
+        #[test]
+        fn prop_ordering((z1, k1) in key_index_parts(), (z2, k2) in key_index_parts()) {
+            let first = KeyIndex::from_u32s(z1, k1).unwrap();
+            let second = KeyIndex::from_u32s(z2, k2).unwrap();
+            // absent part are sorted first, so K1 < Z1 < Z1K1 < Z2
+            prop_assert_eq!(first.cmp(&second), (z1, k1).cmp(&(z2, k2)));
+        }
+
+        #[test]
+        fn prop_reject_leading_zero(z in 0..=u32::MAX, zeros in "0{1,3}") {
+            prop_assert_eq!(
+                KeyIndex::from_str(&format!("Z{}{}", zeros, z)),
+                Err(KeyIndexParseError::LeadingZero)
+            );
+            prop_assert_eq!(
+                KeyIndex::from_str(&format!("K{}{}", zeros, z)),
+                Err(KeyIndexParseError::LeadingZero)
+            );
+        }
+
+        #[test]
+        fn prop_reject_overflow(z in (u32::MAX as u64 + 1)..=u64::MAX, k in 1..=u32::MAX) {
+            match KeyIndex::from_str(&format!("Z{}K{}", z, k)) {
+                Err(KeyIndexParseError::CantParseZ(e)) => prop_assert_eq!(e.kind(), &IntErrorKind::PosOverflow),
+                other => prop_assert!(false, "expected overflow, got {:?}", other),
+            }
+            match KeyIndex::from_str(&format!("Z{}K{}", k, z)) {
+                Err(KeyIndexParseError::CantParseK(e)) => prop_assert_eq!(e.kind(), &IntErrorKind::PosOverflow),
+                other => prop_assert!(false, "expected overflow, got {:?}", other),
+            }
+        }
+    }
 }
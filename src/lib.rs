@@ -8,28 +8,48 @@ pub use key_index::{KeyIndex, KeyIndexParseError};
 mod zid;
 pub use zid::{Zid, ZidParseError};
 
+pub mod batch;
 pub mod data_types;
+pub mod dependency_graph;
 
 mod eval_error;
 pub use eval_error::{EvalError, EvalErrorKind, TraceEntry};
 
 mod execution_context;
-pub use execution_context::ExecutionContext;
+pub use execution_context::{
+    ExecutionContext, ExecutionLimits, MAX_FUNCTION_CALL_COUNT, MAX_FUNCTION_CALL_DEPTH,
+};
 
 pub mod util;
 
 pub mod parsing;
 
 mod global_context;
-pub use global_context::GlobalContext;
+pub use global_context::{
+    DEFAULT_DUMP_PATH, GlobalContext, LoadProgress, LoadReport, ObjectChanges, PageFailure,
+    decompress,
+};
 
+pub mod evaluation_result;
 pub mod functions;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
+pub mod minimise;
+pub mod object_source;
+pub mod overlay;
+pub mod repl;
 pub mod replay;
+pub mod server;
+pub mod test_runner;
+#[cfg(test)]
+mod test_support;
 
 #[cfg(test)]
 mod bench;
 
-use std::rc::Rc;
 /// Global lock to be used for everything in this interpreter that is stored inside of GlobalContext.
-/// May be (eventually optionally) switched to an Arc in the future for multi-threading.
-pub type RcI<T> = Rc<T>;
+/// Switched to an Arc with the `multithread` feature, so the GlobalContext can be shared between threads.
+#[cfg(not(feature = "multithread"))]
+pub type RcI<T> = std::rc::Rc<T>;
+#[cfg(feature = "multithread")]
+pub type RcI<T> = std::sync::Arc<T>;
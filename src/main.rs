@@ -1,6 +1,3 @@
-use std::{fs::File, io::BufReader};
-
-use anyhow::Context;
 use interpreter2::{ExecutionContext, GlobalContext, RcI, Zid, data_types::WfTestCase, zid};
 
 fn run_test_case(zid: Zid, context: &ExecutionContext) {
@@ -12,11 +9,11 @@ fn run_test_case(zid: Zid, context: &ExecutionContext) {
     test_case.run_test(context).unwrap()
 }
 
+/// Usage: `interpreter2 [DUMP]`. The dump may be compressed with gzip or bzip2.
 fn main() -> anyhow::Result<()> {
-    let file =
-        BufReader::new(File::open("./wikifunctionswiki-20251201-pages-meta-current.xml").unwrap());
-    let global_context =
-        RcI::new(GlobalContext::from_wikifunction_dump(file).context("loading dump")?);
+    let dump = std::env::args().nth(1);
+    let (global_context, _) = GlobalContext::load_for_cli(dump.as_deref(), None, false)?;
+    let global_context = RcI::new(global_context);
 
     let execution_context = ExecutionContext::default_for_global(global_context.clone());
 
@@ -0,0 +1,396 @@
+//! Delta-debugging reducer for failing evaluations.
+//!
+//! Starting from the data that fail to evaluate (typically the root of a replay), it repeatedly try to make it smaller
+//! (replacing subtrees by their evaluated value, removing list entries, inlining references), and keep a change only if
+//! evaluating the result still fail with the same error kind. The result can be serialized to JSON and evaluated without the dump
+//! (except for the function, types and implementations it reference).
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::{
+    EvalErrorKind, ExecutionContext, KeyIndex,
+    data_types::{WfData, WfDataType, WfTypedList},
+    parsing::serialize_json::serialize_to_string_pretty,
+    util::catch_panic,
+};
+
+#[derive(Debug)]
+pub struct MinimiseResult {
+    pub data: WfData,
+    /// Number of evaluations, of candidates and of the subtrees evaluated to generate them
+    pub attempts: usize,
+    /// Number of candidates that were kept
+    pub reductions: usize,
+}
+
+impl MinimiseResult {
+    pub fn to_json(&self) -> String {
+        serialize_to_string_pretty(&self.data)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PathStep {
+    Key(KeyIndex),
+    List(usize),
+}
+
+/// Evaluate the data in a fresh context, and check it fail with the same kind of error.
+pub fn reproduces(data: &WfData, kind: &EvalErrorKind, context: &ExecutionContext) -> bool {
+    let attempt_context = context.new_fresh();
+    match catch_panic(|| data.clone().evaluate(&attempt_context).map_err(|(e, _)| e)) {
+        Ok(_) => false,
+        Err(e) => e.get_kind().is_same_kind(kind),
+    }
+}
+
+/// Minimise the data while it still fail with the given error kind, doing no more than max_attempts evaluations.
+///
+/// Return None if the data does not fail with this error kind in the first place.
+pub fn minimise(
+    root: WfData,
+    kind: &EvalErrorKind,
+    context: &ExecutionContext,
+    max_attempts: usize,
+) -> Option<MinimiseResult> {
+    if !reproduces(&root, kind, context) {
+        return None;
+    }
+
+    let mut current = root;
+    let mut attempts = 0;
+    let mut reductions = 0;
+    // restart from the root after each successful reduction, so the larger subtrees are tried first
+    'restart: loop {
+        for (path, node) in Nodes::new(current.clone()) {
+            let mut candidates = Candidates::new(node, context);
+            while attempts < max_attempts
+                && let Some(candidate) = candidates.next()
+            {
+                attempts += candidates.take_evaluations();
+                if attempts >= max_attempts {
+                    break;
+                }
+                attempts += 1;
+                let new = replace_at(&current, &path, candidate);
+                if reproduces(&new, kind, context) {
+                    current = new;
+                    reductions += 1;
+                    continue 'restart;
+                }
+            }
+            attempts += candidates.take_evaluations();
+            if attempts >= max_attempts {
+                break 'restart;
+            }
+        }
+        break;
+    }
+
+    Some(MinimiseResult {
+        data: current,
+        attempts,
+        reductions,
+    })
+}
+
+/// Children that may be reduced. The type, the called function and the tested function are kept as-is.
+fn children(data: &WfData) -> Vec<(PathStep, WfData)> {
+    match data {
+        WfData::WfTypedList(list) => list
+            .iter()
+            .enumerate()
+            .map(|(pos, entry)| (PathStep::List(pos), entry))
+            .collect(),
+        WfData::WfUntyped(_) | WfData::WfFunctionCall(_) | WfData::WfTestCase(_) => data
+            .list_keys()
+            .into_iter()
+            .filter(|key| {
+                *key != keyindex!(1, 1) && *key != keyindex!(7, 1) && *key != keyindex!(20, 1)
+            })
+            .filter_map(|key| data.get_key(key).map(|value| (PathStep::Key(key), value)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Every node of the tree (including the root), in breadth-first order.
+/// The children of a node are only listed once it is reached, so stopping early doesn’t walk the rest of the tree.
+struct Nodes {
+    queue: VecDeque<(Vec<PathStep>, WfData)>,
+}
+
+impl Nodes {
+    fn new(root: WfData) -> Self {
+        Self {
+            queue: VecDeque::from([(Vec::new(), root)]),
+        }
+    }
+}
+
+impl Iterator for Nodes {
+    type Item = (Vec<PathStep>, WfData);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, data) = self.queue.pop_front()?;
+        for (step, child) in children(&data) {
+            let mut child_path = path.clone();
+            child_path.push(step);
+            self.queue.push_back((child_path, child));
+        }
+        Some((path, data))
+    }
+}
+
+/// Objects with an identity, that should stay referenced rather than inlined.
+fn has_identity(data: &WfData) -> bool {
+    matches!(
+        data,
+        WfData::WfFunction(_)
+            | WfData::WfType(_)
+            | WfData::WfBoolean(_)
+            | WfData::WfImplementation(_)
+    )
+}
+
+/// Possible replacements for a node, from the most to the least aggressive, generated lazily.
+struct Candidates<'c> {
+    pending: PendingCandidates,
+    context: &'c ExecutionContext,
+    /// evaluations done to generate the candidates, not yet counted as attempts
+    evaluations: usize,
+}
+
+enum PendingCandidates {
+    /// remove chunks of entries, starting with the whole list, then halves, ..., then single entries
+    ListChunks {
+        list: WfTypedList,
+        entries: Vec<WfData>,
+        chunk_size: usize,
+        start: usize,
+    },
+    /// replace by the evaluated value
+    Evaluate(WfData),
+    Done,
+}
+
+impl<'c> Candidates<'c> {
+    fn new(data: WfData, context: &'c ExecutionContext) -> Self {
+        let pending = match data {
+            WfData::WfTypedList(list) => {
+                let entries: Vec<WfData> = list.iter().collect();
+                PendingCandidates::ListChunks {
+                    list,
+                    chunk_size: entries.len(),
+                    entries,
+                    start: 0,
+                }
+            }
+            WfData::WfReference(_)
+            | WfData::WfUntyped(_)
+            | WfData::WfFunctionCall(_)
+            | WfData::WfTestCase(_) => PendingCandidates::Evaluate(data),
+            _ => PendingCandidates::Done,
+        };
+        Self {
+            pending,
+            context,
+            evaluations: 0,
+        }
+    }
+
+    fn take_evaluations(&mut self) -> usize {
+        std::mem::take(&mut self.evaluations)
+    }
+}
+
+impl Iterator for Candidates<'_> {
+    type Item = WfData;
+
+    fn next(&mut self) -> Option<WfData> {
+        match std::mem::replace(&mut self.pending, PendingCandidates::Done) {
+            PendingCandidates::ListChunks {
+                list,
+                entries,
+                mut chunk_size,
+                mut start,
+            } => {
+                if start >= entries.len() {
+                    chunk_size /= 2;
+                    start = 0;
+                }
+                if chunk_size == 0 {
+                    return None;
+                }
+                let mut remaining = entries.clone();
+                remaining.drain(start..(start + chunk_size).min(entries.len()));
+                let candidate =
+                    WfTypedList::new((*list.inner_type).clone(), remaining).into_wf_data();
+                self.pending = PendingCandidates::ListChunks {
+                    list,
+                    entries,
+                    chunk_size,
+                    start: start + chunk_size,
+                };
+                Some(candidate)
+            }
+            PendingCandidates::Evaluate(data) => {
+                self.evaluations += 1;
+                let attempt_context = self.context.new_fresh();
+                let evaluated =
+                    catch_panic(|| data.clone().evaluate(&attempt_context).map_err(|(e, _)| e))
+                        .ok()?;
+                let keep_reference =
+                    matches!(data, WfData::WfReference(_)) && has_identity(&evaluated);
+                (!keep_reference && evaluated != data).then_some(evaluated)
+            }
+            PendingCandidates::Done => None,
+        }
+    }
+}
+
+fn replace_at(data: &WfData, path: &[PathStep], replacement: WfData) -> WfData {
+    let Some((step, rest)) = path.split_first() else {
+        return replacement;
+    };
+    match (step, data) {
+        (PathStep::List(pos), WfData::WfTypedList(list)) => {
+            let mut entries: Vec<WfData> = list.iter().collect();
+            entries[*pos] = replace_at(&entries[*pos], rest, replacement);
+            WfTypedList::new((*list.inner_type).clone(), entries).into_wf_data()
+        }
+        (PathStep::Key(key), _) => {
+            let mut map: BTreeMap<KeyIndex, WfData> = data
+                .list_keys()
+                .into_iter()
+                .filter_map(|k| data.get_key(k).map(|value| (k, value)))
+                .collect();
+            let child = map
+                .get(key)
+                .expect("path generated from the same data")
+                .clone();
+            map.insert(*key, replace_at(&child, rest, replacement));
+            WfData::from_map(map)
+        }
+        _ => unreachable!("path generated from the same data"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map_macro::btree_map;
+
+    use crate::{
+        EvalErrorKind, ExecutionContext, GlobalContext, RcI,
+        data_types::{MaybeEvaluated, WfBoolean, WfData, WfDataType, WfTypedList},
+        minimise::minimise,
+    };
+
+    fn equality_call(first: WfData, second: WfData) -> WfData {
+        WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(7)),
+            keyindex!(7, 1) => WfData::new_reference(zid!(844)),
+            keyindex!(844, 1) => first,
+            keyindex!(844, 2) => second,
+        })
+    }
+
+    #[test]
+    fn test_minimise() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+
+        let root = equality_call(
+            equality_call(
+                WfData::new_reference(zid!(41)),
+                WfData::new_reference(zid!(42)),
+            ),
+            equality_call(
+                WfData::unvalid(EvalErrorKind::TestData),
+                equality_call(
+                    WfBoolean::new(true).into_wf_data(),
+                    WfBoolean::new(true).into_wf_data(),
+                ),
+            ),
+        );
+
+        let result = minimise(root, &EvalErrorKind::TestData, &context, 1000).unwrap();
+        assert_eq!(result.reductions, 2);
+        assert_eq!(
+            result.data.get_key(keyindex!(844, 1)),
+            Some(WfBoolean::new(false).into_wf_data())
+        );
+        let inner = result.data.get_key(keyindex!(844, 2)).unwrap();
+        assert_eq!(
+            inner.get_key(keyindex!(844, 2)),
+            Some(WfBoolean::new(true).into_wf_data())
+        );
+        // WfInvalid can’t be expressed in JSON, but the rest should still be parsable
+        assert!(
+            crate::parsing::parse_json::parse_value(
+                &sonic_rs::from_str(&result.to_json()).unwrap()
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_minimise_list() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+        let list = WfTypedList::new(
+            MaybeEvaluated::Unchecked(WfData::new_reference(zid!(40))),
+            vec![
+                WfBoolean::new(true).into_wf_data(),
+                WfBoolean::new(false).into_wf_data(),
+                WfData::unvalid(EvalErrorKind::TestData),
+            ],
+        );
+        let root = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(7)),
+            keyindex!(7, 1) => WfData::new_reference(zid!(844)),
+            keyindex!(844, 1) => list.into_wf_data(),
+            keyindex!(844, 2) => WfData::new_reference(zid!(41)),
+        });
+        let kind = root
+            .clone()
+            .evaluate(&context)
+            .unwrap_err()
+            .0
+            .get_kind()
+            .clone();
+
+        let result = minimise(root, &kind, &context, 1000).unwrap();
+        match result.data.get_key(keyindex!(844, 1)).unwrap() {
+            WfData::WfTypedList(list) => assert!(list.is_empty()),
+            other => panic!("expected a list, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_minimise_counts_generating_evaluations() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+        let root = equality_call(
+            equality_call(
+                WfData::new_reference(zid!(41)),
+                WfData::new_reference(zid!(42)),
+            ),
+            WfData::unvalid(EvalErrorKind::TestData),
+        );
+        // evaluating the root, to get a candidate for it, use the whole budget
+        let result = minimise(root, &EvalErrorKind::TestData, &context, 1).unwrap();
+        assert_eq!(result.attempts, 1);
+        assert_eq!(result.reductions, 0);
+    }
+
+    #[test]
+    fn test_minimise_not_reproduced() {
+        let context =
+            ExecutionContext::default_for_global(RcI::new(GlobalContext::default_for_test()));
+        let root = equality_call(
+            WfData::new_reference(zid!(41)),
+            WfData::new_reference(zid!(41)),
+        );
+        assert!(minimise(root, &EvalErrorKind::TestData, &context, 1000).is_none());
+    }
+}
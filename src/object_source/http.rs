@@ -0,0 +1,264 @@
+//! Fetch pages from a wiki running WikiLambda (such as Wikifunctions), through its `wikilambda_fetch` API.
+//!
+//! Only plain HTTP is supported, as there is no TLS implementation among the dependencies. Reaching Wikifunctions
+//! itself need a local proxy or mirror.
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use anyhow::{Context, bail};
+use sonic_rs::{JsonValueTrait, Value};
+
+use crate::{Zid, data_types::WfData, global_context::parse_page, object_source::ObjectSource};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct HttpSource {
+    host: String,
+    port: u16,
+    /// Path to `api.php`
+    path: String,
+}
+
+impl HttpSource {
+    /// The URL of `api.php`, such as `http://localhost:8080/w/api.php`
+    pub fn new(api_url: &str) -> Result<Self, anyhow::Error> {
+        let Some(rest) = api_url.strip_prefix("http://") else {
+            bail!("only http:// URLs are supported, got {}", api_url);
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(position) => (&rest[..position], &rest[position..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().context("parsing the port")?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            bail!("missing host in {}", api_url);
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    /// The status code and body of the response
+    fn get(&self, target: &str) -> Result<(u16, Vec<u8>), anyhow::Error> {
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port))
+            .with_context(|| format!("connecting to {}:{}", self.host, self.port))?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}:{}\r\nUser-Agent: interpreter2\r\nAccept: application/json\r\nConnection: close\r\n\r\n",
+            target, self.host, self.port
+        )?;
+        let mut response = Vec::new();
+        stream
+            .read_to_end(&mut response)
+            .context("reading the response")?;
+
+        let header_end = response
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .context("incomplete response headers")?;
+        let head = std::str::from_utf8(&response[..header_end]).context("decoding headers")?;
+        let mut lines = head.split("\r\n");
+        let status = lines
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|status| status.parse().ok())
+            .context("invalid status line")?;
+        let chunked = lines.any(|line| {
+            line.split_once(':').is_some_and(|(name, value)| {
+                name.trim().eq_ignore_ascii_case("transfer-encoding")
+                    && value.trim().eq_ignore_ascii_case("chunked")
+            })
+        });
+        let body = &response[header_end + 4..];
+        let body = if chunked {
+            decode_chunked(body)?
+        } else {
+            body.to_vec()
+        };
+        Ok((status, body))
+    }
+}
+
+fn decode_chunked(mut body: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+    let mut result = Vec::new();
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .context("incomplete chunk size")?;
+        let size_text = std::str::from_utf8(&body[..line_end]).context("decoding chunk size")?;
+        // ignore chunk extensions
+        let size_text = size_text.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_text, 16).context("parsing chunk size")?;
+        if size == 0 {
+            return Ok(result);
+        }
+        let chunk = body
+            .get(line_end + 2..line_end + 2 + size)
+            .context("incomplete chunk")?;
+        result.extend_from_slice(chunk);
+        body = body.get(line_end + 2 + size + 2..).unwrap_or_default();
+    }
+}
+
+impl ObjectSource for HttpSource {
+    /// The API reports pages that don’t exist as an error, so any error it reports is considered a missing object.
+    /// Failing to reach it, or an unexpected response, is an error.
+    fn fetch(&self, zid: Zid) -> Result<Option<WfData>, anyhow::Error> {
+        let (status, body) = self
+            .get(&format!(
+                "{}?action=wikilambda_fetch&format=json&zids={}",
+                self.path, zid
+            ))
+            .with_context(|| format!("fetching {}", zid))?;
+        if status != 200 {
+            bail!("fetching {}: HTTP status {}", zid, status);
+        }
+        let response: Value = sonic_rs::from_slice(&body)
+            .with_context(|| format!("parsing the response for {}", zid))?;
+        match response
+            .get(zid.to_string().as_str())
+            .and_then(|page| page.get("wikilambda_fetch"))
+            .and_then(|page| page.as_str())
+        {
+            Some(page) => parse_page(page)
+                .with_context(|| format!("parsing {}", zid))
+                .map(Some),
+            None if response.get("error").is_some() => Ok(None),
+            None => bail!("unexpected response for {}", zid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+    };
+
+    use tiny_http::{Response, Server};
+
+    use crate::{
+        EvalErrorKind, GlobalContext, Zid,
+        fuzzing::FUZZING_PAGES,
+        object_source::{HttpSource, ObjectSource, http::decode_chunked},
+        test_support::fuzzing_pages_context,
+    };
+
+    /// Answer like the `wikilambda_fetch` API, with the fuzzing pages. Z10003 is an internal server error.
+    fn mock_server() -> (
+        Arc<Server>,
+        String,
+        Arc<AtomicUsize>,
+        thread::JoinHandle<()>,
+    ) {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let port = server.server_addr().to_ip().unwrap().port();
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let handle = {
+            let server = server.clone();
+            let request_count = request_count.clone();
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    request_count.fetch_add(1, Ordering::SeqCst);
+                    let zid = request
+                        .url()
+                        .split_once("zids=")
+                        .map(|(_, zid)| zid.to_string())
+                        .unwrap_or_default();
+                    let response = match FUZZING_PAGES.iter().find(|(title, _)| *title == zid) {
+                        Some((_, body)) => Response::from_string(
+                            sonic_rs::json!({ zid.as_str(): { "wikilambda_fetch": body } })
+                                .to_string(),
+                        ),
+                        None if zid == "Z10003" => {
+                            Response::from_string("oops").with_status_code(500)
+                        }
+                        None => Response::from_string(
+                            r#"{"error": {"code": "wikilambda-zerror", "info": "Z504"}}"#,
+                        ),
+                    };
+                    request.respond(response).unwrap();
+                }
+            })
+        };
+        (
+            server,
+            format!("http://127.0.0.1:{}/w/api.php", port),
+            request_count,
+            handle,
+        )
+    }
+
+    #[test]
+    fn test_http_source() {
+        let (server, url, request_count, handle) = mock_server();
+
+        let source = HttpSource::new(&url).unwrap();
+        let expected = fuzzing_pages_context();
+        for (title, _) in FUZZING_PAGES {
+            let zid = Zid::from_str(title).unwrap();
+            assert_eq!(
+                source.fetch(zid).unwrap().as_ref(),
+                expected.objects.get(&zid)
+            );
+        }
+        assert_eq!(source.fetch(zid!(99999)).unwrap(), None);
+        assert!(source.fetch(zid!(10003)).is_err());
+
+        // the global context only fetch each object once
+        request_count.store(0, Ordering::SeqCst);
+        let global_context = GlobalContext::from_source(source);
+        for _ in 0..2 {
+            assert!(global_context.get_object_value(&zid!(10001)).is_ok());
+            assert!(matches!(
+                global_context
+                    .get_object_value(&zid!(99999))
+                    .unwrap_err()
+                    .get_kind(),
+                EvalErrorKind::MissingPersistentObject(_)
+            ));
+        }
+        assert_eq!(request_count.load(Ordering::SeqCst), 2);
+        assert!(matches!(
+            global_context
+                .get_object_value(&zid!(10003))
+                .unwrap_err()
+                .get_kind(),
+            EvalErrorKind::ObjectSourceFailed(..)
+        ));
+
+        server.unblock();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_url_and_chunks() {
+        assert!(HttpSource::new("https://www.wikifunctions.org/w/api.php").is_err());
+        let source = HttpSource::new("http://localhost").unwrap();
+        assert_eq!(
+            (source.host.as_str(), source.port, source.path.as_str()),
+            ("localhost", 80, "/")
+        );
+
+        assert_eq!(
+            decode_chunked(b"4\r\nWiki\r\n6;ext\r\npedia \r\n0\r\n\r\n").unwrap(),
+            b"Wikipedia "
+        );
+        assert!(decode_chunked(b"9\r\nWiki\r\n").is_err());
+    }
+}
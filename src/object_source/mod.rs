@@ -0,0 +1,216 @@
+//! Where persistent objects come from, when they are fetched on demand rather than loaded upfront (see
+//! `GlobalContext::from_source`). The global context caches what was fetched, so each object is fetched at most once.
+//!
+//! Sources keep the raw JSON of the pages, and only convert those that are fetched.
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{BufRead, ErrorKind},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use sonic_rs::{JsonValueTrait, Value};
+
+use crate::{
+    Zid,
+    data_types::WfData,
+    global_context::{decompress, parse_page},
+    parsing::parse_json,
+};
+
+mod http;
+pub use http::HttpSource;
+
+pub trait ObjectSource: Send + Sync {
+    /// The value (Z2K2) of the persistent object, or None if the source doesn’t have it
+    fn fetch(&self, zid: Zid) -> Result<Option<WfData>, anyhow::Error>;
+}
+
+/// The zobject pages of an XML dump
+pub struct DumpSource {
+    pages: BTreeMap<Zid, String>,
+}
+
+impl DumpSource {
+    /// The dump may be compressed (see `global_context::decompress`)
+    pub fn from_reader<F: BufRead>(reader: F) -> Result<Self, anyhow::Error> {
+        let mut pages = BTreeMap::new();
+        let reader = decompress(reader).context("reading the dump")?;
+        for result in parse_mediawiki_dump_reboot::parse(reader) {
+            let page = result.map_err(|e| anyhow::anyhow!("reading the dump: {}", e))?;
+            if page.model.as_deref() != Some("zobject") {
+                continue;
+            }
+            let zid = Zid::from_str(&page.title)
+                .with_context(|| format!("parsing page title {}", page.title))?;
+            pages.insert(zid, page.text);
+        }
+        Ok(Self { pages })
+    }
+}
+
+impl ObjectSource for DumpSource {
+    fn fetch(&self, zid: Zid) -> Result<Option<WfData>, anyhow::Error> {
+        self.pages
+            .get(&zid)
+            .map(|body| parse_page(body))
+            .transpose()
+    }
+}
+
+/// A directory of `Z12345.json` files, each containing a page (as in the dump)
+pub struct DirectorySource {
+    path: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl ObjectSource for DirectorySource {
+    fn fetch(&self, zid: Zid) -> Result<Option<WfData>, anyhow::Error> {
+        let path = self.path.join(format!("{}.json", zid));
+        let body = match fs::read_to_string(&path) {
+            Ok(body) => body,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+        };
+        parse_page(&body)
+            .with_context(|| format!("parsing {}", path.display()))
+            .map(Some)
+    }
+}
+
+/// A snapshot, as written by `GlobalContext::write_snapshot`
+pub struct SnapshotSource {
+    lines: BTreeMap<Zid, String>,
+}
+
+impl SnapshotSource {
+    /// Only the ZID of each line is parsed
+    pub fn from_reader<F: BufRead>(reader: F) -> Result<Self, anyhow::Error> {
+        let mut lines = BTreeMap::new();
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line.context("reading the snapshot")?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let zid = sonic_rs::get(&line, ["zid"])
+                .ok()
+                .and_then(|zid| zid.as_str().map(Zid::from_str))
+                .with_context(|| format!("missing zid at snapshot line {}", line_number + 1))?
+                .with_context(|| format!("parsing zid at snapshot line {}", line_number + 1))?;
+            lines.insert(zid, line);
+        }
+        Ok(Self { lines })
+    }
+}
+
+impl ObjectSource for SnapshotSource {
+    fn fetch(&self, zid: Zid) -> Result<Option<WfData>, anyhow::Error> {
+        let Some(line) = self.lines.get(&zid) else {
+            return Ok(None);
+        };
+        let entry: Value = sonic_rs::from_str(line)
+            .with_context(|| format!("parsing json of the snapshot line of {}", zid))?;
+        let value = entry
+            .get("value")
+            .with_context(|| format!("missing value for {}", zid))?;
+        Ok(Some(
+            parse_json::parse_value(value).with_context(|| format!("convert {} to IR", zid))?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        EvalErrorKind, ExecutionContext, GlobalContext, RcI,
+        data_types::{WfBoolean, WfData, WfDataType},
+        fuzzing::FUZZING_PAGES,
+        object_source::{DirectorySource, DumpSource, ObjectSource, SnapshotSource},
+        test_support::{TempDirectory, dump_xml, function_call, fuzzing_pages_context},
+    };
+
+    /// Each source should give the same objects as a fully loaded context
+    fn check_source<S: ObjectSource>(source: S) {
+        let expected = fuzzing_pages_context();
+        for (zid, value) in &expected.objects {
+            assert_eq!(source.fetch(*zid).unwrap().as_ref(), Some(value));
+        }
+        assert_eq!(source.fetch(zid!(99999)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_dump_source() {
+        let mut xml = dump_xml(FUZZING_PAGES);
+        xml.insert_str(
+            xml.len() - "</mediawiki>".len(),
+            "<page><title>Main Page</title><ns>0</ns><revision><model>wikitext</model><format>text/x-wiki</format><text>hello</text></revision></page>",
+        );
+        check_source(DumpSource::from_reader(xml.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_directory_source() {
+        let temp_directory = TempDirectory::new("directory-source");
+        let directory = temp_directory.path();
+        for (title, body) in FUZZING_PAGES {
+            fs::write(directory.join(format!("{}.json", title)), body).unwrap();
+        }
+        check_source(DirectorySource::new(directory));
+        fs::write(directory.join("Z10003.json"), "{").unwrap();
+        assert!(DirectorySource::new(directory).fetch(zid!(10003)).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_source() {
+        let mut snapshot = Vec::new();
+        fuzzing_pages_context()
+            .write_snapshot(&mut snapshot)
+            .unwrap();
+        check_source(SnapshotSource::from_reader(&snapshot[..]).unwrap());
+    }
+
+    #[test]
+    fn test_evaluate_from_source() {
+        let mut snapshot = Vec::new();
+        fuzzing_pages_context()
+            .write_snapshot(&mut snapshot)
+            .unwrap();
+        let global_context =
+            GlobalContext::from_source(SnapshotSource::from_reader(&snapshot[..]).unwrap());
+        // the builtin Z844 isn’t in the snapshot
+        let error = global_context.get_object_value(&zid!(844)).unwrap_err();
+        assert!(matches!(
+            error.get_kind(),
+            EvalErrorKind::MissingPersistentObject(_)
+        ));
+
+        // the builtins and core types are loaded upfront, the compositions are fetched from the snapshot
+        let mut global_context =
+            GlobalContext::from_source(SnapshotSource::from_reader(&snapshot[..]).unwrap());
+        global_context.objects = crate::fuzzing::fuzzing_global_context().objects;
+        for zid in fuzzing_pages_context().objects.keys() {
+            global_context.objects.remove(zid);
+        }
+        let context = ExecutionContext::default_for_global(RcI::new(global_context));
+        assert_eq!(
+            function_call(zid!(10001), vec![WfData::new_reference(zid!(41))])
+                .evaluate(&context)
+                .map_err(|(e, _)| e)
+                .unwrap(),
+            WfBoolean::new(false).into_wf_data()
+        );
+        // only what was needed was fetched
+        assert_eq!(
+            context.get_global().fetched_zids(),
+            vec![zid!(10001), zid!(10011)]
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! Hand-written or modified pages, loaded on top of a base global context (usually loaded from a dump).
+//!
+//! Pages are read from a directory of `Z12345.json` files (as the function-schemata data definitions), or from a
+//! stream of JSON pages (such as stdin). Pages that can’t be loaded are recorded with where they came from, rather than
+//! stopping the loading.
+use std::{
+    fmt::Display,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, bail};
+use sonic_rs::JsonValueTrait;
+
+use crate::{
+    GlobalContext, ObjectChanges, Zid, data_types::WfData, global_context::parse_page,
+    parsing::LoadError,
+};
+
+/// Where a page comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum PageOrigin {
+    File(PathBuf),
+    /// The 1-indexed position of the page in the stream
+    Stream(String, usize),
+}
+
+impl Display for PageOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Stream(name, position) => write!(f, "{} (page {})", name, position),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct OverlayError {
+    pub origin: PageOrigin,
+    pub error: anyhow::Error,
+}
+
+impl OverlayError {
+    /// The error converting the JSON to a ZObject, if that is what failed
+    pub fn load_error(&self) -> Option<&LoadError> {
+        LoadError::find_in(&self.error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverlayConflict {
+    /// The base already has a different value for the ZID
+    Base(Zid, PageOrigin),
+    /// The ZID is defined twice in the overlay. The second one is used.
+    Duplicate(Zid, PageOrigin, PageOrigin),
+}
+
+#[derive(Debug, Default)]
+pub struct Overlay {
+    pages: Vec<(Zid, PageOrigin, WfData)>,
+    errors: Vec<OverlayError>,
+}
+
+/// The ZID in Z2K1, either as a string or a reference
+fn page_identity(body: &str) -> Option<Zid> {
+    let identity = sonic_rs::get(body, ["Z2K1"]).ok()?;
+    let text = match identity.as_str() {
+        Some(text) => text.to_string(),
+        None => sonic_rs::get(identity.as_raw_str(), ["Z6K1"])
+            .ok()?
+            .as_str()?
+            .to_string(),
+    };
+    Zid::from_str(&text).ok()
+}
+
+impl Overlay {
+    /// Every `Z12345.json` file of the directory (other files are ignored). Fails only if the directory can’t be listed.
+    pub fn from_directory(path: &Path) -> Result<Self, anyhow::Error> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(path).with_context(|| format!("listing {}", path.display()))? {
+            let path = entry
+                .with_context(|| format!("listing {}", path.display()))?
+                .path();
+            let is_page = path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+                && path
+                    .file_stem()
+                    .is_some_and(|stem| Zid::from_str(&stem.to_string_lossy()).is_ok());
+            if is_page {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut overlay = Self::default();
+        for path in paths {
+            let zid = Zid::from_str(
+                &path
+                    .file_stem()
+                    .expect("checked when listing")
+                    .to_string_lossy(),
+            )
+            .expect("checked when listing");
+            let result = fs::read_to_string(&path)
+                .context("reading the file")
+                .and_then(|body| {
+                    if let Some(identity) = page_identity(&body)
+                        && identity != zid
+                    {
+                        bail!("the page is {} but the file is named {}", identity, zid);
+                    }
+                    parse_page(&body)
+                });
+            overlay.push(zid, PageOrigin::File(path), result);
+        }
+        Ok(overlay)
+    }
+
+    /// A stream of JSON pages, one after another (such as JSON Lines), identified by their Z2K1. The name is used to
+    /// report where the errors come from. The stream can’t be read past invalid JSON.
+    pub fn from_reader<R: Read>(reader: R, name: &str) -> Self {
+        let mut overlay = Self::default();
+        let stream = serde_json::Deserializer::from_reader(reader).into_iter::<serde_json::Value>();
+        for (index, page) in stream.enumerate() {
+            let origin = PageOrigin::Stream(name.to_string(), index + 1);
+            let page = match page {
+                Ok(page) => page.to_string(),
+                Err(e) => {
+                    overlay.errors.push(OverlayError {
+                        origin,
+                        error: anyhow::Error::new(e).context("parsing json of the page"),
+                    });
+                    break;
+                }
+            };
+            match page_identity(&page) {
+                Some(zid) => overlay.push(zid, origin, parse_page(&page)),
+                None => overlay.errors.push(OverlayError {
+                    origin,
+                    error: anyhow::anyhow!("the page has no Z2K1 identity"),
+                }),
+            }
+        }
+        overlay
+    }
+
+    fn push(&mut self, zid: Zid, origin: PageOrigin, result: Result<WfData, anyhow::Error>) {
+        match result {
+            Ok(value) => self.pages.push((zid, origin, value)),
+            Err(error) => self.errors.push(OverlayError { origin, error }),
+        }
+    }
+
+    /// The pages that couldn’t be loaded
+    pub fn errors(&self) -> &[OverlayError] {
+        &self.errors
+    }
+
+    pub fn zids(&self) -> impl Iterator<Item = Zid> {
+        self.pages.iter().map(|(zid, _, _)| *zid)
+    }
+
+    pub fn conflicts(&self, base: &GlobalContext) -> Vec<OverlayConflict> {
+        let mut result = Vec::new();
+        for (position, (zid, origin, value)) in self.pages.iter().enumerate() {
+            if let Some((_, first_origin, _)) = self.pages[..position]
+                .iter()
+                .find(|(other, _, _)| other == zid)
+            {
+                result.push(OverlayConflict::Duplicate(
+                    *zid,
+                    first_origin.clone(),
+                    origin.clone(),
+                ));
+            } else if base
+                .get_object_value(zid)
+                .is_ok_and(|existing| &existing != value)
+            {
+                result.push(OverlayConflict::Base(*zid, origin.clone()));
+            }
+        }
+        result
+    }
+
+    /// Add or replace the pages that were loaded in the base
+    pub fn apply(self, base: &mut GlobalContext) -> ObjectChanges {
+        let mut changes = ObjectChanges::default();
+        for (zid, _, value) in self.pages {
+            base.update_object(zid, value, &mut changes);
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, fs};
+
+    use crate::{
+        fuzzing::FUZZING_PAGES,
+        overlay::{Overlay, OverlayConflict, PageOrigin},
+        parsing::LoadError,
+        test_support::{TempDirectory, fuzzing_pages_context},
+    };
+
+    #[test]
+    fn test_directory_overlay() {
+        let temp_directory = TempDirectory::new("overlay");
+        let directory = temp_directory.path();
+        // same as the base
+        fs::write(directory.join("Z10001.json"), FUZZING_PAGES[0].1).unwrap();
+        // different from the base
+        fs::write(
+            directory.join("Z10011.json"),
+            r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10011"}, "Z2K2": "Z41"}"#,
+        )
+        .unwrap();
+        fs::write(
+            directory.join("Z10021.json"),
+            r#"{"Z1K1": "Z2", "Z2K1": "Z10021", "Z2K2": "Z42"}"#,
+        )
+        .unwrap();
+        // a LoadError
+        fs::write(
+            directory.join("Z10022.json"),
+            r#"{"Z1K1": "Z2", "Z2K2": {"Z1K1": "Z11", "Z11K2": []}}"#,
+        )
+        .unwrap();
+        // not the right identity
+        fs::write(
+            directory.join("Z10023.json"),
+            r#"{"Z1K1": "Z2", "Z2K1": "Z10024", "Z2K2": "Z42"}"#,
+        )
+        .unwrap();
+        fs::write(directory.join("notes.json"), "{").unwrap();
+        let overlay = Overlay::from_directory(directory).unwrap();
+
+        let errors = overlay.errors();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].origin,
+            PageOrigin::File(directory.join("Z10022.json"))
+        );
+        assert!(matches!(
+            errors[0].load_error(),
+            Some(LoadError::InsideMap(_, _))
+        ));
+        assert!(errors[1].error.to_string().contains("Z10024"));
+        assert_eq!(errors[1].load_error(), None);
+
+        let mut global_context = fuzzing_pages_context();
+        assert_eq!(
+            overlay.conflicts(&global_context),
+            vec![OverlayConflict::Base(
+                zid!(10011),
+                PageOrigin::File(directory.join("Z10011.json"))
+            )]
+        );
+        let changes = overlay.apply(&mut global_context);
+        assert_eq!(changes.added, BTreeSet::from([zid!(10021)]));
+        assert_eq!(changes.modified, BTreeSet::from([zid!(10011)]));
+    }
+
+    #[test]
+    fn test_stream_overlay() {
+        let input = concat!(
+            r#"{"Z1K1": "Z2", "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10021"}, "Z2K2": "Z41"}"#,
+            "\n",
+            r#"{"Z1K1": "Z2", "Z2K2": "Z41"}"#,
+            "\n",
+            r#"{
+                "Z1K1": "Z2",
+                "Z2K1": {"Z1K1": "Z6", "Z6K1": "Z10021"},
+                "Z2K2": "Z42"
+            }"#,
+            "\n{"
+        );
+        let overlay = Overlay::from_reader(input.as_bytes(), "stdin");
+        assert_eq!(overlay.zids().collect::<Vec<_>>(), vec![zid!(10021); 2]);
+        let errors = overlay.errors();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].origin, PageOrigin::Stream("stdin".to_string(), 2));
+        assert_eq!(errors[1].origin.to_string(), "stdin (page 4)");
+
+        assert_eq!(
+            overlay.conflicts(&fuzzing_pages_context()),
+            vec![OverlayConflict::Duplicate(
+                zid!(10021),
+                PageOrigin::Stream("stdin".to_string(), 1),
+                PageOrigin::Stream("stdin".to_string(), 3)
+            )]
+        );
+    }
+}
@@ -0,0 +1,633 @@
+//! A compact textual syntax for ZObjects, more readable than the canonical JSON for compositions:
+//!
+//! - `Z41` is a reference, `"text"` a string (with JSON escapes)
+//! - `Z802(Z844(Z41, $K1), "yes", "no")` is a Z7 function call, with the arguments in order (`Z802K1`, `Z802K2`, …)
+//! - `$K1` is a Z18 argument reference (to `K1`, `$Z10001K1` is also accepted)
+//! - `[Z6: "a", "b"]` is a typed list (`[Z6]` when empty)
+//! - `{Z1K1: Z11, Z11K1: Z1002, Z11K2: "text"}` is any other object
+//!
+//! References and called functions can also be written with a label, defined in `Aliases`.
+//!
+//! Both the parser and the printer go through the canonical JSON form, so the result is the same as
+//! `parse_json::parse_value` (and the printer works on every `WfData`).
+use std::collections::BTreeMap;
+
+use sonic_rs::{Array, JsonValueTrait, Object, Value, ValueRef};
+use thiserror::Error;
+
+use crate::{
+    KeyIndex, KeyIndexParseError, Zid,
+    data_types::WfData,
+    parsing::{LoadError, parse_json::parse_value, serialize_json::serialize_value},
+};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CompactParseError {
+    #[error("unexpected end of input, expected {0}")]
+    UnexpectedEnd(&'static str),
+    #[error("unexpected {found:?} at byte {position}, expected {expected}")]
+    Unexpected {
+        found: char,
+        position: usize,
+        expected: &'static str,
+    },
+    #[error("invalid string literal at byte {0}")]
+    InvalidString(usize),
+    #[error("invalid key {0:?}")]
+    InvalidKey(String, #[source] KeyIndexParseError),
+    #[error("{0:?} is neither a ZID nor a known alias")]
+    UnknownAlias(String),
+    #[error(
+        "{0:?} can’t be used as an alias (it should be made of letters, digits and _, and not be a ZID)"
+    )]
+    InvalidAlias(String),
+    #[error("invalid ZObject")]
+    Load(#[from] LoadError),
+}
+
+/// Labels that can be used in place of ZIDs
+#[derive(Debug, Clone, Default)]
+pub struct Aliases {
+    to_zid: BTreeMap<String, Zid>,
+    /// The first label added for each ZID, used when printing
+    to_label: BTreeMap<Zid, String>,
+}
+
+impl Aliases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, label: &str, zid: Zid) -> Result<(), CompactParseError> {
+        let is_word = label
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && label.chars().all(is_word_char);
+        if !is_word || Zid::from_str(label).is_ok() {
+            return Err(CompactParseError::InvalidAlias(label.to_string()));
+        }
+        self.to_zid.insert(label.to_string(), zid);
+        self.to_label
+            .entry(zid)
+            .or_insert_with(|| label.to_string());
+        Ok(())
+    }
+
+    pub fn get_zid(&self, label: &str) -> Option<Zid> {
+        self.to_zid.get(label).copied()
+    }
+
+    pub fn get_label(&self, zid: &Zid) -> Option<&str> {
+        self.to_label.get(zid).map(String::as_str)
+    }
+
+    fn name(&self, zid: &Zid) -> String {
+        match self.get_label(zid) {
+            Some(label) => label.to_string(),
+            None => zid.to_string(),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// The syntax tree, shared by the parser and the printer
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Reference(Zid),
+    String(String),
+    Argument(String),
+    Call(Zid, Vec<Node>),
+    /// The first element is the type. Empty only if the (invalid) JSON array is empty.
+    List(Vec<Node>),
+    Object(Vec<(String, Node)>),
+}
+
+impl Node {
+    fn from_value(value: &Value) -> Self {
+        match value.as_ref() {
+            ValueRef::String(text) => match Zid::from_str(text) {
+                Ok(zid) => Node::Reference(zid),
+                Err(_) => Node::String(text.to_string()),
+            },
+            ValueRef::Array(array) => Node::List(array.iter().map(Node::from_value).collect()),
+            ValueRef::Object(object) => Self::from_object(object),
+            // not produced by the serializer
+            _ => Node::String(value.to_string()),
+        }
+    }
+
+    fn from_object(object: &Object) -> Self {
+        let entries: BTreeMap<&str, &Value> = object.iter().collect();
+        let get_str = |key: &str| entries.get(key).and_then(|v| v.as_str());
+
+        if entries.len() == 2 {
+            match (
+                get_str("Z1K1"),
+                get_str("Z6K1"),
+                get_str("Z9K1").map(Zid::from_str),
+                get_str("Z18K1"),
+            ) {
+                (Some("Z6"), Some(text), _, _) => return Node::String(text.to_string()),
+                (Some("Z9"), _, Some(Ok(zid)), _) => return Node::Reference(zid),
+                (Some("Z18"), _, _, Some(text)) if KeyIndex::from_str(text).is_ok() => {
+                    return Node::Argument(text.to_string());
+                }
+                _ => (),
+            }
+        }
+
+        if let (Some("Z7"), Some(Ok(function))) =
+            (get_str("Z1K1"), get_str("Z7K1").map(Zid::from_str))
+        {
+            // only if the arguments are exactly the keys of the function, so they can be written in order
+            let arguments: Option<Vec<Node>> = (1..entries.len() - 1)
+                .map(|index| {
+                    entries
+                        .get(format!("{}K{}", function, index).as_str())
+                        .map(|v| Node::from_value(v))
+                })
+                .collect();
+            if let Some(arguments) = arguments {
+                return Node::Call(function, arguments);
+            }
+        }
+
+        // objects built by sonic_rs don’t keep the order of their keys
+        let mut sorted: Vec<(&str, &Value)> = object.iter().collect();
+        sorted.sort_by_key(|(k, _)| (KeyIndex::from_str(k).ok(), *k));
+        Node::Object(
+            sorted
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), Node::from_value(v)))
+                .collect(),
+        )
+    }
+
+    fn to_value(&self) -> Value {
+        let typed = |r#type: &str, key: &str, value: Value| {
+            let mut object = Object::new();
+            object.insert(&"Z1K1", Value::from(r#type));
+            object.insert(&key, value);
+            object.into_value()
+        };
+        match self {
+            Node::Reference(zid) => Value::from(zid.to_string().as_str()),
+            // always explicitly typed, as the text may look like a reference
+            Node::String(text) => typed("Z6", "Z6K1", Value::from(text.as_str())),
+            Node::Argument(key) => typed("Z18", "Z18K1", Value::from(key.as_str())),
+            Node::Call(function, arguments) => {
+                let mut object = Object::new();
+                object.insert(&"Z1K1", Value::from("Z7"));
+                object.insert(&"Z7K1", Value::from(function.to_string().as_str()));
+                for (index, argument) in arguments.iter().enumerate() {
+                    object.insert(&format!("{}K{}", function, index + 1), argument.to_value());
+                }
+                object.into_value()
+            }
+            Node::List(entries) => {
+                let mut array = Array::new();
+                for entry in entries {
+                    array.push(entry.to_value());
+                }
+                array.into_value()
+            }
+            Node::Object(entries) => {
+                let mut object = Object::new();
+                for (key, value) in entries {
+                    object.insert(key, value.to_value());
+                }
+                object.into_value()
+            }
+        }
+    }
+
+    fn write_flat(&self, aliases: &Aliases, output: &mut String) {
+        match self {
+            Node::Reference(zid) => output.push_str(&aliases.name(zid)),
+            Node::String(text) => output.push_str(&quote(text)),
+            Node::Argument(key) => {
+                output.push('$');
+                output.push_str(key);
+            }
+            Node::Call(function, arguments) => {
+                output.push_str(&aliases.name(function));
+                output.push('(');
+                for (index, argument) in arguments.iter().enumerate() {
+                    if index > 0 {
+                        output.push_str(", ");
+                    }
+                    argument.write_flat(aliases, output);
+                }
+                output.push(')');
+            }
+            Node::List(entries) => {
+                output.push('[');
+                for (index, entry) in entries.iter().enumerate() {
+                    output.push_str(match index {
+                        0 => "",
+                        1 => ": ",
+                        _ => ", ",
+                    });
+                    entry.write_flat(aliases, output);
+                }
+                output.push(']');
+            }
+            Node::Object(entries) => {
+                output.push('{');
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        output.push_str(", ");
+                    }
+                    output.push_str(key);
+                    output.push_str(": ");
+                    value.write_flat(aliases, output);
+                }
+                output.push('}');
+            }
+        }
+    }
+
+    /// Write on a single line if it fit in the width, otherwise put each child on its own line
+    fn write_pretty(&self, aliases: &Aliases, indent: usize, width: usize, output: &mut String) {
+        let mut flat = String::new();
+        self.write_flat(aliases, &mut flat);
+        if indent + flat.chars().count() <= width {
+            output.push_str(&flat);
+            return;
+        }
+
+        let inner_indent = " ".repeat(indent + 4);
+        let write_children = |children: Vec<(Option<&str>, &Node)>, output: &mut String| {
+            output.push('\n');
+            let count = children.len();
+            for (index, (key, child)) in children.into_iter().enumerate() {
+                output.push_str(&inner_indent);
+                if let Some(key) = key {
+                    output.push_str(key);
+                    output.push_str(": ");
+                }
+                child.write_pretty(aliases, indent + 4, width, output);
+                if index + 1 < count {
+                    output.push(',');
+                }
+                output.push('\n');
+            }
+            output.push_str(&" ".repeat(indent));
+        };
+        match self {
+            Node::Call(function, arguments) if !arguments.is_empty() => {
+                output.push_str(&aliases.name(function));
+                output.push('(');
+                write_children(arguments.iter().map(|a| (None, a)).collect(), output);
+                output.push(')');
+            }
+            Node::List(entries) if entries.len() > 1 => {
+                output.push('[');
+                entries[0].write_flat(aliases, output);
+                output.push(':');
+                write_children(entries[1..].iter().map(|e| (None, e)).collect(), output);
+                output.push(']');
+            }
+            Node::Object(entries) if !entries.is_empty() => {
+                output.push('{');
+                write_children(
+                    entries.iter().map(|(k, v)| (Some(k.as_str()), v)).collect(),
+                    output,
+                );
+                output.push('}');
+            }
+            _ => output.push_str(&flat),
+        }
+    }
+}
+
+fn quote(text: &str) -> String {
+    sonic_rs::to_string(text).expect("serializing a string shouldn’t fail")
+}
+
+struct Parser<'l> {
+    text: &'l str,
+    position: usize,
+    aliases: &'l Aliases,
+}
+
+impl<'l> Parser<'l> {
+    fn rest(&self) -> &'l str {
+        &self.text[self.position..]
+    }
+
+    /// The next non-whitespace character, without consuming it
+    fn peek(&mut self) -> Option<char> {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+        self.rest().chars().next()
+    }
+
+    fn error(&mut self, expected: &'static str) -> CompactParseError {
+        match self.peek() {
+            Some(found) => CompactParseError::Unexpected {
+                found,
+                position: self.position,
+                expected,
+            },
+            None => CompactParseError::UnexpectedEnd(expected),
+        }
+    }
+
+    fn expect(
+        &mut self,
+        expected_char: char,
+        expected: &'static str,
+    ) -> Result<(), CompactParseError> {
+        if self.peek() == Some(expected_char) {
+            self.position += expected_char.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(expected))
+        }
+    }
+
+    fn word(&mut self, expected: &'static str) -> Result<&'l str, CompactParseError> {
+        self.peek();
+        let rest = self.rest();
+        let length = rest.find(|c| !is_word_char(c)).unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error(expected));
+        }
+        self.position += length;
+        Ok(&rest[..length])
+    }
+
+    fn key(&mut self) -> Result<&'l str, CompactParseError> {
+        let key = self.word("a key")?;
+        KeyIndex::from_str(key).map_err(|e| CompactParseError::InvalidKey(key.to_string(), e))?;
+        Ok(key)
+    }
+
+    fn zid(&mut self) -> Result<Zid, CompactParseError> {
+        let word = self.word("a value")?;
+        Zid::from_str(word)
+            .ok()
+            .or_else(|| self.aliases.get_zid(word))
+            .ok_or_else(|| CompactParseError::UnknownAlias(word.to_string()))
+    }
+
+    fn string(&mut self) -> Result<String, CompactParseError> {
+        let start = self.position;
+        let mut escaped = false;
+        for (offset, c) in self.rest().char_indices().skip(1) {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    self.position += offset + 1;
+                    return sonic_rs::from_str(&self.text[start..self.position])
+                        .map_err(|_| CompactParseError::InvalidString(start));
+                }
+                _ => (),
+            }
+        }
+        Err(CompactParseError::UnexpectedEnd("the end of the string"))
+    }
+
+    /// Comma separated nodes, up to and including the end character
+    fn sequence(&mut self, end: char) -> Result<Vec<Node>, CompactParseError> {
+        let mut result = Vec::new();
+        if self.peek() == Some(end) {
+            self.position += 1;
+            return Ok(result);
+        }
+        loop {
+            result.push(self.node()?);
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(c) if c == end => {
+                    self.position += 1;
+                    return Ok(result);
+                }
+                _ => return Err(self.error("a comma or the end of the sequence")),
+            }
+        }
+    }
+
+    fn node(&mut self) -> Result<Node, CompactParseError> {
+        match self.peek() {
+            Some('"') => Ok(Node::String(self.string()?)),
+            Some('$') => {
+                self.position += 1;
+                Ok(Node::Argument(self.key()?.to_string()))
+            }
+            Some('[') => {
+                self.position += 1;
+                if self.peek() == Some(']') {
+                    self.position += 1;
+                    return Ok(Node::List(Vec::new()));
+                }
+                let mut entries = vec![self.node()?];
+                if self.peek() == Some(':') {
+                    self.position += 1;
+                    entries.extend(self.sequence(']')?);
+                } else {
+                    self.expect(']', "a colon or the end of the list")?;
+                }
+                Ok(Node::List(entries))
+            }
+            Some('{') => {
+                self.position += 1;
+                let mut entries = Vec::new();
+                if self.peek() == Some('}') {
+                    self.position += 1;
+                    return Ok(Node::Object(entries));
+                }
+                loop {
+                    let key = self.key()?.to_string();
+                    self.expect(':', "a colon")?;
+                    entries.push((key, self.node()?));
+                    match self.peek() {
+                        Some(',') => self.position += 1,
+                        Some('}') => {
+                            self.position += 1;
+                            return Ok(Node::Object(entries));
+                        }
+                        _ => return Err(self.error("a comma or the end of the object")),
+                    }
+                }
+            }
+            Some(c) if is_word_char(c) => {
+                let zid = self.zid()?;
+                if self.peek() == Some('(') {
+                    self.position += 1;
+                    Ok(Node::Call(zid, self.sequence(')')?))
+                } else {
+                    Ok(Node::Reference(zid))
+                }
+            }
+            _ => Err(self.error("a value")),
+        }
+    }
+}
+
+pub fn parse_compact(text: &str, aliases: &Aliases) -> Result<WfData, CompactParseError> {
+    let mut parser = Parser {
+        text,
+        position: 0,
+        aliases,
+    };
+    let node = parser.node()?;
+    if parser.peek().is_some() {
+        return Err(parser.error("the end of the input"));
+    }
+    Ok(parse_value(&node.to_value())?)
+}
+
+/// Print on a single line
+pub fn print_compact(data: &WfData, aliases: &Aliases) -> String {
+    let mut output = String::new();
+    Node::from_value(&serialize_value(data)).write_flat(aliases, &mut output);
+    output
+}
+
+/// Print over multiple lines, indented, when it doesn’t fit in the width
+pub fn print_compact_pretty(data: &WfData, aliases: &Aliases, width: usize) -> String {
+    let mut output = String::new();
+    Node::from_value(&serialize_value(data)).write_pretty(aliases, 0, width, &mut output);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExecutionContext, RcI,
+        data_types::{WfBoolean, WfDataType},
+        parsing::{
+            compact::{
+                Aliases, CompactParseError, parse_compact, print_compact, print_compact_pretty,
+            },
+            parse_json::parse_value,
+        },
+    };
+
+    #[test]
+    fn test_parse_compact() {
+        let aliases = Aliases::new();
+        let parsed =
+            parse_compact(r#"Z802(Z844(Z41, $K1), "yes", [Z6: "a", "Z4"])"#, &aliases).unwrap();
+        let expected = parse_value(
+            &sonic_rs::from_str(
+                r#"{
+                "Z1K1": "Z7",
+                "Z7K1": "Z802",
+                "Z802K1": {"Z1K1": "Z7", "Z7K1": "Z844", "Z844K1": "Z41", "Z844K2": {"Z1K1": "Z18", "Z18K1": "K1"}},
+                "Z802K2": "yes",
+                "Z802K3": ["Z6", "a", {"Z1K1": "Z6", "Z6K1": "Z4"}]
+            }"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(parsed, expected);
+
+        assert_eq!(
+            parse_compact(r#" { Z1K1: Z11, Z11K1: Z1002, Z11K2: "a\"b" } "#, &aliases).unwrap(),
+            parse_value(
+                &sonic_rs::from_str(r#"{"Z1K1": "Z11", "Z11K1": "Z1002", "Z11K2": "a\"b"}"#)
+                    .unwrap()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut aliases = Aliases::new();
+        aliases.insert("if", zid!(802)).unwrap();
+        aliases.insert("true", zid!(41)).unwrap();
+        for text in [
+            r#"if(Z844(true, $K1), "yes", "no")"#,
+            r#"[Z6: "a", "Z4", "with \"quotes\"\n"]"#,
+            "[Z6]",
+            "Z10001($Z10001K1)",
+            r#"{Z1K1: Z11, Z11K1: Z1002, Z11K2: "text"}"#,
+            "{Z1K1: Z7, Z7K1: if, Z802K2: true}",
+        ] {
+            let parsed = parse_compact(text, &aliases).unwrap();
+            assert_eq!(print_compact(&parsed, &aliases), text);
+        }
+        // without the aliases
+        let parsed = parse_compact("if(true)", &aliases).unwrap();
+        assert_eq!(print_compact(&parsed, &Aliases::new()), "Z802(Z41)");
+        assert_eq!(
+            print_compact(&WfBoolean::new(false).into_wf_data(), &Aliases::new()),
+            "Z42"
+        );
+    }
+
+    #[test]
+    fn test_pretty() {
+        let aliases = Aliases::new();
+        let parsed = parse_compact(r#"Z802(Z844(Z41, $K1), "yes", [Z6: "a"])"#, &aliases).unwrap();
+        assert_eq!(
+            print_compact_pretty(&parsed, &aliases, 30),
+            "Z802(\n    Z844(Z41, $K1),\n    \"yes\",\n    [Z6: \"a\"]\n)"
+        );
+        assert_eq!(
+            print_compact_pretty(&parsed, &aliases, 80),
+            print_compact(&parsed, &aliases)
+        );
+        assert_eq!(
+            parse_compact(&print_compact_pretty(&parsed, &aliases, 10), &aliases).unwrap(),
+            parsed
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let aliases = Aliases::new();
+        assert_eq!(
+            parse_compact("Z802(Z41", &aliases),
+            Err(CompactParseError::UnexpectedEnd(
+                "a comma or the end of the sequence"
+            ))
+        );
+        assert_eq!(
+            parse_compact("not(Z41)", &aliases),
+            Err(CompactParseError::UnknownAlias("not".to_string()))
+        );
+        assert!(matches!(
+            parse_compact("Z41 Z42", &aliases),
+            Err(CompactParseError::Unexpected {
+                found: 'Z',
+                position: 4,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_compact("$K0", &aliases),
+            Err(CompactParseError::InvalidKey(..))
+        ));
+        assert!(matches!(
+            parse_compact("[]", &aliases),
+            Err(CompactParseError::Load(_))
+        ));
+        assert!(Aliases::new().insert("Z12", zid!(12)).is_err());
+        assert!(Aliases::new().insert("1a", zid!(12)).is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let context = ExecutionContext::default_for_global(RcI::new(
+            crate::fuzzing::fuzzing_global_context(),
+        ));
+        let mut aliases = Aliases::new();
+        aliases.insert("not", zid!(10001)).unwrap();
+        let data = parse_compact("not(not(Z41))", &aliases).unwrap();
+        assert_eq!(
+            data.evaluate(&context).map_err(|(e, _)| e).unwrap(),
+            WfBoolean::new(true).into_wf_data()
+        );
+    }
+}
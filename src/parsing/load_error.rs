@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 use sonic_rs::Value;
 use thiserror::Error;
 
@@ -26,3 +28,56 @@ pub enum LoadError {
     )]
     EmptyArray,
 }
+
+/// A step of the path from the root of the JSON to where a `LoadError` happened
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsonPathSegment {
+    Key(KeyIndex),
+    /// The position in the JSON array, where 0 is the type of the list
+    Index(usize),
+}
+
+impl Display for JsonPathSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Key(key) => write!(f, ".{}", key),
+            Self::Index(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
+impl LoadError {
+    /// The path given by the `InsideMap` and `InsideArray` wrappers, from the outermost
+    pub fn path(&self) -> Vec<JsonPathSegment> {
+        let mut result = Vec::new();
+        let mut current = self;
+        loop {
+            match current {
+                Self::InsideMap(key, inner) => {
+                    result.push(JsonPathSegment::Key(*key));
+                    current = inner;
+                }
+                Self::InsideArray(index, inner) => {
+                    result.push(JsonPathSegment::Index(*index));
+                    current = inner;
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    /// The error itself, without the `InsideMap` and `InsideArray` wrappers
+    pub fn innermost(&self) -> &LoadError {
+        match self {
+            Self::InsideMap(_, inner) | Self::InsideArray(_, inner) => inner.innermost(),
+            other => other,
+        }
+    }
+
+    /// The outermost `LoadError` in the chain of an error, if converting the JSON to a ZObject is what failed
+    pub fn find_in(error: &anyhow::Error) -> Option<&LoadError> {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<LoadError>())
+    }
+}
@@ -1,4 +1,6 @@
 mod load_error;
-pub use load_error::LoadError;
+pub use load_error::{JsonPathSegment, LoadError};
 
+pub mod compact;
 pub mod parse_json;
+pub mod serialize_json;
@@ -97,6 +97,11 @@ mod tests {
         );
         assert_eq!(parse_str("p4").unwrap(), WfString::new("p4").into_wf_data());
         assert_eq!(parse_str("").unwrap(), WfString::new("").into_wf_data());
+        // not a valid reference, due to the leading zero
+        assert_eq!(
+            parse_str("Z01").unwrap(),
+            WfString::new("Z01").into_wf_data()
+        );
     }
 
     #[test]
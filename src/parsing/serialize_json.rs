@@ -0,0 +1,125 @@
+//! Convert WfData back into the canonical JSON notation of ZObjects (the inverse of parse_json).
+
+use serde::{
+    Serialize, Serializer,
+    ser::{SerializeMap, SerializeSeq},
+};
+use sonic_rs::Value;
+
+use crate::{
+    Zid,
+    data_types::{MaybeEvaluated, WfData, WfDataType, types_def::WfTypeGeneric},
+};
+
+/// Serialize the wrapped data, with keys in the order given by list_keys (so Z1K1 come first).
+///
+/// Does not evaluate. Object with an identity (such as functions, boolean and standard types) are serialized as a reference to themselves.
+pub struct SerializeWfData<'l>(pub &'l WfData);
+
+impl Serialize for SerializeWfData<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            WfData::WfReference(reference) => serializer.serialize_str(&reference.to.to_string()),
+            WfData::WfString(string) => {
+                // a string that looks like a reference need to be explicitly typed
+                if Zid::from_str(&string.text).is_ok() {
+                    let mut map = serializer.serialize_map(Some(2))?;
+                    map.serialize_entry("Z1K1", "Z6")?;
+                    map.serialize_entry("Z6K1", &*string.text)?;
+                    map.end()
+                } else {
+                    serializer.serialize_str(&string.text)
+                }
+            }
+            WfData::WfBoolean(boolean) => {
+                serializer.serialize_str(if boolean.value { "Z41" } else { "Z42" })
+            }
+            WfData::WfFunction(function) => {
+                serializer.serialize_str(&function.0.identity.to_string())
+            }
+            WfData::WfType(WfTypeGeneric::WfStandardType(standard)) => {
+                serializer.serialize_str(&standard.inner.identity_ref.to_string())
+            }
+            WfData::WfTypedList(list) => {
+                let mut seq = serializer.serialize_seq(Some(list.len() + 1))?;
+                match &*list.inner_type {
+                    MaybeEvaluated::Unchecked(r#type) => {
+                        seq.serialize_element(&SerializeWfData(r#type))?
+                    }
+                    MaybeEvaluated::Valid(r#type) => {
+                        seq.serialize_element(&SerializeWfData(&r#type.clone().into_wf_data()))?
+                    }
+                }
+                for entry in list.iter() {
+                    seq.serialize_element(&SerializeWfData(&entry))?;
+                }
+                seq.end()
+            }
+            other => {
+                let keys = other.list_keys();
+                let mut map = serializer.serialize_map(Some(keys.len()))?;
+                for key in keys {
+                    if let Some(value) = other.get_key(key) {
+                        map.serialize_entry(&key.to_string(), &SerializeWfData(&value))?;
+                    }
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+pub fn serialize_value(data: &WfData) -> Value {
+    sonic_rs::to_value(&SerializeWfData(data)).expect("serializing WfData shouldn’t fail")
+}
+
+pub fn serialize_to_string(data: &WfData) -> String {
+    sonic_rs::to_string(&SerializeWfData(data)).expect("serializing WfData shouldn’t fail")
+}
+
+pub fn serialize_to_string_pretty(data: &WfData) -> String {
+    sonic_rs::to_string_pretty(&SerializeWfData(data)).expect("serializing WfData shouldn’t fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use sonic_rs::Value;
+
+    use crate::{
+        data_types::{WfBoolean, WfDataType, WfString},
+        parsing::{
+            parse_json::parse_value,
+            serialize_json::{serialize_to_string, serialize_value},
+        },
+    };
+
+    fn round_trip(json: &str) {
+        let value: Value = sonic_rs::from_str(json).unwrap();
+        let data = parse_value(&value).unwrap();
+        assert_eq!(serialize_value(&data), value);
+        assert_eq!(parse_value(&serialize_value(&data)).unwrap(), data);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(r#""Z41""#);
+        round_trip(r#""hello""#);
+        round_trip(r#"{"Z1K1": "Z6", "Z6K1": "Z4"}"#);
+        round_trip(r#"["Z6", "a", "b"]"#);
+        round_trip(
+            r#"{"Z1K1": "Z7", "Z7K1": "Z844", "Z844K1": "Z41", "Z844K2": {"Z1K1": "Z18", "Z18K1": "Z10K1"}}"#,
+        );
+    }
+
+    #[test]
+    fn test_serialize_specialised() {
+        assert_eq!(
+            serialize_to_string(&WfBoolean::new(false).into_wf_data()),
+            r#""Z42""#
+        );
+        assert_eq!(
+            serialize_to_string(&WfString::new("Z1").into_wf_data()),
+            r#"{"Z1K1":"Z6","Z6K1":"Z1"}"#
+        );
+    }
+}
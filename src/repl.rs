@@ -0,0 +1,321 @@
+//! An interactive session over a loaded global context, as used by the `repl` binary.
+//!
+//! Each line is either a command (starting with `:`, see `:help`), or a ZObject to evaluate, in canonical JSON or in
+//! the compact syntax (see `parsing::compact`).
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use crate::{
+    EvalError, ExecutionContext, Zid,
+    data_types::{WfData, WfDataType, WfFunction, WfTypedList},
+    parsing::{
+        compact::{Aliases, parse_compact, print_compact},
+        parse_json::parse_value,
+        serialize_json::serialize_to_string,
+    },
+    replay::generate_replay,
+    util::{catch_panic, panic_message},
+};
+
+/// Serialized data longer than that are truncated when printed
+pub const MAX_OUTPUT_LENGTH: usize = 2000;
+/// Same, for values printed on a single line among others (such as with `:keys`)
+const MAX_INLINE_OUTPUT_LENGTH: usize = 200;
+
+const HELP: &str = "\
+<ZObject>               evaluate the ZObject, in JSON or compact syntax (such as Z802(Z41, \"a\", \"b\"))
+:keys <ZObject>         list the keys of the ZObject (without evaluating it)
+:implementations <ZID>  list the implementations of the function
+:testers <ZID>          list the testers of the function
+:trace on|off           whether to print the trace of errors
+:compact on|off         whether to print values in compact syntax rather than JSON
+:alias <label> <ZID>    allow using the label in place of the ZID in compact syntax
+:replay                 show the replay of the last error
+:help                   show this help
+:quit                   exit (in the repl binary)";
+
+pub struct Repl {
+    context: ExecutionContext,
+    tracing: bool,
+    compact: bool,
+    aliases: Aliases,
+    /// The evaluated input and its error
+    last_error: Option<(WfData, EvalError)>,
+}
+
+/// Truncate the text (at a character boundary) to at most max_length bytes, noting how much was removed
+pub fn bounded(text: String, max_length: usize) -> String {
+    if text.len() <= max_length {
+        return text;
+    }
+    let mut end = max_length;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}… ({} more bytes)", &text[..end], text.len() - end)
+}
+
+impl Repl {
+    pub fn new(context: ExecutionContext) -> Self {
+        Self {
+            context,
+            tracing: false,
+            compact: false,
+            aliases: Aliases::new(),
+            last_error: None,
+        }
+    }
+
+    /// Process a line of input, returning the text to print
+    pub fn process_line(&mut self, line: &str) -> String {
+        let line = line.trim();
+        if line.is_empty() {
+            return String::new();
+        }
+        let (command, argument) = match line.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (line, ""),
+        };
+        let result = match command {
+            ":help" => Ok(HELP.to_string()),
+            ":keys" => self.keys(argument),
+            ":implementations" => self.function_list(argument, true),
+            ":testers" => self.function_list(argument, false),
+            ":trace" => match argument {
+                "on" => {
+                    self.tracing = true;
+                    Ok("tracing enabled".to_string())
+                }
+                "off" => {
+                    self.tracing = false;
+                    Ok("tracing disabled".to_string())
+                }
+                _ => Err("expected :trace on or :trace off".to_string()),
+            },
+            ":compact" => match argument {
+                "on" => {
+                    self.compact = true;
+                    Ok("printing in compact syntax".to_string())
+                }
+                "off" => {
+                    self.compact = false;
+                    Ok("printing in JSON".to_string())
+                }
+                _ => Err("expected :compact on or :compact off".to_string()),
+            },
+            ":alias" => self.alias(argument),
+            ":replay" => self.replay(),
+            other if other.starts_with(':') => Err(format!("unknown command {}, see :help", other)),
+            _ => Ok(self.evaluate(line)),
+        };
+        match result {
+            Ok(text) => text,
+            Err(message) => format!("error: {}", message),
+        }
+    }
+
+    fn parse_input(&self, text: &str) -> Result<WfData, String> {
+        if let Ok(zid) = Zid::from_str(text) {
+            return Ok(WfData::new_reference(zid));
+        }
+        match sonic_rs::from_str(text) {
+            Ok(value) => parse_value(&value).map_err(|e| format!("invalid ZObject: {}", e)),
+            Err(json_error) => parse_compact(text, &self.aliases).map_err(|compact_error| {
+                format!(
+                    "invalid JSON ({}) or compact syntax ({})",
+                    json_error, compact_error
+                )
+            }),
+        }
+    }
+
+    /// Serialize in the format chosen with `:compact`
+    fn show(&self, data: &WfData) -> String {
+        if self.compact {
+            print_compact(data, &self.aliases)
+        } else {
+            serialize_to_string(data)
+        }
+    }
+
+    fn alias(&mut self, argument: &str) -> Result<String, String> {
+        let (label, zid) = argument
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| "expected :alias <label> <ZID>".to_string())?;
+        let zid = Zid::from_str(zid.trim()).map_err(|e| format!("invalid ZID: {}", e))?;
+        self.aliases.insert(label, zid).map_err(|e| e.to_string())?;
+        Ok(format!("{} is now {}", label, zid))
+    }
+
+    fn evaluate(&mut self, text: &str) -> String {
+        let input = match self.parse_input(text) {
+            Ok(v) => v,
+            Err(message) => return format!("error: {}", message),
+        };
+        let evaluation_context = self.context.new_fresh();
+        match catch_panic(|| {
+            input
+                .clone()
+                .evaluate(&evaluation_context)
+                .map_err(|(e, _)| e)
+        }) {
+            Ok(value) => {
+                self.last_error = None;
+                bounded(self.show(&value), MAX_OUTPUT_LENGTH)
+            }
+            Err(e) => {
+                let text = if self.tracing {
+                    format!("error: {}", e.render_trace())
+                } else {
+                    format!("error: {} (use :trace on to see where)", e.get_kind())
+                };
+                self.last_error = Some((input, e));
+                text
+            }
+        }
+    }
+
+    fn keys(&self, argument: &str) -> Result<String, String> {
+        let input = self.parse_input(argument)?;
+        // listing the keys of malformed data may panic, which is a bug in the interpreter
+        catch_panic(|| {
+            let data = match input {
+                WfData::WfReference(reference) => {
+                    self.context.get_global().get_object_value(&reference.to)?
+                }
+                other => other,
+            };
+            if let WfData::WfTypedList(list) = &data {
+                return Ok(format!("a list of {} elements", list.len()));
+            }
+            Ok(data
+                .list_keys()
+                .into_iter()
+                .filter_map(|key| {
+                    data.get_key(key).map(|value| {
+                        format!(
+                            "{}: {}",
+                            key,
+                            bounded(self.show(&value), MAX_INLINE_OUTPUT_LENGTH)
+                        )
+                    })
+                })
+                .collect::<Vec<_>>()
+                .join("\n"))
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    fn function_list(&self, argument: &str, implementations: bool) -> Result<String, String> {
+        let zid = Zid::from_str(argument).map_err(|e| format!("expected a function ZID: {}", e))?;
+        let evaluation_context = self.context.new_fresh();
+        let list = catch_panic(|| {
+            let function = WfData::new_reference(zid)
+                .evaluate(&evaluation_context)
+                .and_then(|v| WfFunction::parse(v, &evaluation_context))
+                .map_err(|(e, _)| e)?;
+            if implementations {
+                Ok(function.0.implementations.clone())
+            } else {
+                function
+                    .0
+                    .testers
+                    .clone()
+                    .evaluate(&evaluation_context)
+                    .and_then(|v| WfTypedList::parse(v, &evaluation_context))
+                    .map_err(|(e, _)| e)
+            }
+        })
+        .map_err(|e| e.to_string())?;
+        if list.is_empty() {
+            return Ok("(none)".to_string());
+        }
+        Ok(list
+            .iter()
+            .map(|entry| bounded(self.show(&entry), MAX_INLINE_OUTPUT_LENGTH))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    fn replay(&self) -> Result<String, String> {
+        let (input, error) = self
+            .last_error
+            .as_ref()
+            .ok_or_else(|| "no error to replay".to_string())?;
+        // the replay may panic if it doesn’t match the evaluation, which is a bug in the interpreter
+        let replay_context = self.context.new_fresh();
+        let replay = catch_unwind(AssertUnwindSafe(|| {
+            generate_replay(input.clone(), &replay_context, error)
+        }))
+        .map_err(|payload| format!("replay panicked: {}", panic_message(&*payload)))?;
+        Ok(format!(
+            "{}failing data: {}\n{}",
+            replay.pretty_trace(),
+            bounded(self.show(&replay.root), MAX_OUTPUT_LENGTH),
+            error.get_kind()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExecutionContext, RcI,
+        repl::{Repl, bounded},
+    };
+
+    #[test]
+    fn test_repl() {
+        let mut repl = Repl::new(ExecutionContext::default_for_global(RcI::new(
+            crate::fuzzing::fuzzing_global_context(),
+        )));
+
+        assert_eq!(
+            repl.process_line(r#"{"Z1K1": "Z7", "Z7K1": "Z10001", "Z10001K1": "Z41"}"#),
+            r#""Z42""#
+        );
+        assert!(repl.process_line("Z10011").contains("Z844"));
+        assert!(
+            repl.process_line(":keys Z10001")
+                .starts_with("Z1K1: \"Z8\"\nZ8K1: ")
+        );
+        assert_eq!(repl.process_line(":implementations Z10001"), r#""Z10011""#);
+        assert_eq!(repl.process_line(":testers Z10001"), "(none)");
+
+        assert_eq!(repl.process_line(":replay"), "error: no error to replay");
+        let failing = r#"{"Z1K1": "Z7", "Z7K1": "Z10001", "Z10001K1": "Z12345"}"#;
+        assert!(repl.process_line(failing).contains("use :trace on"));
+        repl.process_line(":trace on");
+        assert!(
+            repl.process_line(failing)
+                .contains("after substitution for function Z10001")
+        );
+        let replay = repl.process_line(":replay");
+        assert!(replay.ends_with("Persistent object Z12345 does not exist"));
+
+        assert!(
+            repl.process_line(":unknown")
+                .starts_with("error: unknown command")
+        );
+        assert!(repl.process_line("{").starts_with("error: invalid JSON"));
+
+        assert_eq!(repl.process_line("Z10001(Z10001(Z41))"), r#""Z41""#);
+        assert!(repl.process_line("not(Z41)").contains("known alias"));
+        assert_eq!(repl.process_line(":alias not Z10001"), "not is now Z10001");
+        repl.process_line(":compact on");
+        assert_eq!(repl.process_line("not(Z41)"), "Z42");
+        assert_eq!(
+            repl.process_line(r#"{"Z1K1": "Z7", "Z7K1": "Z10001", "Z10001K1": "Z41"}"#),
+            "Z42"
+        );
+        assert!(
+            repl.process_line(":keys Z10011")
+                .contains("Z14K2: Z844($Z10001K1, Z42)")
+        );
+    }
+
+    #[test]
+    fn test_bounded() {
+        assert_eq!(bounded("abc".to_string(), 3), "abc");
+        assert_eq!(bounded("aéc".to_string(), 2), "a… (3 more bytes)");
+    }
+}
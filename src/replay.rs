@@ -88,14 +88,26 @@ pub fn generate_replay(
 ) -> ReplayResult {
     let mut current = input;
     let mut full_trace = Vec::new();
+    // the data `current` pointed to before each step that moved it, with the length of full_trace at that time
+    let mut containers = Vec::new();
     let mut ongoing_substitution = None;
 
     let mut iterator = error.get_trace().iter().rev();
     // will iterate from higher level to lower level
     while let Some(step) = iterator.next() {
         //println!("{:?}: {:?}", step, current);
+        if !matches!(step, TraceEntry::ProcessingNonCompositionFunction(_)) {
+            containers.push((full_trace.len(), current.clone()));
+        }
         match step {
             TraceEntry::InsideKey(key) => {
+                // the evaluator follow references without recording it in the trace
+                if let WfData::WfReference(reference) = &current {
+                    current = context
+                        .get_global()
+                        .get_object_value(&reference.to)
+                        .unwrap();
+                }
                 current = current.get_key(*key).unwrap();
                 full_trace.push(FullTraceEntry::InsideKey(*key, current.clone()))
             }
@@ -178,6 +190,24 @@ pub fn generate_replay(
         }
     }
 
+    // errors raised on malformed data depend on what the data was parsed as, so the data reached may evaluate fine
+    // (or fail differently) on its own. Then, the root is the nearest data containing it that fail with the same kind.
+    let kind = error.get_kind();
+    let reproduces = |data: &WfData| {
+        data.clone()
+            .evaluate(context)
+            .is_err_and(|(e, _)| e.get_kind().is_same_kind(kind))
+    };
+    if ongoing_substitution.is_none()
+        && kind.is_raised_on_malformed_data()
+        && !reproduces(&current)
+        && let Some(pos) = containers.iter().rposition(|(_, data)| reproduces(data))
+    {
+        let (trace_len, container) = containers.swap_remove(pos);
+        full_trace.truncate(trace_len);
+        current = container;
+    }
+
     //NOTE: this is just for debug. Might be turned off eventually.
     if ongoing_substitution.is_none() {
         match error.get_kind() {
@@ -193,9 +223,14 @@ pub fn generate_replay(
             }
             EvalErrorKind::TestCaseFailedWithFalse(_) => (),
             EvalErrorKind::Unimplemented(_) => (),
-            EvalErrorKind::WrongType(_expected, _got) => (),
-            _ => {
-                let _ = current.clone().evaluate(context).unwrap_err();
+            kind => {
+                let (root_error, _) = current.clone().evaluate(context).unwrap_err();
+                assert!(
+                    root_error.get_kind().is_same_kind(kind),
+                    "replay root failed with {}, expected {}",
+                    root_error,
+                    kind
+                );
             }
         };
     };
@@ -212,9 +247,9 @@ mod tests {
 
     use crate::{
         EvalErrorKind, ExecutionContext, GlobalContext, RcI,
-        data_types::{WfBoolean, WfData, WfDataType},
+        data_types::{WfBoolean, WfData, WfDataType, WfString},
         eval_error::TraceEntry,
-        replay::generate_replay,
+        replay::{FullTraceEntry, generate_replay},
     };
 
     #[test]
@@ -251,4 +286,39 @@ mod tests {
         let replay_result = generate_replay(unparsed.clone(), &context, &err.0);
         assert_eq!(replay_result.root, WfData::unvalid(EvalErrorKind::TestData));
     }
+
+    #[test]
+    fn test_replay_malformed_data() {
+        let global_context = GlobalContext::default_for_test();
+        let context = ExecutionContext::default_for_global(RcI::new(global_context));
+
+        // a string isn’t a boolean, but evaluate fine on its own
+        let unparsed = WfData::from_map(btree_map! {
+            keyindex!(1, 1) => WfData::new_reference(zid!(7)),
+            keyindex!(7, 1) => WfData::new_reference(zid!(844)),
+            keyindex!(844, 1) => WfString::new("true").into_wf_data(),
+            keyindex!(844, 2) => WfBoolean::new(true).into_wf_data(),
+        });
+        let err = unparsed.clone().evaluate(&context).unwrap_err();
+        assert_eq!(
+            err.0.get_kind(),
+            &EvalErrorKind::WrongType(zid!(6), zid!(40))
+        );
+        assert_eq!(
+            err.0.get_trace(),
+            &vec![
+                TraceEntry::InsideKey(keyindex!(1, 1)),
+                TraceEntry::InsideKey(keyindex!(844, 1)),
+                TraceEntry::ProcessingNonCompositionFunction(zid!(844)),
+            ]
+        );
+
+        let replay_result = generate_replay(unparsed.clone(), &context, &err.0);
+        // the call, not the string
+        assert_eq!(replay_result.root, unparsed);
+        assert!(matches!(
+            replay_result.full_trace[..],
+            [FullTraceEntry::ProcessingNonCompositionFunction(function)] if function == zid!(844)
+        ));
+    }
 }
@@ -0,0 +1,119 @@
+//! Handling of the orchestrator evaluate endpoint, as served on localhost by the `evaluation_server` binary.
+//!
+//! The request body is a Z7 function call, either directly or in the `zobject` field (as sent to the official
+//! orchestrator). The response is the Z22 evaluation result (see [`crate::evaluation_result`]).
+use sonic_rs::{JsonValueTrait, Value};
+
+use crate::{
+    ExecutionContext,
+    data_types::{WfData, WfDataType},
+    evaluation_result::evaluate_with_metadata,
+    parsing::parse_json::parse_value,
+};
+
+pub const EVALUATE_PATHS: &[&str] = &["/1/v1/evaluate", "/1/v1/evaluate/"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    /// JSON for successful request, plain text otherwise
+    pub body: String,
+}
+
+impl Response {
+    fn error(status: u16, message: String) -> Self {
+        Self {
+            status,
+            body: message,
+        }
+    }
+}
+
+/// Each evaluation use a fresh context created from the given one, so they share its limits but not its counters.
+pub fn handle_request(
+    method: &str,
+    path: &str,
+    body: &str,
+    context: &ExecutionContext,
+) -> Response {
+    // the query string (such as a cache buster) doesn’t change what is evaluated
+    let path = path.split_once('?').map_or(path, |(path, _)| path);
+    if !EVALUATE_PATHS.contains(&path) {
+        return Response::error(404, format!("unknown path {}", path));
+    }
+    if method != "POST" {
+        return Response::error(405, format!("expected a POST request, got {}", method));
+    }
+
+    let request: Value = match sonic_rs::from_str(body) {
+        Ok(v) => v,
+        Err(e) => return Response::error(400, format!("invalid JSON: {}", e)),
+    };
+    let function_call = match request.get("zobject") {
+        Some(zobject) => parse_value(zobject),
+        None => parse_value(&request),
+    };
+    let function_call = match function_call {
+        Ok(v) => v,
+        Err(e) => return Response::error(400, format!("invalid ZObject: {}", e)),
+    };
+    let is_function_call = match &function_call {
+        WfData::WfUntyped(untyped) => matches!(
+            untyped.get_key(keyindex!(1, 1)),
+            Some(WfData::WfReference(r)) if r.to == zid!(7)
+        ),
+        _ => false,
+    };
+    if !is_function_call {
+        return Response::error(400, "expected a Z7 function call".to_string());
+    }
+
+    Response {
+        status: 200,
+        body: evaluate_with_metadata(function_call, context).to_json(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sonic_rs::{JsonValueTrait, Value};
+
+    use crate::{ExecutionContext, RcI, server::handle_request};
+
+    #[test]
+    fn test_handle_request() {
+        let context = ExecutionContext::default_for_global(RcI::new(
+            crate::fuzzing::fuzzing_global_context(),
+        ));
+        let call = r#"{"Z1K1": "Z7", "Z7K1": "Z10001", "Z10001K1": "Z41"}"#;
+
+        for (path, body) in [
+            ("/1/v1/evaluate/", call.to_string()),
+            ("/1/v1/evaluate/", format!(r#"{{"zobject": {}}}"#, call)),
+            ("/1/v1/evaluate?uselang=en", call.to_string()),
+        ] {
+            let response = handle_request("POST", path, &body, &context);
+            assert_eq!(response.status, 200);
+            let result: Value = sonic_rs::from_str(&response.body).unwrap();
+            assert_eq!(result["Z22K1"].as_str(), Some("Z42"));
+        }
+
+        assert_eq!(
+            handle_request("POST", "/1/v1/evaluate/", r#""Z41""#, &context).status,
+            400
+        );
+        assert_eq!(
+            handle_request("POST", "/1/v1/evaluate/", "{", &context).status,
+            400
+        );
+        assert_eq!(
+            handle_request("GET", "/1/v1/evaluate/", call, &context).status,
+            405
+        );
+        assert_eq!(handle_request("POST", "/", call, &context).status, 404);
+        assert_eq!(
+            handle_request("POST", "/?/1/v1/evaluate", call, &context).status,
+            404
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::BufRead,
+};
+
+use anyhow::Context;
+
+use crate::test_runner::{TestCaseOutcome, TestCaseRecord, TestStatus};
+
+/// Read a JSON Lines report (as written with `ReportFormat::JsonLines`), returning the status of each test (by their key)
+pub fn load_baseline<R: BufRead>(reader: R) -> Result<BTreeMap<String, TestStatus>, anyhow::Error> {
+    let mut result = BTreeMap::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line.context("reading baseline")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: TestCaseRecord = serde_json::from_str(&line)
+            .with_context(|| format!("parsing line {} of baseline", line_number + 1))?;
+        result.insert(record.key, record.status);
+    }
+    Ok(result)
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct BaselineComparison {
+    /// Passed in the baseline, doesn’t pass anymore
+    pub newly_failing: Vec<String>,
+    /// Didn’t crash in the baseline, now crash
+    pub newly_crashing: Vec<String>,
+    /// Didn’t pass in the baseline, now pass
+    pub newly_passing: Vec<String>,
+    /// Not present in the baseline
+    pub new_tests: Vec<String>,
+    /// Present in the baseline, but were not run
+    pub missing_tests: Vec<String>,
+}
+
+impl BaselineComparison {
+    /// Only tests that got worse are regressions. New or missing tests aren’t.
+    pub fn has_regressions(&self) -> bool {
+        !self.newly_failing.is_empty() || !self.newly_crashing.is_empty()
+    }
+}
+
+pub fn compare_with_baseline(
+    baseline: &BTreeMap<String, TestStatus>,
+    outcomes: &[TestCaseOutcome],
+) -> BaselineComparison {
+    let mut result = BaselineComparison::default();
+    let mut seen = BTreeSet::new();
+    for outcome in outcomes {
+        let key = outcome.key();
+        let status = outcome.status();
+        match baseline.get(&key) {
+            None => result.new_tests.push(key.clone()),
+            Some(previous) if *previous != TestStatus::Crashed && status == TestStatus::Crashed => {
+                result.newly_crashing.push(key.clone())
+            }
+            Some(TestStatus::Passed) if status != TestStatus::Passed => {
+                result.newly_failing.push(key.clone())
+            }
+            Some(previous) if *previous != TestStatus::Passed && status == TestStatus::Passed => {
+                result.newly_passing.push(key.clone())
+            }
+            Some(_) => (),
+        }
+        seen.insert(key);
+    }
+    for key in baseline.keys() {
+        if !seen.contains(key) {
+            result.missing_tests.push(key.clone());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        EvalError, EvalErrorKind, Zid,
+        test_runner::{TestCaseOutcome, compare_with_baseline, load_baseline},
+    };
+
+    fn outcome(test: Zid, result: Result<(), EvalError>) -> TestCaseOutcome {
+        TestCaseOutcome {
+            test,
+            function: None,
+            implementation: None,
+            duration: Duration::ZERO,
+            result,
+        }
+    }
+
+    #[test]
+    fn test_compare_with_baseline() {
+        let baseline = load_baseline(
+            concat!(
+                r#"{"key":"Z1001","test":"Z1001","function":null,"implementation":null,"status":"passed","duration_ms":1.0,"error_kind":null,"message":null,"trace":null}"#,
+                "\n",
+                r#"{"key":"Z1002","test":"Z1002","function":null,"implementation":null,"status":"errored","duration_ms":1.0,"error_kind":"TestData","message":"","trace":""}"#,
+                "\n",
+                r#"{"key":"Z1003","test":"Z1003","function":null,"implementation":null,"status":"passed","duration_ms":1.0,"error_kind":null,"message":null,"trace":null}"#,
+                "\n",
+                r#"{"key":"Z1005","test":"Z1005","function":null,"implementation":null,"status":"errored","duration_ms":1.0,"error_kind":"TestData","message":"","trace":""}"#,
+                "\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let outcomes = vec![
+            outcome(
+                zid!(1001),
+                Err(EvalError::from_kind(EvalErrorKind::TestData)),
+            ),
+            outcome(zid!(1002), Ok(())),
+            outcome(
+                zid!(1004),
+                Err(EvalError::from_kind(EvalErrorKind::TestData)),
+            ),
+            outcome(
+                zid!(1005),
+                Err(EvalError::from_kind(EvalErrorKind::InternalPanic(
+                    "oops".to_string(),
+                ))),
+            ),
+        ];
+
+        let comparison = compare_with_baseline(&baseline, &outcomes);
+        assert_eq!(comparison.newly_failing, vec!["Z1001".to_string()]);
+        assert_eq!(comparison.newly_crashing, vec!["Z1005".to_string()]);
+        assert_eq!(comparison.newly_passing, vec!["Z1002".to_string()]);
+        assert_eq!(comparison.new_tests, vec!["Z1004".to_string()]);
+        assert_eq!(comparison.missing_tests, vec!["Z1003".to_string()]);
+        assert!(comparison.has_regressions());
+
+        // a new failing test isn’t a regression
+        let comparison = compare_with_baseline(&baseline, &outcomes[1..3]);
+        assert!(!comparison.has_regressions());
+    }
+}
@@ -0,0 +1,201 @@
+//! Utilities to run test cases (Z20) in bulk and report their results.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    EvalError, EvalErrorKind, ExecutionContext, Zid,
+    data_types::{WfDataType, WfFunction, WfTestCase},
+    util::catch_panic,
+};
+
+mod report;
+pub use report::{ReportFormat, TestCaseRecord, write_report};
+
+mod baseline;
+pub use baseline::{BaselineComparison, compare_with_baseline, load_baseline};
+
+#[cfg(feature = "multithread")]
+mod parallel;
+#[cfg(feature = "multithread")]
+pub use parallel::run_test_cases_parallel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    /// The validator returned false
+    Failed,
+    /// An error occurred while running the test
+    Errored,
+    /// The interpreter panicked while running the test (that’s a bug in the interpreter)
+    Crashed,
+}
+
+/// The result of running a single test case, alongside some metadata
+#[derive(Debug)]
+pub struct TestCaseOutcome {
+    pub test: Zid,
+    /// None if the test case couldn’t even be parsed
+    pub function: Option<Zid>,
+    /// Only set if the test was run with a forced implementation
+    pub implementation: Option<Zid>,
+    pub duration: Duration,
+    pub result: Result<(), EvalError>,
+}
+
+impl TestCaseOutcome {
+    pub fn status(&self) -> TestStatus {
+        match &self.result {
+            Ok(()) => TestStatus::Passed,
+            Err(e) => match e.get_kind() {
+                EvalErrorKind::TestCaseFailedWithFalse(_) => TestStatus::Failed,
+                EvalErrorKind::InternalPanic(_) => TestStatus::Crashed,
+                _ => TestStatus::Errored,
+            },
+        }
+    }
+
+    /// Identify this run of a test in reports and baselines, as the test ZID, with the implementation if forced (e.g. `Z8010@Z901`)
+    pub fn key(&self) -> String {
+        match self.implementation {
+            Some(implementation) => format!("{}@{}", self.test, implementation),
+            None => self.test.to_string(),
+        }
+    }
+}
+
+/// Load the test case from the global context and run it. Never stop at the first error, that is stored in the outcome instead.
+///
+/// Panics are caught, and reported as an `EvalErrorKind::InternalPanic` error, so one bad ZObject doesn’t stop a bulk run.
+pub fn run_test_case(test: Zid, context: &ExecutionContext) -> TestCaseOutcome {
+    run_test_case_inner(test, None, context)
+}
+
+/// Like run_test_case, but `function` is forced to use `implementation` for the tested call (see `WfTestCase::run_test_with_forced_implementation`)
+pub fn run_test_case_with_forced_implementation(
+    test: Zid,
+    function: Zid,
+    implementation: Zid,
+    context: &ExecutionContext,
+) -> TestCaseOutcome {
+    run_test_case_inner(test, Some((function, implementation)), context)
+}
+
+fn run_test_case_inner(
+    test: Zid,
+    forced: Option<(Zid, Zid)>,
+    context: &ExecutionContext,
+) -> TestCaseOutcome {
+    let start = Instant::now();
+    let mut function = None;
+    let result = catch_panic(|| match WfTestCase::load(test, context) {
+        Ok(test_case) => {
+            function = Some(test_case.0.function.0.identity);
+            match forced {
+                Some((forced_function, implementation)) => test_case
+                    .run_test_with_forced_implementation(forced_function, implementation, context),
+                None => test_case.run_test(context),
+            }
+        }
+        Err(e) => Err(e),
+    });
+    TestCaseOutcome {
+        test,
+        function,
+        implementation: forced.map(|(_, implementation)| implementation),
+        duration: start.elapsed(),
+        result,
+    }
+}
+
+/// Run every tester of the function against every of its implementations (see `WfFunction::run_testers`).
+///
+/// If the function can’t be loaded, or its testers and implementations listed, a single outcome with the function
+/// itself as the test holds the error, so it still appears in reports and baseline comparisons.
+pub fn run_function_testers(function: Zid, context: &ExecutionContext) -> Vec<TestCaseOutcome> {
+    let start = Instant::now();
+    let matrix = catch_panic(|| {
+        let value = context
+            .get_global()
+            .get_object_value(&function)?
+            .evaluate(context)
+            .map_err(|(e, _)| e)?;
+        WfFunction::parse(value, context)
+            .map_err(|(e, _)| e)?
+            .run_testers(context)
+    });
+    match matrix {
+        Ok(matrix) => matrix.into_outcomes().collect(),
+        Err(e) => vec![TestCaseOutcome {
+            test: function,
+            function: Some(function),
+            implementation: None,
+            duration: start.elapsed(),
+            result: Err(e),
+        }],
+    }
+}
+
+/// Result of running every tester of a function against every of its implementations.
+#[derive(Debug)]
+pub struct TestersMatrix {
+    pub function: Zid,
+    pub testers: Vec<Zid>,
+    pub implementations: Vec<Zid>,
+    /// indexed as `results[tester_pos][implementation_pos]`
+    pub results: Vec<Vec<TestCaseOutcome>>,
+}
+
+impl TestersMatrix {
+    pub fn get(&self, tester: Zid, implementation: Zid) -> Option<&TestCaseOutcome> {
+        let tester_pos = self.testers.iter().position(|x| *x == tester)?;
+        let implementation_pos = self
+            .implementations
+            .iter()
+            .position(|x| *x == implementation)?;
+        Some(&self.results[tester_pos][implementation_pos])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TestCaseOutcome> {
+        self.results.iter().flatten()
+    }
+
+    pub fn into_outcomes(self) -> impl Iterator<Item = TestCaseOutcome> {
+        self.results.into_iter().flatten()
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.iter().all(|outcome| outcome.result.is_ok())
+    }
+
+    pub fn count_passed(&self) -> usize {
+        self.iter().filter(|outcome| outcome.result.is_ok()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EvalErrorKind, ExecutionContext, RcI,
+        fuzzing::fuzzing_global_context,
+        test_runner::{TestStatus, run_function_testers},
+    };
+
+    #[test]
+    fn test_run_function_testers_missing_function() {
+        let context = ExecutionContext::default_for_global(RcI::new(fuzzing_global_context()));
+        // Z10001 has no tester
+        assert!(run_function_testers(zid!(10001), &context).is_empty());
+
+        let outcomes = run_function_testers(zid!(99999), &context);
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].test, zid!(99999));
+        assert_eq!(outcomes[0].status(), TestStatus::Errored);
+        assert_eq!(
+            outcomes[0].result.as_ref().unwrap_err().get_kind(),
+            &EvalErrorKind::MissingPersistentObject(zid!(99999))
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::{
+    ExecutionContext, GlobalContext, RcI, Zid,
+    test_runner::{TestCaseOutcome, run_test_case},
+};
+
+/// Run the given tests on `threads` worker threads, all sharing the same immutable GlobalContext.
+/// Each worker build its own ExecutionContext (one per test, so counters are not shared).
+///
+/// Outcomes are returned in the same order as `tests`.
+pub fn run_test_cases_parallel(
+    global_context: RcI<GlobalContext>,
+    tests: &[Zid],
+    threads: usize,
+) -> Vec<TestCaseOutcome> {
+    let next_test = AtomicUsize::new(0);
+
+    let mut outcomes: Vec<(usize, TestCaseOutcome)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                let global_context = global_context.clone();
+                let next_test = &next_test;
+                scope.spawn(move || {
+                    let mut outcomes = Vec::new();
+                    loop {
+                        let pos = next_test.fetch_add(1, Ordering::Relaxed);
+                        let Some(test) = tests.get(pos) else {
+                            break;
+                        };
+                        let context = ExecutionContext::default_for_global(global_context.clone());
+                        outcomes.push((pos, run_test_case(*test, &context)));
+                    }
+                    outcomes
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("test worker thread panicked"))
+            .collect()
+    });
+
+    outcomes.sort_by_key(|(pos, _)| *pos);
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use map_macro::btree_map;
+
+    use crate::{
+        GlobalContext, RcI, Zid,
+        data_types::{WfData, WfDataType, WfString},
+        test_runner::{TestStatus, run_test_cases_parallel},
+    };
+
+    #[test]
+    fn test_run_parallel() {
+        let mut global_context = GlobalContext::default_for_test();
+        // not valid test cases, but that’s enough to check every test is run and ordered
+        for i in 0..50 {
+            global_context.add_direct_no_persistent_data(
+                Zid::from_u32_panic(20000 + i),
+                WfData::from_map(btree_map! {
+                    keyindex!(1, 1) => WfData::new_reference(zid!(20)),
+                    keyindex!(20, 1) => WfString::new("not a function").into_wf_data(),
+                }),
+            );
+        }
+        let tests: Vec<_> = (0..50).map(|i| Zid::from_u32_panic(20000 + i)).collect();
+
+        let outcomes = run_test_cases_parallel(RcI::new(global_context), &tests, 4);
+        assert_eq!(outcomes.len(), 50);
+        for (outcome, test) in outcomes.iter().zip(tests.iter()) {
+            assert_eq!(outcome.test, *test);
+            assert_eq!(outcome.status(), TestStatus::Errored);
+        }
+    }
+}
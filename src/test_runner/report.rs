@@ -0,0 +1,274 @@
+use std::{collections::BTreeMap, io::Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::test_runner::{TestCaseOutcome, TestStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    JUnit,
+    Tap,
+    JsonLines,
+}
+
+impl ReportFormat {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(text: &str) -> Option<Self> {
+        match text {
+            "junit" => Some(Self::JUnit),
+            "tap" => Some(Self::Tap),
+            "jsonl" => Some(Self::JsonLines),
+            _ => None,
+        }
+    }
+}
+
+/// One line of the JSON Lines report. Also what baselines are read from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestCaseRecord {
+    pub key: String,
+    pub test: String,
+    pub function: Option<String>,
+    pub implementation: Option<String>,
+    pub status: TestStatus,
+    pub duration_ms: f64,
+    pub error_kind: Option<String>,
+    pub message: Option<String>,
+    pub trace: Option<String>,
+}
+
+impl From<&TestCaseOutcome> for TestCaseRecord {
+    fn from(outcome: &TestCaseOutcome) -> Self {
+        let error = outcome.result.as_ref().err();
+        Self {
+            key: outcome.key(),
+            test: outcome.test.to_string(),
+            function: outcome.function.map(|x| x.to_string()),
+            implementation: outcome.implementation.map(|x| x.to_string()),
+            status: outcome.status(),
+            duration_ms: outcome.duration.as_secs_f64() * 1000.0,
+            error_kind: error.map(|e| e.get_kind().name().to_string()),
+            message: error.map(|e| e.get_kind().to_string()),
+            trace: error.map(|e| e.render_trace()),
+        }
+    }
+}
+
+pub fn write_report<W: Write>(
+    format: ReportFormat,
+    outcomes: &[TestCaseOutcome],
+    writer: &mut W,
+) -> std::io::Result<()> {
+    let records: Vec<TestCaseRecord> = outcomes.iter().map(TestCaseRecord::from).collect();
+    match format {
+        ReportFormat::JUnit => write_junit(&records, writer),
+        ReportFormat::Tap => write_tap(&records, writer),
+        ReportFormat::JsonLines => write_json_lines(&records, writer),
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            // not allowed in XML 1.0
+            c if (c as u32) < 0x20 && c != '\n' && c != '\r' && c != '\t' => (),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn count_status(records: &[&TestCaseRecord], status: TestStatus) -> usize {
+    records.iter().filter(|r| r.status == status).count()
+}
+
+/// JUnit doesn’t distinguish crash from errors
+fn count_errors(records: &[&TestCaseRecord]) -> usize {
+    count_status(records, TestStatus::Errored) + count_status(records, TestStatus::Crashed)
+}
+
+fn write_junit<W: Write>(records: &[TestCaseRecord], writer: &mut W) -> std::io::Result<()> {
+    // one test suite per function
+    let mut by_function: BTreeMap<&str, Vec<&TestCaseRecord>> = BTreeMap::new();
+    for record in records {
+        by_function
+            .entry(record.function.as_deref().unwrap_or("unknown"))
+            .or_default()
+            .push(record);
+    }
+    let all: Vec<&TestCaseRecord> = records.iter().collect();
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<testsuites tests="{}" failures="{}" errors="{}" time="{:.3}">"#,
+        all.len(),
+        count_status(&all, TestStatus::Failed),
+        count_errors(&all),
+        records.iter().map(|r| r.duration_ms).sum::<f64>() / 1000.0
+    )?;
+    for (function, records) in by_function {
+        writeln!(
+            writer,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" errors="{}" time="{:.3}">"#,
+            escape_xml(function),
+            records.len(),
+            count_status(&records, TestStatus::Failed),
+            count_errors(&records),
+            records.iter().map(|r| r.duration_ms).sum::<f64>() / 1000.0
+        )?;
+        for record in records {
+            write!(
+                writer,
+                r#"    <testcase name="{}" classname="{}" time="{:.3}""#,
+                escape_xml(&record.key),
+                escape_xml(function),
+                record.duration_ms / 1000.0
+            )?;
+            let tag = match record.status {
+                TestStatus::Passed => {
+                    writeln!(writer, "/>")?;
+                    continue;
+                }
+                TestStatus::Failed => "failure",
+                TestStatus::Errored | TestStatus::Crashed => "error",
+            };
+            writeln!(writer, ">")?;
+            writeln!(
+                writer,
+                r#"      <{} type="{}" message="{}">{}</{}>"#,
+                tag,
+                escape_xml(record.error_kind.as_deref().unwrap_or("")),
+                escape_xml(record.message.as_deref().unwrap_or("")),
+                escape_xml(record.trace.as_deref().unwrap_or("")),
+                tag
+            )?;
+            writeln!(writer, "    </testcase>")?;
+        }
+        writeln!(writer, "  </testsuite>")?;
+    }
+    writeln!(writer, "</testsuites>")?;
+    Ok(())
+}
+
+fn write_tap<W: Write>(records: &[TestCaseRecord], writer: &mut W) -> std::io::Result<()> {
+    writeln!(writer, "TAP version 13")?;
+    writeln!(writer, "1..{}", records.len())?;
+    for (pos, record) in records.iter().enumerate() {
+        let description = match &record.function {
+            Some(function) => format!("{} (function {})", record.key, function),
+            None => record.key.clone(),
+        };
+        if record.status == TestStatus::Passed {
+            writeln!(writer, "ok {} - {}", pos + 1, description)?;
+            continue;
+        }
+        writeln!(writer, "not ok {} - {}", pos + 1, description)?;
+        // YAML diagnostic block. Strings are JSON-encoded, which is valid YAML.
+        writeln!(writer, "  ---")?;
+        writeln!(
+            writer,
+            "  status: {}",
+            serde_json::to_string(&record.status)?
+        )?;
+        if let Some(error_kind) = &record.error_kind {
+            writeln!(writer, "  error_kind: {}", error_kind)?;
+        }
+        if let Some(message) = &record.message {
+            writeln!(writer, "  message: {}", serde_json::to_string(message)?)?;
+        }
+        writeln!(writer, "  duration_ms: {:.3}", record.duration_ms)?;
+        if let Some(trace) = &record.trace {
+            writeln!(writer, "  trace: |")?;
+            for line in trace.lines() {
+                writeln!(writer, "    {}", line)?;
+            }
+        }
+        writeln!(writer, "  ...")?;
+    }
+    Ok(())
+}
+
+fn write_json_lines<W: Write>(records: &[TestCaseRecord], writer: &mut W) -> std::io::Result<()> {
+    for record in records {
+        serde_json::to_writer(&mut *writer, record)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        EvalError, EvalErrorKind,
+        test_runner::{ReportFormat, TestCaseOutcome, TestCaseRecord, write_report},
+    };
+
+    fn sample_outcomes() -> Vec<TestCaseOutcome> {
+        vec![
+            TestCaseOutcome {
+                test: zid!(8010),
+                function: Some(zid!(801)),
+                implementation: None,
+                duration: Duration::from_millis(2),
+                result: Ok(()),
+            },
+            TestCaseOutcome {
+                test: zid!(8011),
+                function: Some(zid!(801)),
+                implementation: Some(zid!(901)),
+                duration: Duration::from_millis(3),
+                result: Err(
+                    EvalError::from_kind(EvalErrorKind::MissingKey(keyindex!(801, 1)))
+                        .inside_key(keyindex!(20, 2)),
+                ),
+            },
+        ]
+    }
+
+    fn render(format: ReportFormat) -> String {
+        let mut output = Vec::new();
+        write_report(format, &sample_outcomes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let output = render(ReportFormat::JsonLines);
+        let records: Vec<TestCaseRecord> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].key, "Z8010");
+        assert_eq!(records[1].key, "Z8011@Z901");
+        assert_eq!(records[1].error_kind.as_deref(), Some("MissingKey"));
+        assert!(records[1].trace.as_ref().unwrap().contains("inside Z20K2"));
+    }
+
+    #[test]
+    fn test_tap() {
+        let output = render(ReportFormat::Tap);
+        assert!(output.starts_with("TAP version 13\n1..2\n"));
+        assert!(output.contains("ok 1 - Z8010 (function Z801)\n"));
+        assert!(output.contains("not ok 2 - Z8011@Z901 (function Z801)\n"));
+        assert!(output.contains("  error_kind: MissingKey\n"));
+    }
+
+    #[test]
+    fn test_junit() {
+        let output = render(ReportFormat::JUnit);
+        assert!(output.contains(r#"<testsuites tests="2" failures="0" errors="1""#));
+        assert!(output.contains(r#"<testsuite name="Z801" tests="2""#));
+        assert!(output.contains(r#"<testcase name="Z8010" classname="Z801""#));
+        assert!(output.contains(r#"<error type="MissingKey""#));
+    }
+}
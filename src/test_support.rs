@@ -0,0 +1,68 @@
+//! Fixtures shared by the unit tests.
+use std::{
+    fs,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+};
+
+use crate::{GlobalContext, KeyIndex, Zid, data_types::WfData, fuzzing::FUZZING_PAGES};
+
+/// Only the [`FUZZING_PAGES`], without the core types nor the builtins.
+pub fn fuzzing_pages_context() -> GlobalContext {
+    let mut global_context = GlobalContext::default();
+    for (title, body) in FUZZING_PAGES {
+        global_context.add_from_json(title, body).unwrap();
+    }
+    global_context
+}
+
+/// A dump (in the Wikimedia XML export format) with the given pages, as ZObjects in the main namespace
+pub fn dump_xml(pages: &[(&str, &str)]) -> String {
+    let mut result = r#"<mediawiki xmlns="http://www.mediawiki.org/xml/export-0.11/">"#.to_string();
+    for (title, body) in pages {
+        result.push_str(&format!(
+            "<page><title>{}</title><ns>0</ns><revision><model>zobject</model><format>application/json</format><text>{}</text></revision></page>",
+            title, body
+        ));
+    }
+    result.push_str("</mediawiki>");
+    result
+}
+
+/// An empty directory in the temporary directory, unique to this process, and removed (with its content) once dropped
+pub struct TempDirectory(PathBuf);
+
+impl TempDirectory {
+    pub fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("interpreter2-test-{}-{}", name, std::process::id()));
+        // left over by a previous process with the same id
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("creating the temporary directory");
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// An unevaluated Z7 call of `function`, with `args` as its keys `K1`, `K2`… in order.
+pub fn function_call(function: Zid, args: Vec<WfData>) -> WfData {
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(keyindex!(1, 1), WfData::new_reference(zid!(7)));
+    map.insert(keyindex!(7, 1), WfData::new_reference(function));
+    for (pos, arg) in args.into_iter().enumerate() {
+        map.insert(
+            KeyIndex::global(function, NonZeroU32::new(pos as u32 + 1).unwrap()),
+            arg,
+        );
+    }
+    WfData::from_map(map)
+}
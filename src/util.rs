@@ -1,3 +1,10 @@
+use std::{
+    any::Any,
+    panic::{AssertUnwindSafe, catch_unwind},
+};
+
+use crate::{EvalError, EvalErrorKind};
+
 pub enum MaybeVec<T> {
     Empty,
     One(T),
@@ -35,3 +42,26 @@ impl<T> MaybeVec<T> {
         }
     }
 }
+
+/// Extract the message of a panic payload, as returned by catch_unwind
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panic with a non-string payload".to_string()
+    }
+}
+
+/// Run the function, converting a panic into an `EvalErrorKind::InternalPanic` error.
+///
+/// Note that the data the function had ownership of is lost on panic.
+pub fn catch_panic<T, F: FnOnce() -> Result<T, EvalError>>(f: F) -> Result<T, EvalError> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(v) => v,
+        Err(payload) => Err(EvalError::from_kind(EvalErrorKind::InternalPanic(
+            panic_message(&*payload),
+        ))),
+    }
+}
@@ -5,6 +5,8 @@ use std::{
 
 use thiserror::Error;
 
+use crate::{KeyIndexParseError, key_index::check_number_syntax};
+
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Copy)]
 pub struct Zid(pub NonZero<u32>);
 
@@ -19,6 +21,10 @@ pub enum ZidParseError {
     CantParse(#[source] ParseIntError),
     #[error("Zid can’t be zero")]
     IsZero(#[source] TryFromIntError),
+    #[error("Zid shouldn’t have leading zeros")]
+    LeadingZero,
+    #[error("Zid shouldn’t have a sign")]
+    SignNotAllowed,
 }
 
 impl Zid {
@@ -37,10 +43,14 @@ impl Zid {
             return Err(ZidParseError::Empty);
         }
 
-        let number: u32 = chars_iter
-            .as_str()
-            .parse()
-            .map_err(ZidParseError::CantParse)?;
+        let number_text = chars_iter.as_str();
+        check_number_syntax(number_text).map_err(|e| match e {
+            KeyIndexParseError::SignNotAllowed => ZidParseError::SignNotAllowed,
+            KeyIndexParseError::LeadingZero => ZidParseError::LeadingZero,
+            other => unreachable!("check_number_syntax returned {:?}", other),
+        })?;
+
+        let number: u32 = number_text.parse().map_err(ZidParseError::CantParse)?;
 
         Self::from_u32(number)
     }
@@ -70,7 +80,11 @@ macro_rules! zid {
 
 #[cfg(test)]
 mod tests {
-    use crate::Zid;
+    use std::num::IntErrorKind;
+
+    use proptest::prelude::*;
+
+    use crate::{KeyIndex, Zid, ZidParseError};
 
     #[test]
     fn test_from_str() {
@@ -80,4 +94,46 @@ mod tests {
         Zid::from_str("").unwrap_err();
         Zid::from_str("K3K").unwrap_err();
     }
+
+    #[test]
+    fn test_reject_non_canonical() {
+        assert_eq!(Zid::from_str("Z04"), Err(ZidParseError::LeadingZero));
+        assert_eq!(Zid::from_str("Z+4"), Err(ZidParseError::SignNotAllowed));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trip(z in 1..=u32::MAX) {
+            let zid = Zid::from_u32(z).unwrap();
+            prop_assert_eq!(Zid::from_str(&zid.to_string()), Ok(zid));
+            prop_assert_eq!(zid.get_z().get(), z);
+            // a ZID is also a valid key
+            prop_assert_eq!(KeyIndex::from_str(&zid.to_string()).unwrap().as_zid(), Some(zid));
+        }
+
+        #[test]
+        fn prop_accepted_is_canonical(text in "[ZK0-9+\\-a]{0,12}") {
+            if let Ok(zid) = Zid::from_str(&text) {
+                prop_assert_eq!(zid.to_string(), text);
+            }
+        }
+
+        #[test]
+        fn prop_ordering(a in 1..=u32::MAX, b in 1..=u32::MAX) {
+            prop_assert_eq!(Zid::from_u32(a).unwrap().cmp(&Zid::from_u32(b).unwrap()), a.cmp(&b));
+        }
+
+        #[test]
+        fn prop_reject_leading_zero(z in 0..=u32::MAX, zeros in "0{1,3}") {
+            prop_assert_eq!(Zid::from_str(&format!("Z{}{}", zeros, z)), Err(ZidParseError::LeadingZero));
+        }
+
+        #[test]
+        fn prop_reject_overflow(z in (u32::MAX as u64 + 1)..=u64::MAX) {
+            match Zid::from_str(&format!("Z{}", z)) {
+                Err(ZidParseError::CantParse(e)) => prop_assert_eq!(e.kind(), &IntErrorKind::PosOverflow),
+                other => prop_assert!(false, "expected overflow, got {:?}", other),
+            }
+        }
+    }
 }